@@ -0,0 +1,14 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // gRPC 接口的代码由 proto 生成，使用内置的 protoc，无需另外安装
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/netwatch.proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc not available");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/netwatch.proto"], &["proto"])
+            .expect("failed to compile proto/netwatch.proto");
+    }
+}
@@ -1,80 +1,1306 @@
-use crate::model::{AlertConfig, Target};
+use crate::deadletter::DeadLetter;
+use crate::model::{
+    AlertConfig, ChannelKind, MqttConfig, NtfyConfig, QuietHours, SmtpConfig, SmtpTls, Target,
+    TargetState, WebhookConfig,
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Local};
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use once_cell::sync::Lazy;
+use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS, Transport};
 use serde_json::json;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+const DEFAULT_EMAIL_SUBJECT: &str = "[NetWatch] {{STATUS}} {{TARGET}}";
+const DEFAULT_EMAIL_BODY: &str =
+    "{{STATUS}} {{TARGET}}\n\nHost: {{HOST}}\nTime: {{TIME}}\n\n{{MESSAGE}}\n";
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+// HTTP 类通道单次投递的超时，接受连接却不响应的端点不会一直占用投递名额
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+// 连接 broker 并完成一次发布 (QoS 1/2 等到确认) 的超时
+const MQTT_TIMEOUT: Duration = Duration::from_secs(10);
+// 告警命令 (如重启服务) 的最长执行时间，超时后终止
+const EXEC_TIMEOUT: Duration = Duration::from_secs(30);
+// 每个通道默认最多尝试的次数 (含首次)
+const DEFAULT_RETRY_ATTEMPTS: u32 = 5;
+const RETRY_BASE: Duration = Duration::from_secs(2);
+const RETRY_MAX_BACKOFF: Duration = Duration::from_secs(300);
+// 告警冷却时间上限 (30 天)，更大的值按上限处理，避免换算与计算恢复时间时溢出
+pub const MAX_COOLDOWN_SECS: u64 = 30 * 24 * 3600;
+// 每个通道在静默时段内最多保留的告警条数，汇总中最多列出的条数
+const MAX_HELD: usize = 200;
+const SUMMARY_MAX_LINES: usize = 50;
+const DISCORD_TITLE_MAX: usize = 256;
+const DISCORD_DESCRIPTION_MAX: usize = 4096;
+// 企业微信 markdown 内容上限为 4096 字节，探测消息按字符截断后留出余量
+const WECOM_DETAIL_MAX: usize = 1000;
+// 未填写 url 时使用的 PagerDuty 事件地址 (EU 账号填写 https://events.eu.pagerduty.com/v2/enqueue)
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const PAGERDUTY_SUMMARY_MAX: usize = 1024;
+const NTFY_DEFAULT_PRIORITY: u8 = 3;
+const NTFY_DOWN_PRIORITY: u8 = 4;
+
+// 同时进行的投递数，超出的告警在队列中等待
+const DELIVERY_CONCURRENCY: usize = 16;
+static DELIVERY_SLOTS: Semaphore = Semaphore::const_new(DELIVERY_CONCURRENCY);
+// 尚未投递成功 (等待、发送中或退避中) 的告警数
+static QUEUE_PENDING: AtomicUsize = AtomicUsize::new(0);
+// 每个 (通道, 目标) 最新一条告警的序号：退避期间出现更新的告警时旧告警不再重试，
+// 避免过期的状态晚于新状态送达
+static LATEST_DELIVERY: Lazy<DashMap<(String, String), u64>> = Lazy::new(DashMap::new);
+static DELIVERY_SEQ: AtomicU64 = AtomicU64::new(0);
+
+// 静默时段内被拦下、等待汇总的告警 (按通道 id)，仅保存在内存中
+static HELD: Lazy<DashMap<String, Held>> = Lazy::new(DashMap::new);
+// 首次拦下告警时启动汇总任务
+static SUMMARY_TASK: Lazy<()> = Lazy::new(|| {
+    tokio::spawn(flush_summaries());
+});
+
+struct Held {
+    webhook: WebhookConfig,
+    attempts: u32,
+    notifications: Vec<Notification>,
+    // 超出 MAX_HELD 后只计数
+    dropped: usize,
+}
+
+// 各目标的告警冷却记录 (按目标 id)，仅保存在内存中
+static COOLDOWNS: Lazy<DashMap<String, Cooldown>> = Lazy::new(DashMap::new);
+
+#[derive(Default)]
+struct Cooldown {
+    // 最近一次实际发出的状态
+    delivered: Option<TargetState>,
+    // 各状态最近一次发出的时间
+    sent: HashMap<TargetState, DateTime<Local>>,
+    // 每次告警递增，延迟补发前据此判断期间是否有更新的告警
+    generation: u64,
+}
+
+/// 告警附带的探测上下文，系统告警与心跳为空
+#[derive(Debug, Clone, Default)]
+pub struct AlertContext {
+    // 最近一次探测的延迟
+    pub latency_ms: Option<f32>,
+    // 本次故障 (离开 UP) 的开始时间
+    pub incident_started: Option<DateTime<Local>>,
+}
+
+// 一条待投递的通知，各通道按自己的格式渲染
+#[derive(Debug, Clone)]
+struct Notification {
+    target: Target,
+    state: TargetState,
+    timestamp: DateTime<Local>,
+    detail: String,
+    context: AlertContext,
+    // 仪表盘中该目标的地址，未配置 `dashboard_url` 时为空
+    link: Option<String>,
+}
+
+impl Notification {
+    fn new(target: &Target, state: TargetState, detail: &str, context: &AlertContext) -> Self {
+        Self {
+            target: target.clone(),
+            state,
+            timestamp: Local::now(),
+            detail: detail.to_string(),
+            context: context.clone(),
+            link: None,
+        }
+    }
+
+    fn status_text(&self) -> &'static str {
+        match self.state {
+            TargetState::Up => "🟢 UP",
+            TargetState::Degraded => "🟡 DEGRADED",
+            TargetState::Down => "🔴 DOWN",
+        }
+    }
+
+    // 各聊天工具中按状态着色
+    fn color(&self) -> &'static str {
+        match self.state {
+            TargetState::Up => "#22c55e",
+            TargetState::Degraded => "#eab308",
+            TargetState::Down => "#ef4444",
+        }
+    }
+
+    fn time(&self) -> String {
+        self.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+
+    // 故障至今的时长
+    fn duration(&self) -> Option<chrono::Duration> {
+        self.context
+            .incident_started
+            .map(|started| self.timestamp - started)
+            .filter(|d| d.num_seconds() > 0)
+    }
+
+    fn render(&self, tmpl: &str) -> String {
+        let outage_start = self
+            .context
+            .incident_started
+            .map_or(String::new(), |started| {
+                started.format("%Y-%m-%d %H:%M:%S").to_string()
+            });
+        let duration = self.duration();
+        tmpl.replace("{{TARGET_ID}}", &self.target.id)
+            .replace("{{OUTAGE_START}}", &outage_start)
+            .replace(
+                "{{DOWNTIME}}",
+                &duration.map_or(String::new(), format_duration),
+            )
+            .replace(
+                "{{DOWNTIME_SECS}}",
+                &duration.map_or(String::new(), |d| d.num_seconds().to_string()),
+            )
+            .replace("{{TARGET}}", &self.target.name)
+            .replace("{{HOST}}", &self.target.host)
+            .replace("{{STATUS}}", self.status_text())
+            .replace("{{TIME}}", &self.time())
+            .replace("{{MESSAGE}}", &self.detail)
+    }
+}
 
 pub async fn send_alert(
     target: &Target,
-    is_online: bool,
+    state: TargetState,
     config: &AlertConfig,
     extra_msg: Option<&str>,
+    context: &AlertContext,
 ) -> anyhow::Result<()> {
     if !config.enabled {
         return Ok(());
     }
 
-    let status_text = if is_online { "🟢 UP" } else { "🔴 DOWN" };
-    let _status_icon = if is_online { "🟢" } else { "🔴" };
-    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    let detail = extra_msg.unwrap_or("");
+    let mut notification = Notification::new(target, state, extra_msg.unwrap_or(""), context);
+    notification.link = config
+        .dashboard_url
+        .as_deref()
+        .and_then(|url| dashboard_link(url, &target.id));
+
+    let cooldown = config
+        .cooldown_secs
+        .filter(|&secs| secs > 0)
+        .and_then(|secs| i64::try_from(secs.min(MAX_COOLDOWN_SECS)).ok())
+        .and_then(chrono::Duration::try_seconds);
+    match cooldown {
+        Some(cooldown) => throttle(config, notification, cooldown),
+        None => broadcast(config, &notification),
+    }
+    Ok(())
+}
+
+// 冷却时间内同一状态的告警不再发送 (在阈值附近抖动的目标只会反复产生相同的通知)。
+// 被抑制的告警在冷却结束时仍是该目标的最新告警、且与最近发出的状态不同时补发，
+// 避免接收方停留在过期的状态
+fn throttle(config: &AlertConfig, notification: Notification, cooldown: chrono::Duration) {
+    let target_id = notification.target.id.clone();
+    let mut entry = COOLDOWNS.entry(target_id.clone()).or_default();
+    entry.generation += 1;
+    let last_sent = entry.sent.get(&notification.state).copied();
+    let Some(resume) = last_sent
+        .and_then(|at| at.checked_add_signed(cooldown))
+        .filter(|&resume| notification.timestamp < resume)
+    else {
+        entry.delivered = Some(notification.state);
+        entry
+            .sent
+            .insert(notification.state, notification.timestamp);
+        drop(entry);
+        broadcast(config, &notification);
+        return;
+    };
+    tracing::info!(
+        "Alert suppressed for {}: {} already sent within cooldown",
+        notification.target.name,
+        notification.status_text()
+    );
 
-    // Generic Webhooks
-    let client = reqwest::Client::new();
+    let generation = entry.generation;
+    drop(entry);
+    let config = config.clone();
+    tokio::spawn(async move {
+        let wait = (resume - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        {
+            let Some(mut entry) = COOLDOWNS.get_mut(&target_id) else {
+                return;
+            };
+            if entry.generation != generation || entry.delivered == Some(notification.state) {
+                return;
+            }
+            entry.delivered = Some(notification.state);
+            entry.sent.insert(notification.state, Local::now());
+        }
+        let mut notification = notification;
+        notification.detail = match notification.detail.as_str() {
+            "" => "Delayed by alert cooldown".to_string(),
+            detail => format!("{} (delayed by alert cooldown)", detail),
+        };
+        broadcast(&config, &notification);
+    });
+}
+
+fn delivery_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .expect("Failed to build alert client")
+}
+
+// 投递到所有告警通道 (心跳通道除外)，每个通道独立排队重试
+fn broadcast(config: &AlertConfig, notification: &Notification) {
+    let client = delivery_client();
+    let attempts = config
+        .retry_attempts
+        .unwrap_or(DEFAULT_RETRY_ATTEMPTS)
+        .max(1);
 
     for webhook in &config.webhooks {
-        if !webhook.enabled {
+        // 心跳通道只接收 watchdog 的周期性投递
+        if !webhook.enabled || webhook.heartbeat {
             continue;
         }
 
-        let url = &webhook.url;
-        if url.is_empty() {
+        if !is_configured(webhook) {
             continue;
         }
 
-        // 如果有模板，使用模板替换
-        let payload = if let Some(tmpl) = &webhook.template {
-            let mut body = tmpl.clone();
-            body = body.replace("{{TARGET}}", &target.name);
-            body = body.replace("{{HOST}}", &target.host);
-            body = body.replace("{{STATUS}}", status_text);
-            body = body.replace("{{TIME}}", &timestamp);
-            body = body.replace("{{MESSAGE}}", detail);
-
-            match serde_json::from_str::<serde_json::Value>(&body) {
-                Ok(v) => v,
-                Err(_) => json!({ "text": body }), // Fallback
+        if let Some(quiet) = &webhook.quiet_hours {
+            if !notification.target.critical && is_quiet(quiet, Local::now()) {
+                hold(webhook, quiet, notification, attempts);
+                continue;
             }
-        } else {
-            // 默认 JSON Payload
-            json!({
-                "target": target.name,
-                "host": target.host,
-                "status": status_text,
-                "timestamp": timestamp,
-                "message": detail
+        }
+
+        tokio::spawn(deliver_with_retry(
+            client.clone(),
+            webhook.clone(),
+            notification.clone(),
+            attempts,
+        ));
+    }
+}
+
+fn is_quiet(quiet: &QuietHours, now: DateTime<Local>) -> bool {
+    quiet.windows.iter().any(|expr| {
+        croner::Cron::new(expr)
+            .parse()
+            .is_ok_and(|cron| crate::monitor::in_window(&cron, now))
+    })
+}
+
+// 静默时段内的告警：开启汇总时暂存，否则丢弃
+fn hold(webhook: &WebhookConfig, quiet: &QuietHours, notification: &Notification, attempts: u32) {
+    if !quiet.summary {
+        tracing::info!(
+            "Alert for {} via {} suppressed: quiet hours",
+            notification.target.name,
+            webhook.name
+        );
+        return;
+    }
+    tracing::info!(
+        "Alert for {} via {} held for quiet hours summary",
+        notification.target.name,
+        webhook.name
+    );
+    Lazy::force(&SUMMARY_TASK);
+    let mut held = HELD.entry(webhook.id.clone()).or_insert_with(|| Held {
+        webhook: webhook.clone(),
+        attempts,
+        notifications: Vec::new(),
+        dropped: 0,
+    });
+    if held.notifications.len() < MAX_HELD {
+        held.notifications.push(notification.clone());
+    } else {
+        held.dropped += 1;
+    }
+}
+
+// 每分钟检查一次，通道的静默时段结束后把暂存的告警汇总为一条发送
+async fn flush_summaries() {
+    let client = delivery_client();
+    let mut ticker = tokio::time::interval(Duration::from_secs(60));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let now = Local::now();
+        let ended: Vec<String> = HELD
+            .iter()
+            .filter(|h| {
+                h.webhook
+                    .quiet_hours
+                    .as_ref()
+                    .is_none_or(|quiet| !is_quiet(quiet, now))
             })
+            .map(|h| h.key().clone())
+            .collect();
+        for id in ended {
+            let Some((_, held)) = HELD.remove(&id) else {
+                continue;
+            };
+            tracing::info!(
+                "Quiet hours ended for {}, sending summary of {} alerts",
+                held.webhook.name,
+                held.notifications.len() + held.dropped
+            );
+            let summary = summarize(&held);
+            tokio::spawn(deliver_with_retry(
+                client.clone(),
+                held.webhook,
+                summary,
+                held.attempts,
+            ));
+        }
+    }
+}
+
+// 汇总通知：逐条列出期间的告警，状态取各目标最后一条告警中最严重的
+fn summarize(held: &Held) -> Notification {
+    let mut latest: Vec<(&str, TargetState)> = Vec::new();
+    for n in &held.notifications {
+        match latest.iter_mut().find(|(id, _)| *id == n.target.id) {
+            Some(entry) => entry.1 = n.state,
+            None => latest.push((&n.target.id, n.state)),
+        }
+    }
+    // TargetState 按 Down < Degraded < Up 排序
+    let state = latest
+        .iter()
+        .map(|(_, state)| *state)
+        .min()
+        .unwrap_or(TargetState::Up);
+
+    let total = held.notifications.len() + held.dropped;
+    let mut lines = vec![format!("{} alerts during quiet hours:", total)];
+    for n in held.notifications.iter().take(SUMMARY_MAX_LINES) {
+        let mut line = format!(
+            "{} {} {}",
+            n.timestamp.format("%m-%d %H:%M"),
+            n.status_text(),
+            n.target.name
+        );
+        if !n.detail.is_empty() {
+            line += &format!(": {}", n.detail);
+        }
+        lines.push(line);
+    }
+    if total > SUMMARY_MAX_LINES {
+        lines.push(format!("... and {} more", total - SUMMARY_MAX_LINES));
+    }
+    let target = Target {
+        id: "netwatch-quiet-hours".to_string(),
+        name: "Quiet hours summary".to_string(),
+        host: "netwatch".to_string(),
+        ..Default::default()
+    };
+    Notification::new(&target, state, &lines.join("\n"), &AlertContext::default())
+}
+
+/// 投递队列的状态，供 `/api/system/status` 展示
+pub fn queue_status() -> serde_json::Value {
+    json!({
+        "pending": QUEUE_PENDING.load(Ordering::Relaxed),
+        "in_flight": DELIVERY_CONCURRENCY - DELIVERY_SLOTS.available_permits(),
+    })
+}
+
+// 在队列中等待空闲的投递名额，失败后按指数退避重试，重试耗尽写入死信日志
+async fn deliver_with_retry(
+    client: reqwest::Client,
+    webhook: WebhookConfig,
+    notification: Notification,
+    attempts: u32,
+) {
+    QUEUE_PENDING.fetch_add(1, Ordering::Relaxed);
+    let key = (webhook.id.clone(), notification.target.id.clone());
+    let seq = DELIVERY_SEQ.fetch_add(1, Ordering::Relaxed);
+    LATEST_DELIVERY.insert(key.clone(), seq);
+    let mut attempt = 1;
+    let error = loop {
+        if attempt > 1
+            && LATEST_DELIVERY
+                .get(&key)
+                .is_some_and(|latest| *latest != seq)
+        {
+            tracing::info!(
+                "Dropping retry via {} for {}: superseded by a newer alert",
+                webhook.name,
+                notification.target.name
+            );
+            QUEUE_PENDING.fetch_sub(1, Ordering::Relaxed);
+            return;
+        }
+        let result = {
+            let _slot = DELIVERY_SLOTS
+                .acquire()
+                .await
+                .expect("semaphore is never closed");
+            tracing::debug!("Sending alert via {} (attempt {})", webhook.name, attempt);
+            dispatch(&client, &webhook, &notification).await
         };
+        match result {
+            Ok(()) => {
+                tracing::debug!("Alert sent successfully via {}", webhook.name);
+                QUEUE_PENDING.fetch_sub(1, Ordering::Relaxed);
+                return;
+            }
+            Err(e) if attempt < attempts => {
+                let backoff = retry_backoff(attempt);
+                tracing::warn!(
+                    "Failed to send alert via {} (attempt {}/{}): {}, retrying in {}s",
+                    webhook.name,
+                    attempt,
+                    attempts,
+                    e,
+                    backoff.as_secs()
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => break e,
+        }
+    };
+    QUEUE_PENDING.fetch_sub(1, Ordering::Relaxed);
+    tracing::error!(
+        "Failed to send alert via {} after {} attempts: {}",
+        webhook.name,
+        attempt,
+        error
+    );
+    crate::deadletter::record(DeadLetter {
+        timestamp: Local::now(),
+        channel_id: webhook.id,
+        channel: webhook.name,
+        kind: webhook.kind,
+        target_id: notification.target.id,
+        target_name: notification.target.name,
+        state: notification.state,
+        message: notification.detail,
+        alerted_at: notification.timestamp,
+        attempts: attempt,
+        error,
+    });
+}
 
-        let client = client.clone();
-        let url = url.clone();
-
-        tokio::spawn(async move {
-            tracing::debug!("Sending webhook to {}", url);
-            match client.post(&url).json(&payload).send().await {
-                Ok(res) => {
-                    if !res.status().is_success() {
-                        tracing::error!("Webhook failed with status {}: {}", res.status(), url);
-                        if let Ok(text) = res.text().await {
-                            tracing::error!("Response body: {}", text);
-                        }
-                    } else {
-                        tracing::debug!("Webhook sent successfully to {}", url);
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to send Webhook to {}: {}", url, e);
+// 第 n 次失败后的等待时间：2s、4s、8s ... 最长 5 分钟
+fn retry_backoff(attempt: u32) -> Duration {
+    RETRY_BASE
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(RETRY_MAX_BACKOFF)
+}
+
+/// NetWatch 自身的系统级告警 (如配置无法持久化)，走与目标告警相同的通道与模版
+pub async fn send_system_alert(
+    config: &AlertConfig,
+    healthy: bool,
+    message: &str,
+) -> anyhow::Result<()> {
+    // 系统告警不受静默时段限制
+    let target = Target {
+        id: "netwatch".to_string(),
+        name: "NetWatch".to_string(),
+        host: "system".to_string(),
+        critical: true,
+        ..Default::default()
+    };
+    let state = if healthy {
+        TargetState::Up
+    } else {
+        TargetState::Down
+    };
+    // 系统告警的内容各不相同，不参与冷却
+    if config.enabled {
+        let notification = Notification::new(&target, state, message, &AlertContext::default());
+        broadcast(config, &notification);
+    }
+    Ok(())
+}
+
+/// Watchdog：按告警的同一套模版渲染并同步投递到所有心跳通道 (如 healthchecks.io)，
+/// 任一通道失败即返回错误。返回成功投递的通道数。
+pub async fn send_heartbeat(target: &Target, config: &AlertConfig) -> Result<usize, String> {
+    if !config.enabled {
+        return Err("Alerting is disabled".to_string());
+    }
+    let channels: Vec<&WebhookConfig> = config
+        .webhooks
+        .iter()
+        .filter(|w| w.enabled && w.heartbeat && is_configured(w))
+        .collect();
+    if channels.is_empty() {
+        return Err("No enabled heartbeat channel".to_string());
+    }
+
+    let notification = Notification::new(
+        target,
+        TargetState::Up,
+        "NetWatch alert pipeline heartbeat",
+        &AlertContext::default(),
+    );
+    let client = delivery_client();
+    for webhook in &channels {
+        dispatch(&client, webhook, &notification)
+            .await
+            .map_err(|e| format!("{}: {}", webhook.name, e))?;
+    }
+    Ok(channels.len())
+}
+
+// 通道是否填写了投递所需的地址
+fn is_configured(webhook: &WebhookConfig) -> bool {
+    match webhook.kind {
+        ChannelKind::Webhook
+        | ChannelKind::Slack
+        | ChannelKind::Discord
+        | ChannelKind::DingTalk
+        | ChannelKind::WeCom
+        | ChannelKind::Feishu => !webhook.url.is_empty(),
+        ChannelKind::Email => webhook.smtp.is_some(),
+        ChannelKind::Telegram => webhook.telegram.is_some(),
+        ChannelKind::Ntfy => webhook.ntfy.is_some(),
+        ChannelKind::Mqtt => webhook.mqtt.is_some(),
+        ChannelKind::Exec => !webhook.command.is_empty(),
+        ChannelKind::PagerDuty => webhook
+            .routing_key
+            .as_deref()
+            .is_some_and(|k| !k.is_empty()),
+    }
+}
+
+// 按通道类型渲染并投递一条通知
+async fn dispatch(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    notification: &Notification,
+) -> Result<(), String> {
+    match webhook.kind {
+        ChannelKind::Webhook => {
+            let payload = render_payload(webhook, notification);
+            deliver(client, &webhook.url, &payload).await
+        }
+        ChannelKind::Email => {
+            let smtp = webhook.smtp.as_ref().ok_or("SMTP settings missing")?;
+            let subject =
+                notification.render(smtp.subject.as_deref().unwrap_or(DEFAULT_EMAIL_SUBJECT));
+            let body =
+                notification.render(webhook.template.as_deref().unwrap_or(DEFAULT_EMAIL_BODY));
+            send_email(smtp, subject, body).await
+        }
+        ChannelKind::Telegram => {
+            let telegram = webhook
+                .telegram
+                .as_ref()
+                .ok_or("Telegram settings missing")?;
+            let url = format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                telegram.bot_token
+            );
+            // 自定义模版按纯文本发送，不要求使用者处理 MarkdownV2 转义
+            let payload = match &webhook.template {
+                Some(tmpl) => {
+                    json!({ "chat_id": telegram.chat_id, "text": notification.render(tmpl) })
                 }
+                None => json!({
+                    "chat_id": telegram.chat_id,
+                    "text": telegram_text(notification),
+                    "parse_mode": "MarkdownV2",
+                }),
+            };
+            deliver(client, &url, &payload).await
+        }
+        ChannelKind::Slack => {
+            let payload = match webhook.template {
+                Some(_) => render_payload(webhook, notification),
+                None => slack_payload(notification),
+            };
+            deliver(client, &webhook.url, &payload).await
+        }
+        ChannelKind::Discord => {
+            let payload = match webhook.template {
+                Some(_) => render_payload(webhook, notification),
+                None => discord_payload(notification),
+            };
+            deliver(client, &webhook.url, &payload).await
+        }
+        ChannelKind::DingTalk => {
+            let payload = match webhook.template {
+                Some(_) => render_payload(webhook, notification),
+                None => dingtalk_payload(notification),
+            };
+            let url = match webhook.secret.as_deref().filter(|s| !s.is_empty()) {
+                Some(secret) => dingtalk_signed_url(&webhook.url, secret)?,
+                None => webhook.url.clone(),
+            };
+            deliver_robot(client, &url, &payload).await
+        }
+        ChannelKind::WeCom => {
+            let payload = match webhook.template {
+                Some(_) => render_payload(webhook, notification),
+                None => wecom_payload(notification),
+            };
+            deliver_robot(client, &webhook.url, &payload).await
+        }
+        ChannelKind::Feishu => {
+            let mut payload = match webhook.template {
+                Some(_) => render_payload(webhook, notification),
+                None => feishu_payload(notification),
+            };
+            // 飞书的签名放在请求体中
+            if let (Some(secret), Some(body)) = (
+                webhook.secret.as_deref().filter(|s| !s.is_empty()),
+                payload.as_object_mut(),
+            ) {
+                let timestamp = Local::now().timestamp().to_string();
+                let sign = feishu_sign(&timestamp, secret)?;
+                body.insert("timestamp".to_string(), timestamp.into());
+                body.insert("sign".to_string(), sign.into());
+            }
+            deliver_robot(client, &webhook.url, &payload).await
+        }
+        ChannelKind::PagerDuty => {
+            let routing_key = webhook
+                .routing_key
+                .as_deref()
+                .ok_or("PagerDuty routing_key missing")?;
+            let url = match webhook.url.trim() {
+                "" => PAGERDUTY_EVENTS_URL,
+                url => url,
+            };
+            let payload = pagerduty_event(routing_key, webhook.template.as_deref(), notification);
+            deliver(client, url, &payload).await
+        }
+        ChannelKind::Ntfy => {
+            let ntfy = webhook.ntfy.as_ref().ok_or("ntfy settings missing")?;
+            let payload = ntfy_message(ntfy, webhook.template.as_deref(), notification);
+            // JSON 消息发布到服务器根地址，主题在请求体中
+            let mut request = client
+                .post(ntfy.server.trim_end_matches('/'))
+                .json(&payload);
+            if let Some(token) = ntfy.token.as_deref().filter(|t| !t.is_empty()) {
+                request = request.bearer_auth(token);
             }
+            send(request).await
+        }
+        ChannelKind::Mqtt => {
+            let mqtt = webhook.mqtt.as_ref().ok_or("MQTT settings missing")?;
+            let topic = notification.render(&mqtt.topic);
+            let payload = match &webhook.template {
+                Some(tmpl) => notification.render(tmpl),
+                None => mqtt_payload(notification).to_string(),
+            };
+            publish_mqtt(mqtt, topic, payload.into_bytes()).await
+        }
+        ChannelKind::Exec => run_command(&webhook.command, notification).await,
+    }
+}
+
+fn render_payload(webhook: &WebhookConfig, notification: &Notification) -> serde_json::Value {
+    // 如果有模板，使用模板替换
+    if let Some(tmpl) = &webhook.template {
+        let body = notification.render(tmpl);
+
+        match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(v) => v,
+            Err(_) => json!({ "text": body }), // Fallback
+        }
+    } else {
+        // 默认 JSON Payload，故障期间与恢复时附带故障开始时间与已持续的时长
+        let mut payload = json!({
+            "target": notification.target.name,
+            "host": notification.target.host,
+            "status": notification.status_text(),
+            "timestamp": notification.time(),
+            "message": notification.detail
         });
+        if let Some(started) = notification.context.incident_started {
+            payload["outage_started"] = started.to_rfc3339().into();
+        }
+        if let Some(duration) = notification.duration() {
+            payload["downtime_secs"] = duration.num_seconds().into();
+            payload["downtime"] = format_duration(duration).into();
+        }
+        payload
+    }
+}
+
+// Telegram MarkdownV2 消息：状态、目标、主机、延迟与故障时长
+fn telegram_text(n: &Notification) -> String {
+    let mut text = format!(
+        "*{}* {}\n\n*Host:* `{}`",
+        escape_markdown(n.status_text()),
+        escape_markdown(&n.target.name),
+        n.target.host.replace('\\', "\\\\").replace('`', "\\`"),
+    );
+    if let Some(latency) = n.context.latency_ms {
+        text += &format!(
+            "\n*Latency:* {}",
+            escape_markdown(&format!("{:.1} ms", latency))
+        );
+    }
+    if let Some(duration) = n.duration() {
+        text += &format!(
+            "\n*Duration:* {}",
+            escape_markdown(&format_duration(duration))
+        );
+    }
+    text += &format!("\n*Time:* {}", escape_markdown(&n.time()));
+    if !n.detail.is_empty() {
+        text += &format!("\n\n{}", escape_markdown(&n.detail));
+    }
+    text
+}
+
+// Slack Block Kit：按状态着色的附件，主机、延迟与故障时长作为字段
+fn slack_payload(n: &Notification) -> serde_json::Value {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let title = format!("{} {}", n.status_text(), n.target.name);
+    let mut fields =
+        vec![json!({ "type": "mrkdwn", "text": format!("*Host*\n`{}`", escape(&n.target.host)) })];
+    if let Some(latency) = n.context.latency_ms {
+        fields.push(json!({ "type": "mrkdwn", "text": format!("*Latency*\n{:.1} ms", latency) }));
+    }
+    if let Some(duration) = n.duration() {
+        fields.push(json!({ "type": "mrkdwn", "text": format!("*Downtime*\n{}", format_duration(duration)) }));
+    }
+    let mut blocks = vec![
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("*{}* {}", n.status_text(), escape(&n.target.name)) },
+        }),
+        json!({ "type": "section", "fields": fields }),
+    ];
+    if !n.detail.is_empty() {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": escape(&n.detail) },
+        }));
+    }
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": format!("NetWatch · {}", n.time()) }],
+    }));
+    json!({
+        // 通知预览与不支持 Block Kit 的客户端显示 text
+        "text": escape(&title),
+        "attachments": [{ "color": n.color(), "blocks": blocks }],
+    })
+}
+
+// Discord embed：按状态着色，主机、延迟与故障时长作为行内字段
+fn discord_payload(n: &Notification) -> serde_json::Value {
+    let mut fields =
+        vec![json!({ "name": "Host", "value": format!("`{}`", n.target.host), "inline": true })];
+    if let Some(latency) = n.context.latency_ms {
+        fields.push(
+            json!({ "name": "Latency", "value": format!("{:.1} ms", latency), "inline": true }),
+        );
+    }
+    if let Some(duration) = n.duration() {
+        fields.push(
+            json!({ "name": "Downtime", "value": format_duration(duration), "inline": true }),
+        );
+    }
+    let mut embed = json!({
+        // embed 标题与描述有长度上限，超出时 Discord 拒绝整条消息
+        "title": truncate(&format!("{} {}", n.status_text(), n.target.name), DISCORD_TITLE_MAX),
+        "color": u32::from_str_radix(&n.color()[1..], 16).unwrap_or_default(),
+        "timestamp": n.timestamp.to_rfc3339(),
+        "fields": fields,
+        "footer": { "text": "NetWatch" },
+    });
+    if !n.detail.is_empty() {
+        embed["description"] = truncate(&n.detail, DISCORD_DESCRIPTION_MAX).into();
+    }
+    json!({ "username": "NetWatch", "embeds": [embed] })
+}
+
+// 钉钉 markdown 消息，标题以 NetWatch 开头，便于匹配机器人的自定义关键词
+fn dingtalk_payload(n: &Notification) -> serde_json::Value {
+    let title = format!("NetWatch: {} {}", n.status_text(), n.target.name);
+    let mut text = format!(
+        "### {} {}\n\n- **Host**: {}\n",
+        n.status_text(),
+        n.target.name,
+        n.target.host
+    );
+    if let Some(latency) = n.context.latency_ms {
+        text += &format!("- **Latency**: {:.1} ms\n", latency);
+    }
+    if let Some(duration) = n.duration() {
+        text += &format!("- **Downtime**: {}\n", format_duration(duration));
+    }
+    text += &format!("- **Time**: {}\n", n.time());
+    if !n.detail.is_empty() {
+        text += &format!("\n> {}\n", n.detail);
+    }
+    json!({ "msgtype": "markdown", "markdown": { "title": title, "text": text } })
+}
+
+// 企业微信 markdown 消息：状态按 info (绿) / warning (橙) 着色，字段以引用行列出
+fn wecom_payload(n: &Notification) -> serde_json::Value {
+    let color = match n.state {
+        TargetState::Up => "info",
+        TargetState::Degraded | TargetState::Down => "warning",
+    };
+    let mut content = format!(
+        "### <font color=\"{}\">{}</font> {}\n> Host: <font color=\"comment\">{}</font>\n",
+        color,
+        n.status_text(),
+        n.target.name,
+        n.target.host
+    );
+    if let Some(latency) = n.context.latency_ms {
+        content += &format!(
+            "> Latency: <font color=\"comment\">{:.1} ms</font>\n",
+            latency
+        );
+    }
+    if let Some(duration) = n.duration() {
+        content += &format!(
+            "> Downtime: <font color=\"comment\">{}</font>\n",
+            format_duration(duration)
+        );
+    }
+    content += &format!("> Time: <font color=\"comment\">{}</font>\n", n.time());
+    if !n.detail.is_empty() {
+        content += &format!("\n{}", truncate(&n.detail, WECOM_DETAIL_MAX));
+    }
+    json!({ "msgtype": "markdown", "markdown": { "content": content } })
+}
+
+// 飞书消息卡片：标题栏按状态着色，主机、延迟与故障时长作为并排字段，时间作为备注
+fn feishu_payload(n: &Notification) -> serde_json::Value {
+    let template = match n.state {
+        TargetState::Up => "green",
+        TargetState::Degraded => "yellow",
+        TargetState::Down => "red",
+    };
+    let field = |name: &str, value: String| json!({ "is_short": true, "text": { "tag": "lark_md", "content": format!("**{}**\n{}", name, value) } });
+    let mut fields = vec![field("Host", n.target.host.clone())];
+    if let Some(latency) = n.context.latency_ms {
+        fields.push(field("Latency", format!("{:.1} ms", latency)));
+    }
+    if let Some(duration) = n.duration() {
+        fields.push(field("Downtime", format_duration(duration)));
+    }
+    let mut elements = vec![json!({ "tag": "div", "fields": fields })];
+    if !n.detail.is_empty() {
+        elements
+            .push(json!({ "tag": "div", "text": { "tag": "plain_text", "content": n.detail } }));
+    }
+    elements.push(json!({
+        "tag": "note",
+        "elements": [{ "tag": "plain_text", "content": n.time() }],
+    }));
+    json!({
+        "msg_type": "interactive",
+        "card": {
+            "config": { "wide_screen_mode": true },
+            "header": {
+                "template": template,
+                "title": { "tag": "plain_text", "content": format!("{} {}", n.status_text(), n.target.name) },
+            },
+            "elements": elements,
+        },
+    })
+}
+
+// 飞书签名：以 "秒级时间戳\n密钥" 为密钥对空消息做 HMAC-SHA256，再 Base64
+fn feishu_sign(timestamp: &str, secret: &str) -> Result<String, String> {
+    let mac = Hmac::<Sha256>::new_from_slice(format!("{}\n{}", timestamp, secret).as_bytes())
+        .map_err(|e| format!("Invalid secret: {}", e))?;
+    Ok(STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+// PagerDuty 事件：同一目标使用固定的 dedup_key，恢复时解决该目标触发的事件，
+// 故障期间的重复告警合并到同一事件。自定义模版用于渲染事件摘要
+fn pagerduty_event(
+    routing_key: &str,
+    template: Option<&str>,
+    n: &Notification,
+) -> serde_json::Value {
+    let dedup_key = format!("netwatch-{}", n.target.id);
+    let severity = match n.state {
+        TargetState::Up => {
+            return json!({
+                "routing_key": routing_key,
+                "event_action": "resolve",
+                "dedup_key": dedup_key,
+            });
+        }
+        TargetState::Degraded => "warning",
+        TargetState::Down => "critical",
+    };
+    let summary = match template {
+        Some(tmpl) => n.render(tmpl),
+        None if n.detail.is_empty() => format!("{} {}", n.status_text(), n.target.name),
+        None => format!("{} {}: {}", n.status_text(), n.target.name, n.detail),
+    };
+    let mut details = json!({ "target": n.target.name, "host": n.target.host });
+    if let Some(latency) = n.context.latency_ms {
+        details["latency_ms"] = latency.into();
+    }
+    if let Some(started) = n.context.incident_started {
+        details["incident_started"] = started.to_rfc3339().into();
+    }
+    let mut event = json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": dedup_key,
+        "payload": {
+            "summary": truncate(&summary, PAGERDUTY_SUMMARY_MAX),
+            "source": n.target.host,
+            "severity": severity,
+            "timestamp": n.timestamp.to_rfc3339(),
+            "component": n.target.name,
+            "custom_details": details,
+        },
+        "client": "NetWatch",
+    });
+    if let Some(group) = &n.target.group {
+        event["payload"]["group"] = group.as_str().into();
+    }
+    event
+}
+
+// ntfy JSON 消息：配置了仪表盘地址时点击通知与按钮均打开该目标
+fn ntfy_message(ntfy: &NtfyConfig, template: Option<&str>, n: &Notification) -> serde_json::Value {
+    let (priority, tag, status) = match n.state {
+        TargetState::Up => (NTFY_DEFAULT_PRIORITY, "green_circle", "UP"),
+        TargetState::Degraded => (
+            ntfy.priority.unwrap_or(NTFY_DEFAULT_PRIORITY),
+            "yellow_circle",
+            "DEGRADED",
+        ),
+        TargetState::Down => (
+            ntfy.priority.unwrap_or(NTFY_DOWN_PRIORITY),
+            "red_circle",
+            "DOWN",
+        ),
+    };
+    let message = match template {
+        Some(tmpl) => n.render(tmpl),
+        None => {
+            let mut lines = vec![format!("Host: {}", n.target.host)];
+            if let Some(latency) = n.context.latency_ms {
+                lines.push(format!("Latency: {:.1} ms", latency));
+            }
+            if let Some(duration) = n.duration() {
+                lines.push(format!("Downtime: {}", format_duration(duration)));
+            }
+            lines.push(format!("Time: {}", n.time()));
+            if !n.detail.is_empty() {
+                lines.push(n.detail.clone());
+            }
+            lines.join("\n")
+        }
+    };
+    // 状态图标由标签显示，标题中不再重复
+    let mut body = json!({
+        "topic": ntfy.topic,
+        "title": format!("{} {}", status, n.target.name),
+        "message": message,
+        "priority": priority.clamp(1, 5),
+        "tags": [tag],
+    });
+    if let Some(link) = &n.link {
+        body["click"] = link.as_str().into();
+        body["actions"] = json!([
+            { "action": "view", "label": "Open dashboard", "url": link, "clear": true }
+        ]);
+    }
+    body
+}
+
+// MQTT 消息：便于自动化规则直接按 `state` 字段判断
+fn mqtt_payload(n: &Notification) -> serde_json::Value {
+    json!({
+        "target_id": n.target.id,
+        "target": n.target.name,
+        "host": n.target.host,
+        "state": n.state,
+        "message": n.detail,
+        "latency_ms": n.context.latency_ms,
+        "incident_started": n.context.incident_started,
+        "timestamp": n.timestamp,
+    })
+}
+
+// 仪表盘地址加上目标锚点，如 `https://netwatch.example.com/#target-web`
+fn dashboard_link(base: &str, target_id: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(base.trim()).ok()?;
+    url.set_fragment(Some(&format!("target-{}", target_id)));
+    Some(url.into())
+}
+
+// 钉钉加签：以密钥对 "毫秒时间戳\n密钥" 做 HMAC-SHA256，Base64 后与时间戳一起附在地址上
+fn dingtalk_signed_url(url: &str, secret: &str) -> Result<String, String> {
+    let timestamp = Local::now().timestamp_millis().to_string();
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| format!("Invalid secret: {}", e))?;
+    mac.update(format!("{}\n{}", timestamp, secret).as_bytes());
+    let sign = STANDARD.encode(mac.finalize().into_bytes());
+    let mut url = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("timestamp", &timestamp)
+        .append_pair("sign", &sign);
+    Ok(url.into())
+}
+
+// 超出 `max_chars` 个字符时截断并以省略号结尾
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+// MarkdownV2 中需要转义的字符
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// 如 `45s`、`12m 5s`、`3h 20m`、`2d 4h`
+fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (days, hours, minutes, seconds) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", seconds),
+        (0, 0, _) => format!("{}m {}s", minutes, seconds),
+        (0, _, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    send(client.post(url).json(payload)).await
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<(), String> {
+    let res = request
+        .send()
+        .await
+        // 地址中可能含有令牌 (如 Telegram bot token)，不写入日志
+        .map_err(|e| e.without_url().to_string())?;
+    if res.status().is_success() {
+        return Ok(());
     }
+    let status = res.status();
+    match res.text().await {
+        Ok(text) if !text.is_empty() => Err(format!("status {}: {}", status, text)),
+        _ => Err(format!("status {}", status)),
+    }
+}
+
+// 国内办公软件的机器人接口出错时仍返回 200，错误码在响应体的 `errcode` (钉钉、企业微信)
+// 或 `code` (飞书) 中
+async fn deliver_robot(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let res = client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("status {}: {}", status, text));
+    }
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+    let code = body.get("errcode").or_else(|| body.get("code"));
+    match code.and_then(|c| c.as_i64()) {
+        Some(code) if code != 0 => {
+            let message = body.get("errmsg").or_else(|| body.get("msg"));
+            Err(format!(
+                "error {}: {}",
+                code,
+                message.and_then(|m| m.as_str()).unwrap_or_default()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 连接 MQTT broker 发布一条消息后断开；QoS 1/2 等到 broker 确认才算成功
+async fn publish_mqtt(mqtt: &MqttConfig, topic: String, payload: Vec<u8>) -> Result<(), String> {
+    let url = reqwest::Url::parse(mqtt.broker.trim())
+        .map_err(|e| format!("Invalid broker {}: {}", mqtt.broker, e))?;
+    let (transport, default_port) = match url.scheme() {
+        "mqtt" | "tcp" => (Transport::tcp(), 1883),
+        "mqtts" | "ssl" => (Transport::tls_with_default_config(), 8883),
+        scheme => return Err(format!("Unsupported broker scheme {}", scheme)),
+    };
+    let host = url.host_str().ok_or("Broker has no host")?;
+    let client_id = mqtt.client_id.clone().unwrap_or_else(|| {
+        format!(
+            "netwatch-{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        )
+    });
+    let mut options = MqttOptions::new(client_id, host, url.port().unwrap_or(default_port));
+    options.set_transport(transport);
+    options.set_keep_alive(Duration::from_secs(5));
+    if let Some(username) = &mqtt.username {
+        options.set_credentials(username, mqtt.password.clone().unwrap_or_default());
+    }
+    let qos = match mqtt.qos {
+        0 => QoS::AtMostOnce,
+        1 => QoS::AtLeastOnce,
+        _ => QoS::ExactlyOnce,
+    };
+
+    let (client, mut eventloop) = AsyncClient::new(options, 10);
+    client
+        .publish(topic, qos, mqtt.retain, payload)
+        .await
+        .map_err(|e| e.to_string())?;
+    let publish = async {
+        loop {
+            match eventloop.poll().await.map_err(|e| e.to_string())? {
+                Event::Outgoing(Outgoing::Publish(_)) if qos == QoS::AtMostOnce => break,
+                Event::Incoming(Packet::PubAck(_)) if qos == QoS::AtLeastOnce => break,
+                Event::Incoming(Packet::PubComp(_)) => break,
+                _ => {}
+            }
+        }
+        // 主动断开，避免 broker 把连接中断当作异常 (触发遗嘱等)
+        client.disconnect().await.map_err(|e| e.to_string())?;
+        while !matches!(
+            eventloop.poll().await,
+            Ok(Event::Outgoing(Outgoing::Disconnect)) | Err(_)
+        ) {}
+        Ok(())
+    };
+    tokio::time::timeout(MQTT_TIMEOUT, publish)
+        .await
+        .map_err(|_| format!("MQTT publish timed out after {}s", MQTT_TIMEOUT.as_secs()))?
+}
 
+/// 执行告警命令，通知内容以环境变量传入；退出码非 0 视为投递失败
+async fn run_command(command: &[String], n: &Notification) -> Result<(), String> {
+    if !crate::monitor::exec_allowed() {
+        return Err("Exec channels are disabled (set NETWATCH_ALLOW_EXEC=1)".to_string());
+    }
+    let (program, args) = command.split_first().ok_or("No command configured")?;
+    let state = match n.state {
+        TargetState::Up => "up",
+        TargetState::Degraded => "degraded",
+        TargetState::Down => "down",
+    };
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .env("TARGET", &n.target.name)
+        .env("TARGET_ID", &n.target.id)
+        .env("HOST", &n.target.host)
+        .env("STATUS", state)
+        .env(
+            "LATENCY",
+            n.context
+                .latency_ms
+                .map_or(String::new(), |l| format!("{:.1}", l)),
+        )
+        .env("MESSAGE", &n.detail)
+        .env("TIME", n.time())
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(EXEC_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("Command timed out after {}s", EXEC_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        Err(output.status.to_string())
+    } else {
+        Err(format!("{}: {}", output.status, stderr))
+    }
+}
+
+/// 通过 SMTP 发送纯文本邮件，所有收件人在同一封邮件中
+async fn send_email(smtp: &SmtpConfig, subject: String, body: String) -> Result<(), String> {
+    let from: Mailbox = smtp
+        .from
+        .parse()
+        .map_err(|e| format!("Invalid from address {}: {}", smtp.from, e))?;
+    if smtp.to.is_empty() {
+        return Err("No recipients".to_string());
+    }
+    let mut builder = Message::builder().from(from).subject(subject);
+    for to in &smtp.to {
+        let mailbox: Mailbox = to
+            .parse()
+            .map_err(|e| format!("Invalid recipient {}: {}", to, e))?;
+        builder = builder.to(mailbox);
+    }
+    let message = builder
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+
+    let mut transport = match smtp.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host),
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host),
+        SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &smtp.host,
+        )),
+    }
+    .map_err(|e| e.to_string())?
+    .timeout(Some(SMTP_TIMEOUT));
+    if let Some(port) = smtp.port {
+        transport = transport.port(port);
+    }
+    if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .map_err(|e| e.to_string())?;
     Ok(())
 }
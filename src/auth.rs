@@ -0,0 +1,121 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chrono::{DateTime, Local};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+// 新生成哈希使用的参数 (OWASP 推荐的 Argon2id 最低配置：19 MiB 内存、2 轮、单线程)
+const DEFAULT_M_KIB: u32 = 19 * 1024;
+const DEFAULT_T: u32 = 2;
+const DEFAULT_P: u32 = 1;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+// 校验配置中的哈希时允许的最大参数：每次登录尝试都会按哈希中的参数分配内存，
+// 过大的参数会让并发的登录请求耗尽内存
+const MAX_M_KIB: u32 = 64 * 1024;
+const MAX_T: u32 = 10;
+const MAX_P: u32 = 4;
+
+/// 会话有效期上限 (小时)，配置校验时检查
+pub const MAX_SESSION_HOURS: u64 = 24 * 366;
+
+// 与默认参数相同的占位哈希 (盐为 "somesaltsomesalt"，哈希全零)
+const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$c29tZXNhbHRzb21lc2FsdA$AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+pub const SESSION_COOKIE: &str = "netwatch_session";
+
+struct Session {
+    username: String,
+    expires: DateTime<Local>,
+}
+
+// 会话只保存在内存中，重启后需要重新登录
+static SESSIONS: Lazy<DashMap<String, Session>> = Lazy::new(DashMap::new);
+
+/// 生成 PHC 格式的 Argon2id 哈希，可直接写入配置的 `password_hash`
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| anyhow::anyhow!("getrandom: {}", e))?;
+    let salt = SaltString::encode_b64(&salt).map_err(|e| anyhow::anyhow!("salt: {}", e))?;
+    let params = Params::new(DEFAULT_M_KIB, DEFAULT_T, DEFAULT_P, Some(HASH_LEN))
+        .map_err(|e| anyhow::anyhow!("argon2 params: {}", e))?;
+    let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("argon2: {}", e))?;
+    Ok(hash.to_string())
+}
+
+/// 校验密码与 PHC 格式的 Argon2id 哈希，格式无效或参数超出上限时视为不匹配
+/// (耗时较长，应在阻塞线程中调用)
+pub fn verify_password(password: &str, phc: &str) -> bool {
+    let Some((hash, params)) = parse_phc(phc) else {
+        tracing::warn!("Invalid password hash format, expected $argon2id$v=19$...");
+        return false;
+    };
+    // 比较在 argon2 内部以常量时间进行
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+/// 登录校验。用户不存在时仍对占位哈希计算一次，避免通过响应时间探测用户名
+pub fn verify_login(password: &str, phc: Option<&str>) -> bool {
+    match phc {
+        Some(phc) => verify_password(password, phc),
+        None => {
+            verify_password(password, DUMMY_HASH);
+            false
+        }
+    }
+}
+
+// 只接受 v=19 的 Argon2id，并限制内存、轮数与并行度
+fn parse_phc(phc: &str) -> Option<(PasswordHash<'_>, Params)> {
+    let hash = PasswordHash::new(phc).ok()?;
+    if hash.algorithm != argon2::ARGON2ID_IDENT || hash.version != Some(Version::V0x13.into()) {
+        return None;
+    }
+    let params = Params::try_from(&hash).ok()?;
+    let valid = params.m_cost() <= MAX_M_KIB
+        && params.t_cost() <= MAX_T
+        && params.p_cost() <= MAX_P
+        && hash.salt.is_some_and(|salt| salt.len() >= 8)
+        && hash.hash.is_some_and(|out| out.len() >= 4);
+    valid.then_some((hash, params))
+}
+
+/// 创建会话，返回写入 Cookie 的随机令牌
+pub fn create_session(username: &str, ttl: chrono::Duration) -> anyhow::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow::anyhow!("getrandom: {}", e))?;
+    let token = hex::encode(bytes);
+    let now = Local::now();
+    let expires = now
+        .checked_add_signed(ttl)
+        .ok_or_else(|| anyhow::anyhow!("Session lifetime out of range"))?;
+    SESSIONS.retain(|_, s| s.expires > now);
+    SESSIONS.insert(
+        token.clone(),
+        Session {
+            username: username.to_string(),
+            expires,
+        },
+    );
+    Ok(token)
+}
+
+/// 会话令牌对应的用户名，过期或不存在时返回 None
+pub fn session_user(token: &str) -> Option<String> {
+    let session = SESSIONS.get(token)?;
+    if session.expires <= Local::now() {
+        drop(session);
+        SESSIONS.remove(token);
+        return None;
+    }
+    Some(session.username.clone())
+}
+
+pub fn remove_session(token: &str) {
+    SESSIONS.remove(token);
+}
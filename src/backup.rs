@@ -0,0 +1,194 @@
+use crate::events::StateEvent;
+use crate::model::{AppConfig, BackupConfig, MonitorStatus};
+use chrono::{DateTime, Local, Utc};
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+const SNAPSHOT_KEY: &str = "snapshot.json";
+
+/// 上传到对象存储 (或通过 `/api/backup` 下载) 的快照：配置 + 所有目标的状态与历史记录 + 状态切换事件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub created_at: DateTime<Local>,
+    pub config: AppConfig,
+    pub statuses: Vec<MonitorStatus>,
+    // 旧版本的快照没有事件日志
+    #[serde(default)]
+    pub events: Vec<StateEvent>,
+}
+
+impl Snapshot {
+    /// 采集当前快照，事件日志读取失败时只记录错误 (会读取文件，应在阻塞线程中调用)
+    pub fn capture(state: &DashMap<String, MonitorStatus>, config: AppConfig) -> Self {
+        let events = crate::events::read(|_| true).unwrap_or_else(|e| {
+            tracing::error!("Failed to read state events for snapshot: {}", e);
+            Vec::new()
+        });
+        Self {
+            created_at: Local::now(),
+            config,
+            statuses: state.iter().map(|v| v.value().clone()).collect(),
+            events,
+        }
+    }
+}
+
+/// 周期性备份任务，每次读取最新的 backup 配置，未配置时空转
+pub async fn backup_task(
+    state: Arc<DashMap<String, MonitorStatus>>,
+    config_rx: watch::Receiver<AppConfig>,
+) {
+    loop {
+        let config = config_rx.borrow().clone();
+        let Some(backup) = config.backup.clone() else {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            continue;
+        };
+        tokio::time::sleep(Duration::from_secs(backup.interval_secs.max(60))).await;
+
+        let config = config_rx.borrow().clone();
+        let snapshot_state = state.clone();
+        let snapshot =
+            match tokio::task::spawn_blocking(move || Snapshot::capture(&snapshot_state, config))
+                .await
+            {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    tracing::error!("Failed to capture snapshot: {}", e);
+                    continue;
+                }
+            };
+        match upload_snapshot(&backup, &snapshot).await {
+            Ok(()) => tracing::info!(
+                "Backup uploaded to s3://{}/{}",
+                backup.bucket,
+                object_key(&backup)
+            ),
+            Err(e) => tracing::error!("Backup upload failed: {}", e),
+        }
+    }
+}
+
+pub async fn upload_snapshot(backup: &BackupConfig, snapshot: &Snapshot) -> anyhow::Result<()> {
+    let body = serde_json::to_vec(snapshot)?;
+    let res = signed_request(backup, reqwest::Method::PUT, body).await?;
+    if !res.status().is_success() {
+        anyhow::bail!(
+            "S3 PUT returned {}: {}",
+            res.status(),
+            res.text().await.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// 下载最近一次快照；对象不存在时返回 None
+pub async fn fetch_snapshot(backup: &BackupConfig) -> anyhow::Result<Option<Snapshot>> {
+    let res = signed_request(backup, reqwest::Method::GET, Vec::new()).await?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        anyhow::bail!("S3 GET returned {}", res.status());
+    }
+    Ok(Some(res.json().await?))
+}
+
+fn object_key(backup: &BackupConfig) -> String {
+    format!("{}{}", backup.prefix, SNAPSHOT_KEY)
+}
+
+// 使用 AWS Signature V4 签名的单对象请求，兼容 AWS S3 / MinIO / R2 等
+async fn signed_request(
+    backup: &BackupConfig,
+    method: reqwest::Method,
+    body: Vec<u8>,
+) -> anyhow::Result<reqwest::Response> {
+    let endpoint = reqwest::Url::parse(&backup.endpoint)?;
+    let endpoint_host = endpoint
+        .host_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid S3 endpoint: {}", backup.endpoint))?;
+    let authority = match endpoint.port() {
+        Some(port) => format!("{}:{}", endpoint_host, port),
+        None => endpoint_host.to_string(),
+    };
+
+    let key = uri_encode_path(&object_key(backup));
+    let (host, path) = if backup.path_style {
+        (authority, format!("/{}/{}", backup.bucket, key))
+    } else {
+        (
+            format!("{}.{}", backup.bucket, authority),
+            format!("/{}", key),
+        )
+    };
+    let url = format!("{}://{}{}", endpoint.scheme(), host, path);
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(&body));
+
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+        method, path, host, payload_hash, amz_date, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, backup.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = hmac_sha256(
+        format!("AWS4{}", backup.secret_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [backup.region.as_str(), "s3", "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+        backup.access_key, scope, signature
+    );
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()?;
+    let res = client
+        .request(method, &url)
+        .header("x-amz-date", amz_date)
+        .header("x-amz-content-sha256", payload_hash)
+        .header(reqwest::header::AUTHORIZATION, authorization)
+        .body(body)
+        .send()
+        .await?;
+    Ok(res)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// SigV4 要求路径按 RFC 3986 编码，'/' 保留
+fn uri_encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
@@ -0,0 +1,296 @@
+use crate::config;
+use crate::model::{AlertConfig, MonitorStatus};
+use crate::store;
+use chrono::{DateTime, Local};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::Path;
+
+const CACHE_FILE: &str = "cache.json";
+// 使用 SQLite 状态库时缓存保存在该键下，内容与缓存文件相同
+const STORE_CACHE_KEY: &str = "cache";
+
+// v1: 早期版本直接保存 MonitorStatus 数组，没有版本号与校验和
+// v2: { schema_version, saved_at, entries: [{ checksum, status }] }
+const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    schema_version: u32,
+    saved_at: DateTime<Local>,
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    // status 规范化 JSON (键有序) 的 SHA-256
+    checksum: String,
+    status: serde_json::Value,
+}
+
+/// 启动时缓存校验结果，供系统 API 展示
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct IntegrityReport {
+    pub schema_version: Option<u32>,
+    pub migrated: bool,
+    pub restored: usize,
+    pub dropped: usize,
+    pub quarantined: Option<String>,
+    pub errors: Vec<String>,
+}
+
+static INTEGRITY: OnceCell<IntegrityReport> = OnceCell::new();
+
+pub fn integrity_report() -> Option<IntegrityReport> {
+    INTEGRITY.get().cloned()
+}
+
+// 环境变量模式下默认不写缓存，除非通过 NETWATCH_CACHE_PATH 指定可写位置
+pub fn cache_path() -> Option<String> {
+    match env::var("NETWATCH_CACHE_PATH") {
+        Ok(path) => Some(path),
+        Err(_) if config::is_env_mode() => None,
+        Err(_) => Some(CACHE_FILE.to_string()),
+    }
+}
+
+/// 缓存的保存位置：设置了 NETWATCH_STATE_DB 时为 SQLite 状态库，否则为缓存文件
+enum Location {
+    Store,
+    File(String),
+}
+
+impl Location {
+    fn current() -> Option<Self> {
+        if store::is_enabled() {
+            return Some(Location::Store);
+        }
+        cache_path().map(Location::File)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Location::Store => format!(
+                "state database {}",
+                store::state_db_path().unwrap_or_default()
+            ),
+            Location::File(path) => format!("cache file {}", path),
+        }
+    }
+
+    fn read(&self) -> anyhow::Result<Option<String>> {
+        match self {
+            Location::Store => store::get(STORE_CACHE_KEY),
+            Location::File(path) if !Path::new(path).exists() => Ok(None),
+            Location::File(path) => Ok(Some(std::fs::read_to_string(path)?)),
+        }
+    }
+
+    fn write(&self, json: &str) -> anyhow::Result<()> {
+        match self {
+            Location::Store => store::put(STORE_CACHE_KEY, json),
+            Location::File(path) => {
+                // 先写临时文件再替换，避免中途退出留下半个文件
+                let tmp = format!("{}.tmp", path);
+                std::fs::write(&tmp, json)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 把原始内容另存一份供事后排查，返回保存位置
+    fn quarantine(&self, content: &str) -> anyhow::Result<String> {
+        let suffix = format!("corrupt-{}", Local::now().format("%Y%m%d%H%M%S"));
+        match self {
+            Location::Store => {
+                let key = format!("{}.{}", STORE_CACHE_KEY, suffix);
+                store::put(&key, content)?;
+                Ok(format!("state key {}", key))
+            }
+            Location::File(path) => {
+                let quarantine = format!("{}.{}", path, suffix);
+                std::fs::write(&quarantine, content)?;
+                Ok(quarantine)
+            }
+        }
+    }
+}
+
+pub fn cache_exists() -> bool {
+    Location::current().is_some_and(|location| matches!(location.read(), Ok(Some(_))))
+}
+
+fn checksum(status: &serde_json::Value) -> String {
+    hex::encode(Sha256::digest(status.to_string().as_bytes()))
+}
+
+pub fn save_cache(state: &DashMap<String, MonitorStatus>) {
+    let Some(location) = Location::current() else {
+        return;
+    };
+    tracing::info!("Saving monitor cache to {}", location.describe());
+    let entries = state
+        .iter()
+        // 经字符串中转而非 to_value，f32 延迟值保持最短表示，不被展开成 f64 长小数
+        .filter_map(|v| serde_json::to_string(v.value()).ok())
+        .filter_map(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .map(|status| CacheEntry {
+            checksum: checksum(&status),
+            status,
+        })
+        .collect();
+    let file = CacheFile {
+        schema_version: SCHEMA_VERSION,
+        saved_at: Local::now(),
+        entries,
+    };
+    match serde_json::to_string(&file) {
+        Ok(json) => {
+            if let Err(e) = location.write(&json) {
+                tracing::error!("Failed to write cache: {:#}", e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to serialize cache: {}", e),
+    }
+}
+
+/// 加载并校验缓存：能恢复的目标照常恢复，损坏的条目丢弃；
+/// 发现任何问题时把原文件复制到隔离路径并发送系统告警，而不是静默丢弃历史。
+pub fn load_cache(state: &DashMap<String, MonitorStatus>, alert: &AlertConfig) {
+    let Some(location) = Location::current() else {
+        return;
+    };
+
+    let mut report = IntegrityReport::default();
+    let content = match location.read() {
+        Ok(Some(content)) => content,
+        Ok(None) => return,
+        Err(e) => {
+            report.errors.push(format!("Failed to read cache: {:#}", e));
+            String::new()
+        }
+    };
+    tracing::info!("Loading monitor cache from {}", location.describe());
+    let statuses = if report.errors.is_empty() {
+        parse_cache(&content, &mut report)
+    } else {
+        Vec::new()
+    };
+    report.restored = statuses.len();
+    restore_statuses(state, statuses);
+
+    if !report.errors.is_empty() {
+        if !content.is_empty() {
+            match location.quarantine(&content) {
+                Ok(quarantine) => report.quarantined = Some(quarantine),
+                Err(e) => tracing::error!("Failed to quarantine cache: {:#}", e),
+            }
+        }
+        for e in &report.errors {
+            tracing::error!("Cache integrity: {}", e);
+        }
+        let message = format!(
+            "Cache in {} failed integrity check ({} restored, {} dropped), original kept at {}: {}",
+            location.describe(),
+            report.restored,
+            report.dropped,
+            report.quarantined.as_deref().unwrap_or("-"),
+            report.errors.join("; ")
+        );
+        let alert = alert.clone();
+        tokio::spawn(async move {
+            let _ = crate::alert::send_system_alert(&alert, false, &message).await;
+        });
+    } else if report.migrated {
+        tracing::info!(
+            "Migrated cache from schema v1 to v{} ({} targets)",
+            SCHEMA_VERSION,
+            report.restored
+        );
+    }
+    let _ = INTEGRITY.set(report);
+}
+
+fn parse_cache(content: &str, report: &mut IntegrityReport) -> Vec<MonitorStatus> {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(e) => {
+            report.errors.push(format!("Invalid JSON: {}", e));
+            return Vec::new();
+        }
+    };
+
+    // v1 (无版本号的数组) 按条目迁移，没有校验和可比对
+    if let serde_json::Value::Array(items) = value {
+        report.schema_version = Some(1);
+        report.migrated = true;
+        return items
+            .into_iter()
+            .filter_map(|item| decode_status(item, report))
+            .collect();
+    }
+
+    let file: CacheFile = match serde_json::from_value(value) {
+        Ok(f) => f,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("Unrecognized cache layout: {}", e));
+            return Vec::new();
+        }
+    };
+    report.schema_version = Some(file.schema_version);
+    if file.schema_version > SCHEMA_VERSION {
+        report.errors.push(format!(
+            "Cache schema v{} is newer than supported v{}",
+            file.schema_version, SCHEMA_VERSION
+        ));
+        return Vec::new();
+    }
+
+    file.entries
+        .into_iter()
+        .filter_map(|entry| {
+            if checksum(&entry.status) != entry.checksum {
+                let id = entry.status["target"]["id"].as_str().unwrap_or("?");
+                report
+                    .errors
+                    .push(format!("Checksum mismatch for target {}", id));
+                report.dropped += 1;
+                return None;
+            }
+            decode_status(entry.status, report)
+        })
+        .collect()
+}
+
+fn decode_status(value: serde_json::Value, report: &mut IntegrityReport) -> Option<MonitorStatus> {
+    let id = value["target"]["id"].as_str().unwrap_or("?").to_string();
+    match serde_json::from_value(value) {
+        Ok(status) => Some(status),
+        Err(e) => {
+            report.errors.push(format!("Target {}: {}", id, e));
+            report.dropped += 1;
+            None
+        }
+    }
+}
+
+pub fn restore_statuses(state: &DashMap<String, MonitorStatus>, items: Vec<MonitorStatus>) {
+    for item in items {
+        // 我们只恢复 targets 列表中存在的 target 的状态
+        if let Some(mut existing) = state.get_mut(&item.target.id) {
+            existing.records = item.records;
+            existing.current_state = item.current_state;
+            existing.unreachable = item.unreachable;
+            existing.totals = item.totals;
+            existing.incident = item.incident;
+            existing.last_change = item.last_change;
+            tracing::info!("Restored cache for target: {}", item.target.name);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::model::ProbeRecord;
+use chrono::{DateTime, Duration, Local};
+use plotters::prelude::*;
+use std::collections::VecDeque;
+
+// 与仪表盘深色主题一致
+const BACKGROUND: RGBColor = RGBColor(15, 23, 42);
+const LINE: RGBColor = RGBColor(34, 197, 94);
+const FAILED: RGBColor = RGBColor(239, 68, 68);
+
+/// 把 `now - window` 至今的延迟绘制为 PNG 迷你折线图 (不含坐标轴与文字)，
+/// 便于嵌入告警、邮件与聊天消息。失败的探测显示为红色竖条，折线在此处断开；
+/// 维护期间的记录不绘制。`interval` 为探测间隔，决定失败竖条的最小宽度
+pub fn sparkline_png(
+    records: &VecDeque<ProbeRecord>,
+    window: Duration,
+    now: DateTime<Local>,
+    interval: std::time::Duration,
+    (width, height): (u32, u32),
+) -> anyhow::Result<Vec<u8>> {
+    let from = (now - window).timestamp();
+    let to = now.timestamp().max(from + 1);
+    // 记录从新到旧排列，绘制时按时间顺序
+    let records: Vec<&ProbeRecord> = records
+        .iter()
+        .take_while(|r| r.timestamp.timestamp() >= from)
+        .filter(|r| !r.maintenance)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    // 连续成功的探测组成一段折线
+    let mut segments: Vec<Vec<(i64, f64)>> = vec![Vec::new()];
+    let mut failures = Vec::new();
+    for record in &records {
+        let ok = record.rollup.as_ref().map_or(record.success, |r| r.ok > 0);
+        match record.latency_ms.filter(|_| ok) {
+            Some(latency) => segments
+                .last_mut()
+                .unwrap()
+                .push((record.timestamp.timestamp(), f64::from(latency))),
+            None => {
+                let span = record
+                    .rollup
+                    .as_ref()
+                    .map_or(interval.as_secs(), |r| u64::from(r.span_secs));
+                let start = record.timestamp.timestamp();
+                failures.push((start, start + span.max(1) as i64));
+                segments.push(Vec::new());
+            }
+        }
+    }
+    let max = segments
+        .iter()
+        .flatten()
+        .map(|(_, latency)| *latency)
+        .fold(0.0, f64::max);
+    let top = if max > 0.0 { max * 1.1 } else { 1.0 };
+
+    let mut buffer = vec![0u8; width as usize * height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&BACKGROUND)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(2)
+            .build_cartesian_2d(from..to, 0.0..top)?;
+        // 窗口很长时竖条至少 1 像素宽
+        let min_span = (to - from) / i64::from(width.max(1)) + 1;
+        chart.draw_series(failures.iter().map(|&(start, end)| {
+            Rectangle::new(
+                [(start, 0.0), (end.max(start + min_span), top)],
+                FAILED.mix(0.6).filled(),
+            )
+        }))?;
+        for segment in segments.into_iter().filter(|s| !s.is_empty()) {
+            chart.draw_series(
+                AreaSeries::new(segment, 0.0, LINE.mix(0.15)).border_style(LINE.stroke_width(2)),
+            )?;
+        }
+        root.present()?;
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&buffer)?;
+    Ok(png)
+}
@@ -1,11 +1,62 @@
-use crate::model::{AppConfig, Protocol, Target};
+use crate::model::{
+    AppConfig, GrpcConfig, PersistenceFailurePolicy, Protocol, Target, WebhookConfig,
+};
 use anyhow::Context;
-use once_cell::sync::OnceCell;
+use chrono::{DateTime, Local};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 static CONFIG_PATH: OnceCell<String> = OnceCell::new();
 
+// 降级 (仅内存) 模式下，每隔该时间才重新尝试写入配置文件
+const DEGRADED_RETRY: Duration = Duration::from_secs(300);
+
+struct Degraded {
+    since: DateTime<Local>,
+    reason: String,
+    last_attempt: Instant,
+}
+
+static DEGRADED: Lazy<Mutex<Option<Degraded>>> = Lazy::new(|| Mutex::new(None));
+
+/// "读取当前配置 → 修改 → 保存 → 广播" 的各条路径都需持有该锁，避免并发修改互相覆盖
+pub static UPDATE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 配置持久化状态，供 /healthz 与系统 API 展示
+#[derive(Debug, Clone, Serialize)]
+pub struct PersistenceStatus {
+    pub env_mode: bool,
+    pub state_db: bool,
+    pub degraded: bool,
+    pub since: Option<DateTime<Local>>,
+    pub reason: Option<String>,
+}
+
+pub fn persistence_status() -> PersistenceStatus {
+    let guard = DEGRADED.lock().unwrap();
+    PersistenceStatus {
+        env_mode: is_env_mode(),
+        state_db: crate::store::is_enabled(),
+        degraded: guard.is_some(),
+        since: guard.as_ref().map(|d| d.since),
+        reason: guard.as_ref().map(|d| d.reason.clone()),
+    }
+}
+
+pub fn is_degraded() -> bool {
+    DEGRADED.lock().unwrap().is_some()
+}
+
+/// 通过 API 修改的配置能否在重启后保留：未降级，且写入配置文件或 SQLite 状态库
+pub fn is_persisted() -> bool {
+    !is_degraded() && (!is_env_mode() || crate::store::is_enabled())
+}
+
 pub fn init_config_path(path: String) {
     if CONFIG_PATH.set(path).is_err() {
         tracing::warn!("Config path already initialized");
@@ -19,7 +70,100 @@ fn get_config_path() -> &'static str {
         .unwrap_or("config.json")
 }
 
+/// 12-factor 模式：设置了 `NETWATCH_TARGETS_JSON` 时完全从环境变量读取配置，
+/// 不读写 config.json，适用于只读文件系统的容器平台。
+pub fn is_env_mode() -> bool {
+    std::env::var_os("NETWATCH_TARGETS_JSON").is_some()
+}
+
+fn load_env_config() -> anyhow::Result<AppConfig> {
+    let targets_json = std::env::var("NETWATCH_TARGETS_JSON").unwrap_or_default();
+    let targets: Vec<Target> =
+        serde_json::from_str(&targets_json).context("Failed to parse NETWATCH_TARGETS_JSON")?;
+
+    let mut config = AppConfig {
+        targets,
+        ..get_default_config()
+    };
+
+    if let Ok(json) = std::env::var("NETWATCH_ALERT_WEBHOOKS_JSON") {
+        config.alert.webhooks = serde_json::from_str::<Vec<WebhookConfig>>(&json)
+            .context("Failed to parse NETWATCH_ALERT_WEBHOOKS_JSON")?;
+    }
+    if let Ok(url) = std::env::var("NETWATCH_ALERT_WEBHOOK_URL") {
+        config.alert.webhooks.push(WebhookConfig {
+            id: "env".to_string(),
+            name: "Environment Webhook".to_string(),
+            url,
+            template: std::env::var("NETWATCH_ALERT_TEMPLATE").ok(),
+            ..Default::default()
+        });
+    }
+    config.alert.enabled = match std::env::var("NETWATCH_ALERT_ENABLED") {
+        Ok(v) => matches!(v.as_str(), "1" | "true" | "yes"),
+        Err(_) => !config.alert.webhooks.is_empty(),
+    };
+
+    if let Ok(days) = std::env::var("NETWATCH_RETENTION_DAYS") {
+        config.data_retention_days = days
+            .parse()
+            .context("Failed to parse NETWATCH_RETENTION_DAYS")?;
+    }
+    if let Ok(records) = std::env::var("NETWATCH_RETENTION_RECORDS") {
+        config.data_retention_records = Some(
+            records
+                .parse()
+                .context("Failed to parse NETWATCH_RETENTION_RECORDS")?,
+        )
+        .filter(|n| *n > 0);
+    }
+    if let Ok(records) = std::env::var("NETWATCH_LIVE_INIT_RECORDS") {
+        config.live_init_records = records
+            .parse()
+            .context("Failed to parse NETWATCH_LIVE_INIT_RECORDS")?;
+    }
+    if let Ok(secs) = std::env::var("NETWATCH_PROBE_INTERVAL_SECS") {
+        config.probe_interval_secs = secs
+            .parse()
+            .context("Failed to parse NETWATCH_PROBE_INTERVAL_SECS")?;
+    }
+    if let Ok(base_path) = std::env::var("NETWATCH_BASE_PATH") {
+        config.base_path = Some(base_path);
+    }
+    if let Ok(listen) = std::env::var("NETWATCH_GRPC_LISTEN") {
+        config.grpc = Some(GrpcConfig { listen });
+    }
+
+    Ok(config)
+}
+
+/// 规范化 URL 前缀：以 `/` 开头、不以 `/` 结尾，未配置或为 `/` 时为空字符串
+pub fn base_path(config: &AppConfig) -> String {
+    let path = config.base_path.as_deref().unwrap_or("").trim_matches('/');
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+// 状态库中的键：当前配置，以及环境变量模式下该配置所基于的环境变量配置摘要
+const STORE_CONFIG_KEY: &str = "config";
+const STORE_ENV_SEED_KEY: &str = "config_env_seed";
+
 pub fn load_config() -> anyhow::Result<AppConfig> {
+    if is_env_mode() {
+        let config = load_env_config()?;
+        if crate::store::is_enabled() {
+            return load_env_stored_config(config);
+        }
+        tracing::info!("Loading configuration from environment (read-only mode)");
+        return Ok(config);
+    }
+    if crate::store::is_enabled() {
+        return load_stored_config();
+    }
+
     let path = get_config_path();
     if !Path::new(path).exists() {
         tracing::info!("Config file not found at {}, creating default.", path);
@@ -29,6 +173,7 @@ pub fn load_config() -> anyhow::Result<AppConfig> {
     }
 
     let content = fs::read_to_string(path).context("Failed to read config file")?;
+    warn_legacy_tls(&content);
     match serde_json::from_str::<AppConfig>(&content) {
         Ok(config) => Ok(config),
         Err(e) => {
@@ -38,9 +183,126 @@ pub fn load_config() -> anyhow::Result<AppConfig> {
     }
 }
 
-pub fn save_config(config: &AppConfig) -> anyhow::Result<()> {
+// 旧版本的 HTTPS 证书路径写在配置文件中，现在只从启动参数读取，提示迁移而不是静默改用明文
+fn warn_legacy_tls(content: &str) {
+    if serde_json::from_str::<serde_json::Value>(content).is_ok_and(|v| v.get("tls").is_some()) {
+        tracing::warn!(
+            "Ignoring \"tls\" in config file; use --tls-cert/--tls-key or NETWATCH_TLS_CERT/NETWATCH_TLS_KEY"
+        );
+    }
+}
+
+/// 环境变量模式下使用状态库：环境变量不变时沿用库中通过 API 修改过的配置，
+/// 环境变量变化 (例如重新部署) 时以新的环境变量为准并覆盖库中的配置
+fn load_env_stored_config(env_config: AppConfig) -> anyhow::Result<AppConfig> {
+    let seed = hex::encode(Sha256::digest(serde_json::to_vec(&env_config)?));
+    if crate::store::get(STORE_ENV_SEED_KEY)?.as_deref() == Some(seed.as_str()) {
+        if let Some(content) = crate::store::get(STORE_CONFIG_KEY)? {
+            tracing::info!("Loading configuration from state database");
+            return serde_json::from_str(&content)
+                .context("Failed to parse config stored in state database");
+        }
+    }
+    tracing::info!("Loading configuration from environment into state database");
+    crate::store::put(
+        STORE_CONFIG_KEY,
+        &serde_json::to_string_pretty(&env_config)?,
+    )?;
+    crate::store::put(STORE_ENV_SEED_KEY, &seed)?;
+    Ok(env_config)
+}
+
+/// 状态库中还没有配置时，导入现有的 config.json (不存在则使用默认配置)
+fn load_stored_config() -> anyhow::Result<AppConfig> {
+    if let Some(content) = crate::store::get(STORE_CONFIG_KEY)? {
+        return serde_json::from_str(&content)
+            .context("Failed to parse config stored in state database");
+    }
     let path = get_config_path();
+    let config = if Path::new(path).exists() {
+        tracing::info!("Importing {} into state database", path);
+        let content = fs::read_to_string(path).context("Failed to read config file")?;
+        warn_legacy_tls(&content);
+        serde_json::from_str(&content).context("Failed to parse config file")?
+    } else {
+        tracing::info!("No config in state database, creating default.");
+        get_default_config()
+    };
+    write_config(&config)?;
+    Ok(config)
+}
+
+pub fn save_config(config: &AppConfig) -> anyhow::Result<()> {
+    if is_env_mode() && !crate::store::is_enabled() {
+        // 环境变量模式下配置只保存在内存中
+        tracing::debug!("Environment mode: skipping config write");
+        return Ok(());
+    }
+
+    // 降级模式下不反复写盘刷错误日志，只按间隔重试，成功后自动恢复
+    {
+        let guard = DEGRADED.lock().unwrap();
+        if guard
+            .as_ref()
+            .is_some_and(|d| d.last_attempt.elapsed() < DEGRADED_RETRY)
+        {
+            tracing::debug!("Degraded mode: keeping config in memory only");
+            return Ok(());
+        }
+    }
+
+    let result = write_config(config);
+    let mut guard = DEGRADED.lock().unwrap();
+    match (result, guard.as_mut()) {
+        (Ok(()), None) => Ok(()),
+        (Ok(()), Some(_)) => {
+            tracing::info!("Config file writable again, leaving in-memory mode");
+            *guard = None;
+            system_alert(config, true, "Config file is writable again".to_string());
+            Ok(())
+        }
+        (Err(e), Some(degraded)) => {
+            degraded.last_attempt = Instant::now();
+            degraded.reason = format!("{:#}", e);
+            Ok(())
+        }
+        (Err(e), None) if config.persistence_failure == PersistenceFailurePolicy::MemoryOnly => {
+            tracing::error!(
+                "Config file is not writable, switching to in-memory mode: {:#}",
+                e
+            );
+            *guard = Some(Degraded {
+                since: Local::now(),
+                reason: format!("{:#}", e),
+                last_attempt: Instant::now(),
+            });
+            system_alert(
+                config,
+                false,
+                format!(
+                    "Config file is not writable, running in-memory only: {:#}",
+                    e
+                ),
+            );
+            Ok(())
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+fn system_alert(config: &AppConfig, healthy: bool, message: String) {
+    let alert = config.alert.clone();
+    tokio::spawn(async move {
+        let _ = crate::alert::send_system_alert(&alert, healthy, &message).await;
+    });
+}
+
+fn write_config(config: &AppConfig) -> anyhow::Result<()> {
     let content = serde_json::to_string_pretty(config)?;
+    if crate::store::is_enabled() {
+        return crate::store::put(STORE_CONFIG_KEY, &content);
+    }
+    let path = get_config_path();
     let tmp_file = format!("{}.tmp", path);
     fs::write(&tmp_file, content).context("Failed to write temp config file")?;
     fs::rename(&tmp_file, path).context("Failed to replace config file")?;
@@ -56,8 +318,7 @@ fn get_default_config() -> AppConfig {
                 port: Some(53),
                 name: "Google DNS (TCP)".to_string(),
                 protocol: Protocol::Tcp,
-                threshold: 3,
-                last_known_state: None,
+                ..Default::default()
             },
             Target {
                 id: "2".to_string(),
@@ -65,8 +326,7 @@ fn get_default_config() -> AppConfig {
                 port: None,
                 name: "Cloudflare Ping".to_string(),
                 protocol: Protocol::Icmp,
-                threshold: 3,
-                last_known_state: None,
+                ..Default::default()
             },
             Target {
                 id: "3".to_string(),
@@ -74,8 +334,7 @@ fn get_default_config() -> AppConfig {
                 port: Some(53),
                 name: "Google DNS Query".to_string(),
                 protocol: Protocol::Dns,
-                threshold: 3,
-                last_known_state: None,
+                ..Default::default()
             },
             Target {
                 id: "4".to_string(),
@@ -83,11 +342,35 @@ fn get_default_config() -> AppConfig {
                 port: None,
                 name: "Google Web (HTTPS)".to_string(),
                 protocol: Protocol::Https,
-                threshold: 3,
-                last_known_state: None,
+                ..Default::default()
             },
         ],
         alert: Default::default(),
         data_retention_days: 3,
+        data_retention_records: None,
+        live_init_records: 360,
+        http: Default::default(),
+        dns_cache_size: 256,
+        backup: None,
+        influx: None,
+        remote_write: None,
+        bind: None,
+        namespaces: Vec::new(),
+        admin_api_keys: Vec::new(),
+        viewer_api_keys: Vec::new(),
+        auth: None,
+        status_page: None,
+        base_path: None,
+        grpc: None,
+        persistence_failure: PersistenceFailurePolicy::MemoryOnly,
+        groups: Vec::new(),
+        probe_interval_secs: 10,
+        stagger_probes: true,
+        threshold: 3,
+        threshold_up: None,
+        threshold_down: None,
+        maintenance: Default::default(),
+        grace_period_secs: 0,
+        downsample: Default::default(),
     }
 }
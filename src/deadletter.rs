@@ -0,0 +1,89 @@
+use crate::config;
+use crate::model::{ChannelKind, TargetState};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+
+const DEAD_LETTERS_FILE: &str = "dead_letters.jsonl";
+
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 重试耗尽仍未投递成功的告警，逐行追加到死信日志，便于事后排查与手工补发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub timestamp: DateTime<Local>,
+    pub channel_id: String,
+    pub channel: String,
+    pub kind: ChannelKind,
+    pub target_id: String,
+    pub target_name: String,
+    pub state: TargetState,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+    // 告警产生的时间
+    pub alerted_at: DateTime<Local>,
+    pub attempts: u32,
+    // 最后一次失败的原因
+    pub error: String,
+}
+
+// 与事件日志相同：环境变量模式下默认不写，除非通过 NETWATCH_DEAD_LETTERS_PATH 指定可写位置
+pub fn dead_letters_path() -> Option<String> {
+    match env::var("NETWATCH_DEAD_LETTERS_PATH") {
+        Ok(path) => Some(path),
+        Err(_) if config::is_env_mode() => None,
+        Err(_) => Some(DEAD_LETTERS_FILE.to_string()),
+    }
+}
+
+/// 在阻塞线程池中追加一条死信
+pub fn record(letter: DeadLetter) {
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = append(&letter) {
+            tracing::error!("Failed to append dead letter: {}", e);
+        }
+    });
+}
+
+fn append(letter: &DeadLetter) -> anyhow::Result<()> {
+    let Some(path) = dead_letters_path() else {
+        return Ok(());
+    };
+    let mut line = serde_json::to_string(letter)?;
+    line.push('\n');
+    let _guard = WRITE_LOCK.lock().unwrap();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 按文件顺序 (从旧到新) 读取满足条件的死信，无法解析的行跳过
+pub fn read(filter: impl Fn(&DeadLetter) -> bool) -> anyhow::Result<Vec<DeadLetter>> {
+    let Some(path) = dead_letters_path() else {
+        return Ok(Vec::new());
+    };
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut letters = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        match serde_json::from_str::<DeadLetter>(&line) {
+            Ok(letter) if filter(&letter) => letters.push(letter),
+            Ok(_) => {}
+            Err(e) if !line.trim().is_empty() => {
+                tracing::warn!("Skipping malformed dead letter: {}", e)
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(letters)
+}
@@ -0,0 +1,96 @@
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use trust_dns_resolver::config::{
+    NameServerConfig, Protocol as DnsProtocol, ResolverConfig, ResolverOpts,
+};
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::TokioAsyncResolver;
+
+// 所有探测共享的解析器，内置按 TTL 过期的缓存
+static SYSTEM_RESOLVER: OnceCell<TokioAsyncResolver> = OnceCell::new();
+// Target 指定了 DNS 服务器时，按服务器列表复用解析器 (同样带缓存)
+static CUSTOM_RESOLVERS: Lazy<DashMap<Vec<String>, TokioAsyncResolver>> = Lazy::new(DashMap::new);
+static CACHE_SIZE: OnceCell<usize> = OnceCell::new();
+
+/// 启动时调用，设置缓存容量并预先构建系统解析器
+pub fn init_resolver(cache_size: usize) {
+    if CACHE_SIZE.set(cache_size).is_err() {
+        tracing::warn!("DNS resolver already initialized");
+    }
+    match system_resolver() {
+        Ok(_) => tracing::info!("Shared DNS resolver ready (cache size {})", cache_size),
+        Err(e) => tracing::warn!("System DNS resolver unavailable: {}", e),
+    }
+}
+
+fn cache_size() -> usize {
+    CACHE_SIZE.get().copied().unwrap_or(256)
+}
+
+fn system_resolver() -> Result<&'static TokioAsyncResolver, String> {
+    SYSTEM_RESOLVER.get_or_try_init(|| {
+        let (config, mut opts) = trust_dns_resolver::system_conf::read_system_conf()
+            .map_err(|e| format!("Resolver init failed: {}", e))?;
+        opts.cache_size = cache_size();
+        Ok(TokioAsyncResolver::tokio(config, opts))
+    })
+}
+
+fn custom_resolver(servers: &[String]) -> Result<TokioAsyncResolver, String> {
+    if let Some(resolver) = CUSTOM_RESOLVERS.get(servers) {
+        return Ok(resolver.clone());
+    }
+
+    let mut config = ResolverConfig::new();
+    for server in servers {
+        let addr = match server.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(_) => match server.parse::<IpAddr>() {
+                Ok(ip) => SocketAddr::new(ip, 53),
+                Err(_) => return Err(format!("Invalid resolver address: {}", server)),
+            },
+        };
+        config.add_name_server(NameServerConfig::new(addr, DnsProtocol::Udp));
+        config.add_name_server(NameServerConfig::new(addr, DnsProtocol::Tcp));
+    }
+    let mut opts = ResolverOpts::default();
+    opts.cache_size = cache_size();
+
+    let resolver = TokioAsyncResolver::tokio(config, opts);
+    CUSTOM_RESOLVERS.insert(servers.to_vec(), resolver.clone());
+    Ok(resolver)
+}
+
+fn resolver_for(servers: &[String]) -> Result<TokioAsyncResolver, String> {
+    if servers.is_empty() {
+        Ok(system_resolver()?.clone())
+    } else {
+        custom_resolver(servers)
+    }
+}
+
+/// 解析主机名。`servers` 非空时通过指定的 DNS 服务器解析 (用于 split-horizon 等场景)，
+/// 否则使用系统配置。
+pub async fn resolve_ip(host: &str, servers: &[String]) -> Result<IpAddr, String> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(ip);
+    }
+
+    match resolver_for(servers)?.lookup_ip(host).await {
+        Ok(ips) => ips
+            .iter()
+            .next()
+            .ok_or_else(|| "DNS resolution failed".to_string()),
+        Err(e) => Err(format!("DNS error: {}", e)),
+    }
+}
+
+/// 查询 A 记录，区分 "不存在" (NXDOMAIN / 无记录，返回 `Ok(None)`) 与查询失败，供 DNSBL 等场景使用。
+pub async fn lookup_a(name: &str, servers: &[String]) -> Result<Option<Vec<Ipv4Addr>>, String> {
+    match resolver_for(servers)?.ipv4_lookup(name).await {
+        Ok(records) => Ok(Some(records.iter().map(|a| a.0).collect())),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(None),
+        Err(e) => Err(format!("DNS error: {}", e)),
+    }
+}
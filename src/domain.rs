@@ -0,0 +1,167 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::{timeout, Duration};
+
+// IANA RDAP bootstrap：TLD -> RDAP 服务地址
+const BOOTSTRAP_URL: &str = "https://data.iana.org/rdap/dns.json";
+const IANA_WHOIS: &str = "whois.iana.org";
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_WHOIS_RESPONSE: u64 = 64 * 1024;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(QUERY_TIMEOUT)
+        .user_agent(concat!("NetWatch/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("Failed to build RDAP client")
+});
+
+// 获取失败时不缓存，下次查询重试
+static BOOTSTRAP: Lazy<Mutex<Option<HashMap<String, String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 查询域名注册到期时间：优先 RDAP，TLD 没有 RDAP 服务时回落到 WHOIS。
+/// 返回到期时间及数据来源 ("RDAP" / "WHOIS")。
+pub async fn expiry_date(domain: &str) -> Result<(DateTime<Utc>, &'static str), String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    let tld = domain
+        .rsplit('.')
+        .next()
+        .filter(|t| !t.is_empty() && *t != domain)
+        .ok_or_else(|| format!("Not a registrable domain: {}", domain))?;
+
+    match rdap_base(tld).await? {
+        Some(base) => rdap_expiry(&base, &domain).await.map(|d| (d, "RDAP")),
+        None => whois_expiry(&domain, tld).await.map(|d| (d, "WHOIS")),
+    }
+}
+
+async fn rdap_base(tld: &str) -> Result<Option<String>, String> {
+    let mut guard = BOOTSTRAP.lock().await;
+    if guard.is_none() {
+        let json: serde_json::Value = CLIENT
+            .get(BOOTSTRAP_URL)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("RDAP bootstrap failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid RDAP bootstrap: {}", e))?;
+
+        // services: [[["com", "net"], ["https://rdap.verisign.com/com/v1/"]], ...]
+        let mut map = HashMap::new();
+        for service in json["services"].as_array().into_iter().flatten() {
+            let base = service[1]
+                .as_array()
+                .and_then(|urls| {
+                    urls.iter()
+                        .filter_map(|u| u.as_str())
+                        .find(|u| u.starts_with("https://"))
+                        .or_else(|| urls.first().and_then(|u| u.as_str()))
+                })
+                .map(|u| u.to_string());
+            let Some(base) = base else { continue };
+            for tld in service[0].as_array().into_iter().flatten() {
+                if let Some(tld) = tld.as_str() {
+                    map.insert(tld.to_ascii_lowercase(), base.clone());
+                }
+            }
+        }
+        *guard = Some(map);
+    }
+    Ok(guard.as_ref().and_then(|m| m.get(tld).cloned()))
+}
+
+async fn rdap_expiry(base: &str, domain: &str) -> Result<DateTime<Utc>, String> {
+    let url = format!("{}/domain/{}", base.trim_end_matches('/'), domain);
+    let res = CLIENT
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/rdap+json")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("Domain {} not found in RDAP", domain));
+    }
+    let json: serde_json::Value = res
+        .error_for_status()
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid RDAP response: {}", e))?;
+
+    json["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|e| e["eventAction"].as_str() == Some("expiration"))
+        .and_then(|e| e["eventDate"].as_str())
+        .and_then(parse_date)
+        .ok_or_else(|| "RDAP response has no expiration event".to_string())
+}
+
+async fn whois_expiry(domain: &str, tld: &str) -> Result<DateTime<Utc>, String> {
+    // 先向 IANA 查询该 TLD 的 WHOIS 服务器
+    let iana = whois_query(IANA_WHOIS, tld).await?;
+    let server = whois_field(&iana, |key| key == "refer" || key == "whois")
+        .ok_or_else(|| format!("No WHOIS server for .{}", tld))?;
+
+    let response = whois_query(&server, domain).await?;
+    whois_field(&response, |key| {
+        key.contains("expir") || key == "paid-till" || key == "renewal date"
+    })
+    .and_then(|v| parse_date(&v))
+    .ok_or_else(|| format!("No expiry date in WHOIS response from {}", server))
+}
+
+async fn whois_query(server: &str, query: &str) -> Result<String, String> {
+    let exchange = async {
+        let mut stream = TcpStream::connect((server, 43)).await?;
+        stream
+            .write_all(format!("{}\r\n", query).as_bytes())
+            .await?;
+        let mut buf = Vec::new();
+        stream
+            .take(MAX_WHOIS_RESPONSE)
+            .read_to_end(&mut buf)
+            .await?;
+        Ok::<_, std::io::Error>(buf)
+    };
+    match timeout(QUERY_TIMEOUT, exchange).await {
+        Ok(Ok(buf)) => Ok(String::from_utf8_lossy(&buf).into_owned()),
+        Ok(Err(e)) => Err(format!("WHOIS {}: {}", server, e)),
+        Err(_) => Err(format!("WHOIS {}: Timeout", server)),
+    }
+}
+
+// WHOIS 为 "Key: Value" 文本，各注册局的字段名不统一
+fn whois_field(text: &str, matches: impl Fn(&str) -> bool) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let value = value.trim();
+        (matches(&key.trim().to_ascii_lowercase()) && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Some(dt.and_utc());
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s.get(..11).unwrap_or(s), "%d-%b-%Y") {
+        return d.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+    }
+    // 2025-08-13 / 2025.08.13 / 2025/08/13 以及其后带时间的变体
+    let date = s.get(..10)?.replace(['.', '/'], "-");
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
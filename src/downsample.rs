@@ -0,0 +1,173 @@
+use crate::model::{AppConfig, Downsample, MonitorStatus, ProbeRecord, RecordRollup};
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use dashmap::DashMap;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+const COMPACT_INTERVAL: Duration = Duration::from_secs(600);
+
+/// 定期把旧的原始探测记录合并为分钟/小时聚合记录，控制长时间保留时的内存与缓存体积
+pub async fn compaction_task(
+    state: Arc<DashMap<String, MonitorStatus>>,
+    config_rx: watch::Receiver<AppConfig>,
+) {
+    let mut ticker = tokio::time::interval(COMPACT_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let rules = config_rx.borrow().downsample.clone();
+        if rules.raw_hours == 0 {
+            continue;
+        }
+        let now = Local::now();
+        let (mut before, mut after) = (0, 0);
+        for mut entry in state.iter_mut() {
+            let records = &mut entry.value_mut().records;
+            before += records.len();
+            compact(records, &rules, now);
+            after += records.len();
+        }
+        if after < before {
+            tracing::info!("Downsampled probe records: {} -> {}", before, after);
+        }
+    }
+}
+
+/// 压缩单个目标的记录 (最新的在前)。只合并完整落在截止时间之前的时间桶，
+/// 已压缩过的桶保持不变，因此可以重复执行。
+pub fn compact(records: &mut VecDeque<ProbeRecord>, rules: &Downsample, now: DateTime<Local>) {
+    if rules.raw_hours == 0 {
+        return;
+    }
+    let minute_cutoff = (now - ChronoDuration::hours(rules.raw_hours as i64)).timestamp();
+    let hour_cutoff =
+        (now - ChronoDuration::hours(rules.minute_hours.max(rules.raw_hours) as i64)).timestamp();
+    let Some(split) = records
+        .iter()
+        .position(|r| r.timestamp.timestamp() < minute_cutoff)
+    else {
+        return;
+    };
+
+    // 从旧到新遍历，相邻且属于同一时间桶的记录合并
+    let mut compacted = Vec::new();
+    let mut bucket: Vec<ProbeRecord> = Vec::new();
+    let mut current: Option<(i64, u32)> = None;
+    for record in records.drain(split..).rev() {
+        let key = bucket_of(&record, minute_cutoff, hour_cutoff);
+        if key != current {
+            flush(&mut bucket, current, &mut compacted);
+            current = key;
+        }
+        bucket.push(record);
+    }
+    flush(&mut bucket, current, &mut compacted);
+    records.extend(compacted.into_iter().rev());
+}
+
+/// 记录所属的时间桶 (起点, 宽度秒数)；桶未完整落在截止时间之前时返回 None，保留原样
+fn bucket_of(record: &ProbeRecord, minute_cutoff: i64, hour_cutoff: i64) -> Option<(i64, u32)> {
+    let ts = record.timestamp.timestamp();
+    let hour = ts - ts.rem_euclid(3600);
+    let minute = ts - ts.rem_euclid(60);
+    if hour + 3600 <= hour_cutoff {
+        Some((hour, 3600))
+    } else if minute + 60 <= minute_cutoff && record.rollup.is_none() {
+        Some((minute, 60))
+    } else {
+        None
+    }
+}
+
+fn flush(bucket: &mut Vec<ProbeRecord>, key: Option<(i64, u32)>, out: &mut Vec<ProbeRecord>) {
+    match key {
+        Some((start, span)) if !bucket.is_empty() => out.push(merge(bucket, start, span)),
+        _ => out.append(bucket),
+    }
+    bucket.clear();
+}
+
+fn merge(bucket: &mut Vec<ProbeRecord>, start: i64, span_secs: u32) -> ProbeRecord {
+    if let [single] = bucket.as_slice() {
+        if single
+            .rollup
+            .as_ref()
+            .is_some_and(|r| r.span_secs == span_secs)
+        {
+            return single.clone();
+        }
+    }
+
+    // 维护期间的记录不计入统计，除非整个桶都在维护期间
+    let maintenance = bucket.iter().all(|r| r.maintenance);
+    let counted = bucket.iter().filter(|r| maintenance || !r.maintenance);
+
+    let (mut samples, mut ok) = (0u32, 0u32);
+    let (mut latency_sum, mut latency_n) = (0f64, 0u32);
+    let (mut min, mut max): (Option<f32>, Option<f32>) = (None, None);
+    let mut metrics: BTreeMap<String, (f64, u32)> = BTreeMap::new();
+    let mut message = None;
+    let mut degraded = None;
+    for record in counted {
+        let (n, good, low, high) = match &record.rollup {
+            Some(r) => (r.samples, r.ok, r.min_latency_ms, r.max_latency_ms),
+            None if record.success => (1, 1, record.latency_ms, record.latency_ms),
+            None => (1, 0, None, None),
+        };
+        samples += n;
+        ok += good;
+        if let Some(latency) = record.latency_ms.filter(|_| good > 0) {
+            latency_sum += latency as f64 * good as f64;
+            latency_n += good;
+        }
+        min = low.into_iter().chain(min).reduce(f32::min);
+        max = high.into_iter().chain(max).reduce(f32::max);
+        for (key, value) in &record.metrics {
+            let entry = metrics.entry(key.clone()).or_default();
+            entry.0 += value;
+            entry.1 += 1;
+        }
+        // 保留最近一次失败/降级的原因
+        if !record.success {
+            message = record.message.clone().or(message);
+        }
+        degraded = record.degraded.clone().or(degraded);
+    }
+
+    let timestamp = DateTime::from_timestamp(start, 0)
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or_else(|| bucket[0].timestamp);
+    ProbeRecord {
+        timestamp,
+        latency_ms: (latency_n > 0).then(|| (latency_sum / latency_n as f64) as f32),
+        success: ok > 0 && ok * 2 >= samples,
+        message,
+        reuse_latency_ms: None,
+        metrics: metrics
+            .into_iter()
+            .map(|(k, (sum, n))| (k, sum / n as f64))
+            .collect(),
+        content_hash: None,
+        timing: None,
+        status_code: None,
+        response_bytes: None,
+        ttl: None,
+        hops: None,
+        first_attempt_error: None,
+        maintenance,
+        degraded,
+        rollup: Some(RecordRollup {
+            span_secs,
+            samples,
+            ok,
+            uptime_ratio: if samples > 0 {
+                ok as f32 / samples as f32
+            } else {
+                0.0
+            },
+            min_latency_ms: min,
+            max_latency_ms: max,
+        }),
+    }
+}
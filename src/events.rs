@@ -0,0 +1,104 @@
+use crate::config;
+use crate::model::TargetState;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+
+const EVENTS_FILE: &str = "events.jsonl";
+
+// 保证多个目标同时切换状态时每行完整写入
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一次确认后的状态切换，逐行追加到事件日志，不受探测记录保留策略影响
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateEvent {
+    pub timestamp: DateTime<Local>,
+    pub target_id: String,
+    pub target_name: String,
+    pub from: TargetState,
+    pub to: TargetState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+// 与缓存相同：环境变量模式下默认不写，除非通过 NETWATCH_EVENTS_PATH 指定可写位置
+pub fn events_path() -> Option<String> {
+    match env::var("NETWATCH_EVENTS_PATH") {
+        Ok(path) => Some(path),
+        Err(_) if config::is_env_mode() => None,
+        Err(_) => Some(EVENTS_FILE.to_string()),
+    }
+}
+
+/// 在阻塞线程池中追加一条事件，不阻塞探测任务
+pub fn record(event: StateEvent) {
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = append(&event) {
+            tracing::error!("Failed to append state event: {}", e);
+        }
+    });
+}
+
+fn append(event: &StateEvent) -> anyhow::Result<()> {
+    let Some(path) = events_path() else {
+        return Ok(());
+    };
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    let _guard = WRITE_LOCK.lock().unwrap();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 按文件顺序 (从旧到新) 读取满足条件的事件，无法解析的行跳过
+pub fn read(filter: impl Fn(&StateEvent) -> bool) -> anyhow::Result<Vec<StateEvent>> {
+    let Some(path) = events_path() else {
+        return Ok(Vec::new());
+    };
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut events = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        match serde_json::from_str::<StateEvent>(&line) {
+            Ok(event) if filter(&event) => events.push(event),
+            Ok(_) => {}
+            Err(e) if !line.trim().is_empty() => {
+                tracing::warn!("Skipping malformed state event: {}", e)
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(events)
+}
+
+/// 用给定事件整体替换事件日志 (从备份恢复时使用)，先写临时文件再改名
+pub fn replace(events: &[StateEvent]) -> anyhow::Result<()> {
+    let Some(path) = events_path() else {
+        return Ok(());
+    };
+    let mut content = Vec::new();
+    for event in events {
+        serde_json::to_writer(&mut content, event)?;
+        content.push(b'\n');
+    }
+    let tmp = format!("{}.tmp", path);
+    let _guard = WRITE_LOCK.lock().unwrap();
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+pub fn log_exists() -> bool {
+    events_path().is_some_and(|path| std::path::Path::new(&path).exists())
+}
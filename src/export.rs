@@ -0,0 +1,85 @@
+use crate::model::ProbeRecord;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+pub const HISTORY_VERSION: u32 = 1;
+
+/// 单个目标完整历史的导出格式，可原样导入到其他实例 (目标 ID 以导入时的路径为准)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryExport {
+    pub version: u32,
+    pub target_id: String,
+    pub exported_at: DateTime<Local>,
+    // 与内存中一致，最新的在前
+    pub records: Vec<ProbeRecord>,
+}
+
+/// 合并导入的记录：时间戳相同时保留现有记录，导入记录中早于 `keep_since` (按天数保留时) 的丢弃，
+/// 合并后只保留最新的 `limit` 条，结果仍为最新在前。返回实际新增的条数。
+pub fn merge_history(
+    records: &mut VecDeque<ProbeRecord>,
+    imported: Vec<ProbeRecord>,
+    keep_since: Option<DateTime<Local>>,
+    limit: usize,
+) -> usize {
+    let existing = records.len();
+    let mut merged: BTreeMap<DateTime<Local>, ProbeRecord> = imported
+        .into_iter()
+        .filter(|r| keep_since.is_none_or(|since| r.timestamp >= since))
+        .map(|r| (r.timestamp, r))
+        .collect();
+    for record in records.drain(..) {
+        merged.insert(record.timestamp, record);
+    }
+    records.extend(merged.into_values().rev().take(limit));
+    records.len().saturating_sub(existing)
+}
+
+pub const CSV_HEADER: &str =
+    "timestamp,state,success,latency_ms,status_code,response_bytes,ttl,hops,\
+dns_ms,connect_ms,tls_ms,ttfb_ms,samples,uptime_ratio,maintenance,degraded,message\n";
+
+/// 探测记录的一行 CSV (与 `CSV_HEADER` 列顺序一致)，缺省值留空
+pub fn csv_row(record: &ProbeRecord) -> String {
+    let timing = record.timing.as_ref();
+    let rollup = record.rollup.as_ref();
+    let mut row = String::new();
+    let _ = write!(
+        row,
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        record.timestamp.to_rfc3339(),
+        record.state().label().to_lowercase(),
+        record.success,
+        opt(record.latency_ms),
+        opt(record.status_code),
+        opt(record.response_bytes),
+        opt(record.ttl),
+        opt(record.hops),
+        opt(timing.map(|t| t.dns_ms)),
+        opt(timing.map(|t| t.connect_ms)),
+        opt(timing.and_then(|t| t.tls_ms)),
+        opt(timing.map(|t| t.ttfb_ms)),
+        opt(rollup.map(|r| r.samples)),
+        opt(rollup.map(|r| r.uptime_ratio)),
+        record.maintenance,
+        escape(record.degraded.as_deref().unwrap_or("")),
+        escape(record.message.as_deref().unwrap_or("")),
+    );
+    row.push('\n');
+    row
+}
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// RFC 4180：含逗号、引号或换行的字段用双引号包裹，内部引号加倍
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
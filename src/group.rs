@@ -0,0 +1,92 @@
+use crate::model::{
+    AppConfig, GroupState, GroupStatus, MonitorStatus, RollupMode, Target, TargetState,
+};
+use dashmap::DashMap;
+
+/// 按配置中的出现顺序汇总所有分组，`visible` 用于按访问范围过滤成员
+pub fn rollups(
+    config: &AppConfig,
+    status_map: &DashMap<String, MonitorStatus>,
+    visible: impl Fn(&Target) -> bool,
+) -> Vec<GroupStatus> {
+    let mut names: Vec<&str> = Vec::new();
+    for target in &config.targets {
+        if let Some(group) = target.group.as_deref() {
+            if !names.contains(&group) {
+                names.push(group);
+            }
+        }
+    }
+    names
+        .into_iter()
+        .filter_map(|name| rollup(config, status_map, name, &visible))
+        .collect()
+}
+
+/// 汇总单个分组；没有可见成员时返回 None
+pub fn rollup(
+    config: &AppConfig,
+    status_map: &DashMap<String, MonitorStatus>,
+    name: &str,
+    visible: impl Fn(&Target) -> bool,
+) -> Option<GroupStatus> {
+    let mut member_ids = Vec::new();
+    let mut up = 0;
+    let mut degraded = 0;
+    for target in &config.targets {
+        // 停用的目标不参与汇总
+        if target.group.as_deref() != Some(name) || !target.enabled || !visible(target) {
+            continue;
+        }
+        // 以确认后的状态为准，单次抖动不影响分组
+        let Some(status) = status_map.get(&target.id) else {
+            continue;
+        };
+        match status.current_state {
+            TargetState::Up => up += 1,
+            TargetState::Degraded => degraded += 1,
+            TargetState::Down => {}
+        }
+        member_ids.push(target.id.clone());
+    }
+    if member_ids.is_empty() {
+        return None;
+    }
+
+    let members = member_ids.len();
+    let rule = config.groups.iter().find(|g| g.name == name);
+    let rollup = rule.map(|g| g.rollup).unwrap_or_default();
+    let down = members - up - degraded;
+    let state = match rollup {
+        _ if up == members => GroupState::Up,
+        RollupMode::All if down == members => GroupState::Down,
+        RollupMode::All => GroupState::Degraded,
+        RollupMode::WorstOf if down > 0 => GroupState::Down,
+        RollupMode::WorstOf => GroupState::Degraded,
+        RollupMode::Quorum => {
+            let quorum = rule
+                .and_then(|g| g.quorum)
+                .unwrap_or(members / 2 + 1)
+                .clamp(1, members);
+            // 降级的成员仍在提供服务，但不计入法定数量
+            if up >= quorum {
+                GroupState::Up
+            } else if down == members {
+                GroupState::Down
+            } else {
+                GroupState::Degraded
+            }
+        }
+    };
+
+    Some(GroupStatus {
+        group: name.to_string(),
+        state,
+        rollup,
+        members,
+        up,
+        degraded,
+        down,
+        member_ids,
+    })
+}
@@ -0,0 +1,366 @@
+// tonic 的 Status 较大，按其惯用法直接在 Result 中返回
+#![allow(clippy::result_large_err)]
+
+use crate::model::{self, GrpcConfig, LiveUpdate, MonitorStatus, Role};
+use crate::web::{self, Access, AppState, TargetError};
+use axum::http::StatusCode;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use tonic::{Request, Response, Status};
+
+pub mod pb {
+    tonic::include_proto!("netwatch.v1");
+}
+
+use pb::net_watch_server::{NetWatch, NetWatchServer};
+
+// 单独列在 proto 中的 Target 字段，其余字段放在 options_json
+const TYPED_FIELDS: [&str; 10] = [
+    "id",
+    "name",
+    "host",
+    "port",
+    "protocol",
+    "enabled",
+    "group",
+    "namespace",
+    "interval_secs",
+    "public",
+];
+
+/// 在独立端口上提供 gRPC 管理接口，Web 服务关闭时一并停止
+pub async fn serve(config: GrpcConfig, state: AppState) {
+    let addr = match config.listen.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!("Invalid gRPC listen address {}: {}", config.listen, e);
+            return;
+        }
+    };
+    let mut shutdown_rx = state.shutdown_tx.subscribe();
+    tracing::info!("gRPC server listening on {}", addr);
+    let result = tonic::transport::Server::builder()
+        .add_service(NetWatchServer::new(GrpcService { state }))
+        .serve_with_shutdown(addr, async move {
+            let _ = shutdown_rx.recv().await;
+        })
+        .await;
+    if let Err(e) = result {
+        tracing::error!("gRPC server failed: {}", e);
+    }
+}
+
+struct GrpcService {
+    state: AppState,
+}
+
+impl GrpcService {
+    // 与 HTTP API 相同的 API Key；未启用鉴权时视为管理员
+    fn access<T>(&self, request: &Request<T>) -> Result<(Access, Role), Status> {
+        let config = self.state.config_rx.borrow();
+        if !config.auth_enabled() {
+            return Ok((Access::Admin, Role::Admin));
+        }
+        let metadata = request.metadata();
+        let key = metadata
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| metadata.get("x-api-key").and_then(|v| v.to_str().ok()))
+            .ok_or_else(|| Status::unauthenticated("API key required"))?;
+        web::key_access(&config, key).ok_or_else(|| Status::unauthenticated("Invalid API key"))
+    }
+
+    fn editor<T>(&self, request: &Request<T>) -> Result<Access, Status> {
+        match self.access(request)? {
+            (_, Role::Viewer) => Err(Status::permission_denied("Read-only access")),
+            (access, Role::Admin) => Ok(access),
+        }
+    }
+
+    fn status(&self, access: &Access, id: &str) -> Result<MonitorStatus, Status> {
+        self.state
+            .status_map
+            .get(id)
+            .filter(|e| access.can_see(&e.value().target))
+            .map(|e| e.value().clone())
+            .ok_or_else(|| Status::not_found("Target not found"))
+    }
+}
+
+#[tonic::async_trait]
+impl NetWatch for GrpcService {
+    async fn list_targets(
+        &self,
+        request: Request<pb::ListTargetsRequest>,
+    ) -> Result<Response<pb::ListTargetsResponse>, Status> {
+        let (access, _) = self.access(&request)?;
+        let targets = self
+            .state
+            .config_rx
+            .borrow()
+            .targets
+            .iter()
+            .filter(|t| access.can_see(t))
+            .map(|t| to_proto(t, &access))
+            .collect();
+        Ok(Response::new(pb::ListTargetsResponse { targets }))
+    }
+
+    async fn get_target(
+        &self,
+        request: Request<pb::TargetId>,
+    ) -> Result<Response<pb::Target>, Status> {
+        let (access, _) = self.access(&request)?;
+        let id = &request.get_ref().id;
+        let config = self.state.config_rx.borrow();
+        let target = config
+            .targets
+            .iter()
+            .find(|t| t.id == *id && access.can_see(t))
+            .ok_or_else(|| Status::not_found("Target not found"))?;
+        Ok(Response::new(to_proto(target, &access)))
+    }
+
+    async fn create_target(
+        &self,
+        request: Request<pb::Target>,
+    ) -> Result<Response<pb::Target>, Status> {
+        let access = self.editor(&request)?;
+        let body = from_proto(request.into_inner())?;
+        let (target, _) = web::insert_target(&self.state, &access, body)
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(to_proto(&target, &access)))
+    }
+
+    async fn update_target(
+        &self,
+        request: Request<pb::Target>,
+    ) -> Result<Response<pb::Target>, Status> {
+        let access = self.editor(&request)?;
+        let target = request.into_inner();
+        if target.id.is_empty() {
+            return Err(Status::invalid_argument("Target id required"));
+        }
+        let id = target.id.clone();
+        let body = from_proto(target)?;
+        // gRPC 接口不支持 If-Match，总是覆盖
+        let no_precondition = Default::default();
+        let (target, _) = web::update_target(&self.state, &access, &id, &no_precondition, body)
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(to_proto(&target, &access)))
+    }
+
+    async fn delete_target(
+        &self,
+        request: Request<pb::TargetId>,
+    ) -> Result<Response<pb::DeleteTargetResponse>, Status> {
+        let access = self.editor(&request)?;
+        let no_precondition = Default::default();
+        let persisted = web::remove_target(
+            &self.state,
+            &access,
+            &request.get_ref().id,
+            &no_precondition,
+        )
+        .await
+        .map_err(to_status)?;
+        Ok(Response::new(pb::DeleteTargetResponse { persisted }))
+    }
+
+    async fn get_status(
+        &self,
+        request: Request<pb::TargetId>,
+    ) -> Result<Response<pb::TargetStatus>, Status> {
+        let (access, _) = self.access(&request)?;
+        let status = self.status(&access, &request.get_ref().id)?;
+        Ok(Response::new(status_to_proto(&status)))
+    }
+
+    async fn list_statuses(
+        &self,
+        request: Request<pb::ListStatusesRequest>,
+    ) -> Result<Response<pb::ListStatusesResponse>, Status> {
+        let (access, _) = self.access(&request)?;
+        // 与配置中的目标顺序一致
+        let statuses = self
+            .state
+            .config_rx
+            .borrow()
+            .targets
+            .iter()
+            .filter(|t| access.can_see(t))
+            .filter_map(|t| self.state.status_map.get(&t.id))
+            .map(|e| status_to_proto(e.value()))
+            .collect();
+        Ok(Response::new(pb::ListStatusesResponse { statuses }))
+    }
+
+    type WatchStatusStream = Pin<Box<dyn Stream<Item = Result<pb::StatusEvent, Status>> + Send>>;
+
+    async fn watch_status(
+        &self,
+        request: Request<pb::WatchStatusRequest>,
+    ) -> Result<Response<Self::WatchStatusStream>, Status> {
+        let (access, _) = self.access(&request)?;
+        let wanted = request.into_inner().target_ids;
+        let selected = move |id: &str| wanted.is_empty() || wanted.iter().any(|w| w == id);
+
+        let (rx, _) = self.state.live.subscribe();
+        let initial: Vec<_> = self
+            .state
+            .status_map
+            .iter()
+            .filter(|e| access.can_see(&e.value().target) && selected(e.key()))
+            .map(|e| Ok(snapshot_event(e.value())))
+            .collect();
+
+        let config_rx = self.state.config_rx.clone();
+        let updates = tokio_stream::wrappers::BroadcastStream::new(rx).filter_map(move |msg| {
+            let event = match msg {
+                Ok((_, update)) if selected(update.target_id()) => {
+                    let config = config_rx.borrow();
+                    let visible = match &update {
+                        LiveUpdate::Snapshot(status) => access.can_see(&status.target),
+                        LiveUpdate::Probe(u) => config
+                            .targets
+                            .iter()
+                            .any(|t| t.id == u.target_id && access.can_see(t)),
+                    };
+                    visible.then(|| match &update {
+                        LiveUpdate::Probe(u) => Ok(pb::StatusEvent {
+                            target_id: u.target_id.clone(),
+                            state: state_to_proto(u.state) as i32,
+                            previous_state: u.previous_state.map(|s| state_to_proto(s) as i32),
+                            unreachable: u.unreachable,
+                            uptime_24h: u.uptime_24h,
+                            record: Some(record_to_proto(&u.record)),
+                        }),
+                        LiveUpdate::Snapshot(status) => Ok(snapshot_event(status)),
+                    })
+                }
+                Ok(_) => None,
+                // 积压时丢弃了部分推送，由客户端重新订阅获取完整状态
+                Err(_) => Some(Err(Status::data_loss("stream lagged"))),
+            };
+            futures::future::ready(event)
+        });
+
+        let mut shutdown_rx = self.state.shutdown_tx.subscribe();
+        let stream = futures::stream::iter(initial)
+            .chain(updates)
+            .take_until(async move {
+                let _ = shutdown_rx.recv().await;
+            });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn to_status((code, message): TargetError) -> Status {
+    match code {
+        StatusCode::NOT_FOUND => Status::not_found(message),
+        StatusCode::CONFLICT => Status::already_exists(message),
+        StatusCode::UNPROCESSABLE_ENTITY => Status::invalid_argument(message),
+        StatusCode::FORBIDDEN => Status::permission_denied(message),
+        StatusCode::PRECONDITION_FAILED => Status::failed_precondition(message),
+        _ => Status::internal(message),
+    }
+}
+
+fn to_proto(target: &model::Target, access: &Access) -> pb::Target {
+    let mut target = target.clone();
+    if access.read_only() {
+        web::redact_target(&mut target);
+    }
+    let mut options = serde_json::to_value(&target).unwrap_or_default();
+    if let Some(obj) = options.as_object_mut() {
+        TYPED_FIELDS.iter().for_each(|f| {
+            obj.remove(*f);
+        });
+    }
+    pb::Target {
+        protocol: serde_json::to_value(&target.protocol)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default(),
+        port: target.port.map(u32::from),
+        enabled: Some(target.enabled),
+        interval_secs: target.interval_secs,
+        public: Some(target.public),
+        options_json: options.to_string(),
+        id: target.id,
+        name: target.name,
+        host: target.host,
+        group: target.group,
+        namespace: target.namespace,
+    }
+}
+
+// 转换为 HTTP API 使用的 Target JSON，校验与默认值由 serde 完成
+fn from_proto(target: pb::Target) -> Result<serde_json::Value, Status> {
+    let mut obj = if target.options_json.trim().is_empty() {
+        serde_json::Map::new()
+    } else {
+        serde_json::from_str(&target.options_json)
+            .map_err(|e| Status::invalid_argument(format!("Invalid options_json: {}", e)))?
+    };
+    obj.insert("id".to_string(), target.id.into());
+    obj.insert("name".to_string(), target.name.into());
+    obj.insert("host".to_string(), target.host.into());
+    obj.insert("port".to_string(), target.port.into());
+    if !target.protocol.is_empty() {
+        obj.insert("protocol".to_string(), target.protocol.into());
+    }
+    if let Some(enabled) = target.enabled {
+        obj.insert("enabled".to_string(), enabled.into());
+    }
+    obj.insert("group".to_string(), target.group.into());
+    obj.insert("namespace".to_string(), target.namespace.into());
+    obj.insert("interval_secs".to_string(), target.interval_secs.into());
+    if let Some(public) = target.public {
+        obj.insert("public".to_string(), public.into());
+    }
+    Ok(serde_json::Value::Object(obj))
+}
+
+fn state_to_proto(state: model::TargetState) -> pb::TargetState {
+    match state {
+        model::TargetState::Up => pb::TargetState::Up,
+        model::TargetState::Degraded => pb::TargetState::Degraded,
+        model::TargetState::Down => pb::TargetState::Down,
+    }
+}
+
+fn record_to_proto(record: &model::ProbeRecord) -> pb::ProbeRecord {
+    pb::ProbeRecord {
+        timestamp: record.timestamp.to_rfc3339(),
+        success: record.success,
+        latency_ms: record.latency_ms.map(f64::from),
+        message: record.message.clone(),
+        status_code: record.status_code.map(u32::from),
+    }
+}
+
+fn status_to_proto(status: &MonitorStatus) -> pb::TargetStatus {
+    pb::TargetStatus {
+        target_id: status.target.id.clone(),
+        name: status.target.name.clone(),
+        state: state_to_proto(status.current_state) as i32,
+        unreachable: status.unreachable,
+        uptime_24h: status.uptime_24h,
+        last_record: status.records.front().map(record_to_proto),
+    }
+}
+
+fn snapshot_event(status: &MonitorStatus) -> pb::StatusEvent {
+    pb::StatusEvent {
+        target_id: status.target.id.clone(),
+        state: state_to_proto(status.current_state) as i32,
+        previous_state: None,
+        unreachable: status.unreachable,
+        uptime_24h: status.uptime_24h,
+        record: status.records.front().map(record_to_proto),
+    }
+}
@@ -0,0 +1,102 @@
+use crate::model::InfluxConfig;
+use crate::report::ReportPoint;
+
+/// InfluxDB 写入地址，时间戳精度为毫秒
+pub fn write_url(config: &InfluxConfig) -> anyhow::Result<reqwest::Url> {
+    let base = config.url.trim_end_matches('/');
+    let url = match &config.bucket {
+        Some(bucket) => {
+            let mut url = reqwest::Url::parse(&format!("{}/api/v2/write", base))?;
+            url.query_pairs_mut()
+                .append_pair("org", config.org.as_deref().unwrap_or(""))
+                .append_pair("bucket", bucket)
+                .append_pair("precision", "ms");
+            url
+        }
+        None => {
+            let mut url = reqwest::Url::parse(&format!("{}/write", base))?;
+            url.query_pairs_mut()
+                .append_pair("db", config.database.as_deref().unwrap_or("netwatch"))
+                .append_pair("precision", "ms");
+            url
+        }
+    };
+    Ok(url)
+}
+
+/// 一个探测结果对应的 line protocol 行：目标名称/ID/协议/主机作为 tag，
+/// 状态、延迟等作为 field，`metrics` 以 `metric_` 前缀写入
+pub fn line(measurement: &str, point: &ReportPoint) -> String {
+    let record = &point.record;
+    let mut fields = vec![
+        format!("up={}i", record.success as u8),
+        format!("state=\"{}\"", record.state().label().to_lowercase()),
+    ];
+    if let Some(latency) = record.latency_ms {
+        fields.push(format!("latency_ms={}", latency));
+    }
+    if let Some(code) = record.status_code {
+        fields.push(format!("status_code={}i", code));
+    }
+    if let Some(bytes) = record.response_bytes {
+        fields.push(format!("response_bytes={}i", bytes));
+    }
+    if let Some(hops) = record.hops {
+        fields.push(format!("hops={}i", hops));
+    }
+    if let Some(timing) = &record.timing {
+        fields.push(format!("dns_ms={}", timing.dns_ms));
+        fields.push(format!("connect_ms={}", timing.connect_ms));
+        if let Some(tls) = timing.tls_ms {
+            fields.push(format!("tls_ms={}", tls));
+        }
+        fields.push(format!("ttfb_ms={}", timing.ttfb_ms));
+    }
+    for (key, value) in record.metrics.iter().filter(|(_, v)| v.is_finite()) {
+        fields.push(format!("metric_{}={}", escape_key(key), value));
+    }
+    if let Some(message) = &record.message {
+        fields.push(format!("message={}", quote(message)));
+    }
+
+    format!(
+        "{},target={},target_id={},protocol={},host={} {} {}",
+        escape_key(measurement),
+        escape_key(&point.target_name),
+        escape_key(&point.target_id),
+        escape_key(&format!("{:?}", point.protocol).to_uppercase()),
+        escape_key(&point.host),
+        fields.join(","),
+        record.timestamp.timestamp_millis()
+    )
+}
+
+// measurement、tag 与 field key 中的逗号、等号、空格需要转义
+fn escape_key(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push_str("\\ "),
+            _ => out.push(c),
+        }
+    }
+    if out.is_empty() {
+        out.push('-');
+    }
+    out
+}
+
+// 字符串 field 值用双引号包裹，内部的引号与反斜杠转义
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace(['\n', '\r'], " ")
+    )
+}
@@ -0,0 +1,210 @@
+use crate::model::{AppConfig, HttpMethod, Protocol, Target, WebhookConfig};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// 导入的目标与通道使用固定前缀 + Uptime Kuma 的 ID，重复导入时跳过已存在的条目
+const ID_PREFIX: &str = "kuma-";
+
+// 聊天类通道的消息模版，占位符与 alert.rs 的模版一致
+const CHAT_TEXT: &str = "{{STATUS}} {{TARGET}} ({{HOST}}) {{TIME}} {{MESSAGE}}";
+
+/// 导入结果：新增的目标与通知通道数量，以及未能转换或已存在而跳过的条目说明
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub targets: usize,
+    pub webhooks: usize,
+    pub skipped: Vec<String>,
+}
+
+/// 把 Uptime Kuma 的备份文件 (设置 → 备份 → 导出，含 `monitorList` 与 `notificationList`)
+/// 合并进配置。已存在相同 ID 的目标与通道保持不变
+pub fn import(config: &mut AppConfig, backup: &Value) -> anyhow::Result<ImportReport> {
+    let monitors = backup["monitorList"].as_array();
+    let notifications = backup["notificationList"].as_array();
+    if monitors.is_none() && notifications.is_none() {
+        anyhow::bail!("Not an Uptime Kuma backup: missing monitorList / notificationList");
+    }
+    let monitors = monitors.map(Vec::as_slice).unwrap_or_default();
+    let notifications = notifications.map(Vec::as_slice).unwrap_or_default();
+    let mut report = ImportReport::default();
+
+    // Kuma 的分组本身也是一个 type=group 的监控项，子项通过 parent 引用
+    let groups: HashMap<i64, String> = monitors
+        .iter()
+        .filter(|m| m["type"] == "group")
+        .filter_map(|m| Some((m["id"].as_i64()?, text(&m["name"])?)))
+        .collect();
+
+    for monitor in monitors.iter().filter(|m| m["type"] != "group") {
+        let name = text(&monitor["name"]).unwrap_or_else(|| "unnamed".to_string());
+        match convert_monitor(monitor, &groups) {
+            Ok(target) if config.targets.iter().any(|t| t.id == target.id) => {
+                report
+                    .skipped
+                    .push(format!("Monitor {}: already imported", name));
+            }
+            Ok(target) => {
+                config.targets.push(target);
+                report.targets += 1;
+            }
+            Err(reason) => report.skipped.push(format!("Monitor {}: {}", name, reason)),
+        }
+    }
+
+    for notification in notifications {
+        let name = text(&notification["name"]).unwrap_or_else(|| "unnamed".to_string());
+        match convert_notification(notification) {
+            Ok(webhook) if config.alert.webhooks.iter().any(|w| w.id == webhook.id) => {
+                report
+                    .skipped
+                    .push(format!("Notification {}: already imported", name));
+            }
+            Ok(webhook) => {
+                config.alert.webhooks.push(webhook);
+                report.webhooks += 1;
+            }
+            Err(reason) => report
+                .skipped
+                .push(format!("Notification {}: {}", name, reason)),
+        }
+    }
+    Ok(report)
+}
+
+fn convert_monitor(monitor: &Value, groups: &HashMap<i64, String>) -> Result<Target, String> {
+    let id = monitor["id"].as_i64().ok_or("missing id")?;
+    let kind = monitor["type"].as_str().unwrap_or("http");
+    if flag(&monitor["upsideDown"]) {
+        return Err("upside down mode is not supported".to_string());
+    }
+
+    let mut target = Target {
+        id: format!("{}{}", ID_PREFIX, id),
+        name: text(&monitor["name"]).unwrap_or_else(|| format!("Kuma {}", id)),
+        enabled: monitor.get("active").is_none_or(flag),
+        group: monitor["parent"]
+            .as_i64()
+            .and_then(|p| groups.get(&p).cloned()),
+        interval_secs: monitor["interval"].as_u64().filter(|s| *s > 0),
+        created_at: Some(chrono::Local::now()),
+        ..Default::default()
+    };
+    // Kuma 在 maxretries 次重试仍失败后才判定 DOWN，即连续 maxretries + 1 次失败
+    if let Some(retries) = monitor["maxretries"].as_u64().filter(|r| *r > 0) {
+        target.threshold_down = Some((retries + 1).min(u8::MAX as u64) as u8);
+    }
+
+    match kind {
+        "http" => {
+            let url = text(&monitor["url"]).ok_or("missing url")?;
+            target.protocol = if url.starts_with("https://") {
+                Protocol::Https
+            } else {
+                Protocol::Http
+            };
+            target.host = url;
+            target.http_method = match monitor["method"].as_str().unwrap_or("GET") {
+                m if m.eq_ignore_ascii_case("GET") => HttpMethod::Get,
+                m if m.eq_ignore_ascii_case("HEAD") => HttpMethod::Head,
+                m => return Err(format!("HTTP method {} is not supported", m)),
+            };
+            target.verify_tls = !flag(&monitor["ignoreTls"]);
+            // 请求头在 Kuma 中以 JSON 字符串保存
+            if let Some(headers) = text(&monitor["headers"]) {
+                target.headers = serde_json::from_str(&headers)
+                    .map_err(|e| format!("invalid headers: {}", e))?;
+            }
+        }
+        "port" => {
+            target.protocol = Protocol::Tcp;
+            target.host = text(&monitor["hostname"]).ok_or("missing hostname")?;
+            target.port = Some(port(&monitor["port"]).ok_or("missing port")?);
+        }
+        "ping" => {
+            target.protocol = Protocol::Icmp;
+            target.host = text(&monitor["hostname"]).ok_or("missing hostname")?;
+        }
+        // NetWatch 的 DNS 探测检查的是 DNS 服务器本身是否可用
+        "dns" => {
+            target.protocol = Protocol::Dns;
+            target.host = text(&monitor["dns_resolve_server"]).ok_or("missing DNS server")?;
+            target.port = port(&monitor["port"]).filter(|p| *p != 53);
+        }
+        other => return Err(format!("monitor type {} is not supported", other)),
+    }
+    Ok(target)
+}
+
+fn convert_notification(notification: &Value) -> Result<WebhookConfig, String> {
+    let id = notification["id"].as_i64().ok_or("missing id")?;
+    // 通知参数在 Kuma 中以 JSON 字符串保存
+    let settings: Value = match &notification["config"] {
+        Value::String(s) => {
+            serde_json::from_str(s).map_err(|e| format!("invalid config: {}", e))?
+        }
+        other => other.clone(),
+    };
+    let field = |key: &str| text(&settings[key]).ok_or(format!("missing {}", key));
+    let chat = |key: &str| json!({ key: CHAT_TEXT }).to_string();
+
+    let (url, template) = match settings["type"].as_str().unwrap_or_default() {
+        "webhook" => (field("webhookURL")?, None),
+        "slack" => (field("slackwebhookURL")?, Some(chat("text"))),
+        "discord" => (field("discordWebhookUrl")?, Some(chat("content"))),
+        "mattermost" => (field("mattermostWebhookUrl")?, Some(chat("text"))),
+        "rocket.chat" => (field("rocketwebhookURL")?, Some(chat("text"))),
+        "teams" => (field("webhookUrl")?, Some(chat("text"))),
+        "telegram" => (
+            format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                field("telegramBotToken")?
+            ),
+            Some(json!({ "chat_id": field("telegramChatID")?, "text": CHAT_TEXT }).to_string()),
+        ),
+        "gotify" => (
+            format!(
+                "{}/message?token={}",
+                field("gotifyserverurl")?.trim_end_matches('/'),
+                field("gotifyapplicationToken")?
+            ),
+            Some(json!({ "title": "{{STATUS}} {{TARGET}}", "message": CHAT_TEXT }).to_string()),
+        ),
+        "ntfy" => (
+            field("ntfyserverurl")?.trim_end_matches('/').to_string(),
+            Some(json!({ "topic": field("ntfytopic")?, "message": CHAT_TEXT }).to_string()),
+        ),
+        other => return Err(format!("notification type {} is not supported", other)),
+    };
+
+    Ok(WebhookConfig {
+        id: format!("{}{}", ID_PREFIX, id),
+        name: text(&notification["name"]).unwrap_or_else(|| format!("Kuma {}", id)),
+        url,
+        template,
+        enabled: notification.get("active").is_none_or(flag),
+        ..Default::default()
+    })
+}
+
+// Kuma 导出的布尔值可能是 true/false，也可能是 SQLite 的 0/1
+fn flag(value: &Value) -> bool {
+    value
+        .as_bool()
+        .unwrap_or_else(|| value.as_i64().unwrap_or(0) != 0)
+}
+
+fn text(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn port(value: &Value) -> Option<u16> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str()?.parse().ok())
+        .and_then(|p| u16::try_from(p).ok())
+}
@@ -0,0 +1,97 @@
+use crate::model::LiveUpdate;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+// 重连时可补发的最近事件数
+const REPLAY_CAPACITY: usize = 1000;
+
+/// 附带事件 ID 的广播接收端
+pub type LiveReceiver = broadcast::Receiver<(u64, LiveUpdate)>;
+
+/// 实时推送的分发中心：为每条推送分配递增 ID 并保留最近的事件，
+/// 断线重连的客户端凭 `Last-Event-ID` 补齐缺失的事件，无需重新接收完整状态
+pub struct LiveHub {
+    tx: broadcast::Sender<(u64, LiveUpdate)>,
+    // 进程启动时间 (毫秒)，写入事件 ID，重启后旧 ID 不会被误认
+    epoch: i64,
+    buffer: Mutex<Replay>,
+}
+
+struct Replay {
+    next_id: u64,
+    events: VecDeque<(u64, LiveUpdate)>,
+}
+
+impl LiveHub {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        LiveHub {
+            tx,
+            epoch: chrono::Local::now().timestamp_millis(),
+            buffer: Mutex::new(Replay {
+                next_id: 1,
+                events: VecDeque::with_capacity(REPLAY_CAPACITY),
+            }),
+        }
+    }
+
+    pub fn send(&self, update: LiveUpdate) {
+        let mut replay = self.buffer.lock().unwrap();
+        let id = replay.next_id;
+        replay.next_id += 1;
+        if replay.events.len() == REPLAY_CAPACITY {
+            replay.events.pop_front();
+        }
+        replay.events.push_back((id, update.clone()));
+        // 在锁内发送，保证订阅时取出的缓冲与之后收到的事件既不重复也不遗漏
+        let _ = self.tx.send((id, update));
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// 订阅实时推送，返回最后一条已发送事件的 ID (尚无事件时为 0)
+    pub fn subscribe(&self) -> (LiveReceiver, u64) {
+        let replay = self.buffer.lock().unwrap();
+        (self.tx.subscribe(), replay.next_id - 1)
+    }
+
+    /// 从 `last_event_id` 之后继续订阅。ID 属于本次运行且之后的事件仍全部在缓冲中时
+    /// 返回需要补发的事件，否则返回 None，由调用方重新下发完整状态
+    #[allow(clippy::type_complexity)]
+    pub fn resume(
+        &self,
+        last_event_id: &str,
+    ) -> Option<(
+        broadcast::Receiver<(u64, LiveUpdate)>,
+        Vec<(u64, LiveUpdate)>,
+    )> {
+        let (epoch, last) = last_event_id.split_once('-')?;
+        let (epoch, last): (i64, u64) = (epoch.parse().ok()?, last.parse().ok()?);
+        if epoch != self.epoch {
+            return None;
+        }
+        let replay = self.buffer.lock().unwrap();
+        if last >= replay.next_id {
+            return None;
+        }
+        let oldest = replay.events.front().map_or(replay.next_id, |(id, _)| *id);
+        if last + 1 < oldest {
+            return None;
+        }
+        let missed = replay
+            .events
+            .iter()
+            .filter(|(id, _)| *id > last)
+            .cloned()
+            .collect();
+        Some((self.tx.subscribe(), missed))
+    }
+
+    /// 写入 SSE `id` 字段的事件 ID
+    pub fn event_id(&self, id: u64) -> String {
+        format!("{}-{}", self.epoch, id)
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+// 新连接先回放的最近日志条数
+const BACKLOG: usize = 500;
+// 订阅端处理不及时时缓冲的日志条数，超出后该连接收到 lagged 提示
+const CHANNEL_CAPACITY: usize = 1000;
+
+static HUB: Lazy<LogHub> = Lazy::new(|| LogHub {
+    tx: broadcast::channel(CHANNEL_CAPACITY).0,
+    recent: Mutex::new(VecDeque::with_capacity(BACKLOG)),
+});
+
+struct LogHub {
+    tx: broadcast::Sender<Arc<LogLine>>,
+    recent: Mutex<VecDeque<Arc<LogLine>>>,
+}
+
+/// 一条 tracing 日志，供 `/api/logs/stream` 推送
+#[derive(Debug, Serialize)]
+pub struct LogLine {
+    pub timestamp: DateTime<Local>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip)]
+    pub severity: tracing::Level,
+}
+
+/// 把通过全局过滤 (RUST_LOG) 的日志同时送入内存，不影响标准输出
+pub struct CaptureLayer;
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let line = Arc::new(LogLine {
+            timestamp: Local::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+            severity: *metadata.level(),
+        });
+
+        let mut recent = HUB.recent.lock().unwrap();
+        if recent.len() == BACKLOG {
+            recent.pop_front();
+        }
+        recent.push_back(line.clone());
+        // 在锁内发送，订阅时取出的回放与之后收到的日志不重复
+        let _ = HUB.tx.send(line);
+    }
+}
+
+/// 订阅日志，同时返回最近的日志 (从旧到新)
+pub fn subscribe() -> (Vec<Arc<LogLine>>, broadcast::Receiver<Arc<LogLine>>) {
+    let recent = HUB.recent.lock().unwrap();
+    (recent.iter().cloned().collect(), HUB.tx.subscribe())
+}
+
+// `message` 字段作为正文，其余字段以 `key=value` 附在后面，与 fmt 输出一致
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
@@ -1,13 +1,43 @@
 mod alert;
+mod auth;
+mod backup;
+mod cache;
+mod chart;
 mod config;
+mod deadletter;
+mod dns;
+mod domain;
+mod downsample;
+mod events;
+mod export;
+mod group;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod influx;
+mod kuma;
+mod live;
+mod logs;
 mod model;
 mod monitor;
+mod probe_log;
+mod push;
+mod remote_write;
+mod report;
+mod retention;
+mod runtime;
+mod sla;
+mod stats;
+mod store;
+#[cfg(feature = "syn-probe")]
+mod syn;
+mod tls;
+mod validate;
 mod web;
 
 use dashmap::DashMap;
 use std::env;
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc, watch};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use web::AppState;
 
@@ -16,6 +46,11 @@ async fn main() {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
     let mut config_path = "config.json".to_string();
+    let mut import_kuma = None;
+    let mut probe_log_path = None;
+    let mut tls_cert = None;
+    let mut tls_key = None;
+    let mut hash_password = false;
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
@@ -41,11 +76,60 @@ async fn main() {
                     std::process::exit(1);
                 }
             }
+            "--probe-log" => {
+                if i + 1 < args.len() {
+                    probe_log_path = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Missing argument for --probe-log");
+                    std::process::exit(1);
+                }
+            }
+            "--tls-cert" => {
+                if i + 1 < args.len() {
+                    tls_cert = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Missing argument for --tls-cert");
+                    std::process::exit(1);
+                }
+            }
+            "--tls-key" => {
+                if i + 1 < args.len() {
+                    tls_key = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Missing argument for --tls-key");
+                    std::process::exit(1);
+                }
+            }
+            "import-kuma" => {
+                if i + 1 < args.len() {
+                    import_kuma = Some(args[i + 1].clone());
+                    i += 1;
+                } else {
+                    eprintln!("Missing argument for import-kuma");
+                    std::process::exit(1);
+                }
+            }
+            "hash-password" => hash_password = true,
             _ => {}
         }
         i += 1;
     }
 
+    // 从标准输入读取密码，输出写入配置 auth.users[].password_hash 的哈希后退出
+    if hash_password {
+        match hash_password_stdin() {
+            Ok(hash) => println!("{}", hash),
+            Err(e) => {
+                eprintln!("Failed to hash password: {:#}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Initialize config path
     config::init_config_path(config_path);
 
@@ -55,10 +139,20 @@ async fn main() {
             std::env::var("RUST_LOG").unwrap_or_else(|_| "netwatch=info,tower_http=debug".into()),
         ))
         .with(tracing_subscriber::fmt::layer())
+        .with(logs::CaptureLayer)
         .init();
 
+    // 导入 Uptime Kuma 备份后退出，不启动服务
+    if let Some(path) = import_kuma {
+        if let Err(e) = import_kuma_file(&path) {
+            eprintln!("Import failed: {:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // 1. 加载配置
-    let initial_config = match config::load_config() {
+    let mut initial_config = match config::load_config() {
         Ok(c) => c,
         Err(e) => {
             tracing::error!("Failed to load configuration: {}", e);
@@ -66,6 +160,55 @@ async fn main() {
         }
     };
 
+    // 本地没有缓存时 (例如新启动的临时容器)，尝试从对象存储恢复
+    let mut snapshot = None;
+    if let Some(backup_config) = initial_config.backup.clone() {
+        if backup_config.restore_on_startup && !cache::cache_exists() {
+            match backup::fetch_snapshot(&backup_config).await {
+                Ok(Some(s)) => {
+                    tracing::info!("Restoring from backup created at {}", s.created_at);
+                    if backup_config.restore_config {
+                        let mut restored = s.config.clone();
+                        restored.backup = Some(backup_config.clone());
+                        if let Err(e) = config::save_config(&restored) {
+                            tracing::error!("Failed to save restored config: {}", e);
+                        }
+                        initial_config = restored;
+                    }
+                    snapshot = Some(s);
+                }
+                Ok(None) => tracing::info!("No backup found in bucket {}", backup_config.bucket),
+                Err(e) => tracing::error!("Failed to fetch backup: {}", e),
+            }
+        }
+    }
+
+    // HTTPS 证书无效时直接退出，不启动探测
+    if let Err(e) = tls::init(tls_cert, tls_key) {
+        tracing::error!("Invalid TLS settings: {:#}", e);
+        return;
+    }
+    let tls_config = tls::config().cloned();
+    if let Some(tls) = &tls_config {
+        if let Err(e) = tls::load(tls) {
+            tracing::error!("Failed to load TLS certificate: {:#}", e);
+            return;
+        }
+    }
+
+    // 手动添加、未设置令牌的 PUSH 目标生成令牌并写回配置文件
+    push::init();
+    if push::ensure_tokens(&mut initial_config) {
+        if let Err(e) = config::save_config(&initial_config) {
+            tracing::error!("Failed to save generated push tokens: {}", e);
+        }
+    }
+
+    let base_path = config::base_path(&initial_config);
+
+    // 共享 DNS 解析器 (带缓存)
+    dns::init_resolver(initial_config.dns_cache_size);
+
     // 2. 初始化 State
     let status_map = Arc::new(DashMap::new());
     for target in &initial_config.targets {
@@ -73,13 +216,23 @@ async fn main() {
     }
 
     // 尝试加载缓存
-    load_cache(&status_map);
+    cache::load_cache(&status_map, &initial_config.alert);
+    if let Some(snapshot) = snapshot {
+        if !snapshot.events.is_empty() && !events::log_exists() {
+            if let Err(e) = events::replace(&snapshot.events) {
+                tracing::error!("Failed to restore state events: {}", e);
+            }
+        }
+        cache::restore_statuses(&status_map, snapshot.statuses);
+    }
 
     // 3. 创建通道
     let (monitor_tx, monitor_rx) = mpsc::channel(100);
     let (config_tx, config_rx) = watch::channel(initial_config);
-    let (broadcast_tx, _) = broadcast::channel(100);
+    let live = Arc::new(live::LiveHub::new(100));
     let (shutdown_tx, _) = broadcast::channel(1);
+    let shutdown_request = Arc::new(Notify::new());
+    let (report_tx, report_rx) = mpsc::channel(1000);
 
     // 4. 启动配置持久化任务 (Config Writer)
     let persistence_map = status_map.clone();
@@ -89,16 +242,66 @@ async fn main() {
         monitor::config_persistence_task(monitor_rx, persistence_map, persistence_config_tx).await;
     });
 
+    // 启动探测结果上报任务 (Report Writer)
+    let report_config_rx = config_rx.clone();
+    tokio::spawn(async move {
+        report::report_task(report_rx, report_config_rx).await;
+    });
+
+    // 启动对象存储备份任务
+    let backup_map = status_map.clone();
+    let backup_config_rx = config_rx.clone();
+    tokio::spawn(async move {
+        backup::backup_task(backup_map, backup_config_rx).await;
+    });
+
+    // 启动探测日志 (WAL) 写入任务
+    probe_log::init(probe_log_path);
+    tokio::spawn(probe_log::flush_task());
+
+    // 启动 Prometheus remote_write 推送任务
+    tokio::spawn(remote_write::remote_write_task(
+        status_map.clone(),
+        config_rx.clone(),
+    ));
+
+    // 启动旧记录降采样任务
+    tokio::spawn(downsample::compaction_task(
+        status_map.clone(),
+        config_rx.clone(),
+    ));
+
+    // 启动保留期清理任务
+    tokio::spawn(retention::pruning_task(
+        status_map.clone(),
+        config_rx.clone(),
+    ));
+
     // 5. 启动后台探测任务 (Monitor Loop)
+    let schedule = Arc::new(DashMap::new());
+    let monitor_schedule = schedule.clone();
     let monitor_map = status_map.clone();
     let monitor_config_rx = config_rx.clone();
-    let monitor_broadcast_tx = broadcast_tx.clone();
-    tokio::spawn(async move {
+    let monitor_live = live.clone();
+    // 探测跑在独立 runtime 上，其内部 spawn 的任务也都落在该 runtime
+    let probe_runtime = match runtime::build_probe_runtime() {
+        Ok(rt) => rt,
+        Err(e) => {
+            tracing::error!("Failed to start probe runtime: {}", e);
+            return;
+        }
+    };
+    runtime::register("web", tokio::runtime::Handle::current());
+    runtime::register("probe", probe_runtime.handle().clone());
+    tokio::spawn(runtime::sampler_task());
+    probe_runtime.spawn(async move {
         monitor::start_monitor_loop(
             monitor_map,
             monitor_tx,
             monitor_config_rx,
-            monitor_broadcast_tx,
+            monitor_live,
+            report_tx,
+            monitor_schedule,
         )
         .await;
     });
@@ -108,64 +311,51 @@ async fn main() {
         status_map: status_map.clone(),
         config_tx,
         config_rx,
-        broadcast_tx,
+        live,
         shutdown_tx: shutdown_tx.clone(),
+        shutdown_request: shutdown_request.clone(),
+        schedule,
+        base_path,
     };
 
-    let app = web::app(app_state);
-    let addr = "0.0.0.0:3000";
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    tracing::info!("Web Server listening on http://{}", addr);
-
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(status_map.clone(), shutdown_tx))
-        .await
-        .unwrap();
-}
+    // gRPC 管理接口 (可选)，与 Web 服务共用状态
+    let grpc_config = app_state.config_rx.borrow().grpc.clone();
+    if let Some(grpc) = grpc_config {
+        #[cfg(feature = "grpc")]
+        tokio::spawn(grpc::serve(grpc, app_state.clone()));
+        #[cfg(not(feature = "grpc"))]
+        tracing::warn!(
+            "gRPC is configured on {} but this build does not include the grpc feature",
+            grpc.listen
+        );
+    }
 
-const CACHE_FILE: &str = "cache.json";
+    let app = web::app(app_state);
+    let addr = env::var("NETWATCH_LISTEN").unwrap_or_else(|_| "0.0.0.0:3000".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
 
-fn save_cache(state: &DashMap<String, model::MonitorStatus>) {
-    tracing::info!("Saving monitor cache to {}", CACHE_FILE);
-    let items: Vec<model::MonitorStatus> = state.iter().map(|v| v.value().clone()).collect();
-    match serde_json::to_string(&items) {
-        Ok(json) => {
-            if let Err(e) = std::fs::write(CACHE_FILE, json) {
-                tracing::error!("Failed to write cache file: {}", e);
+    let shutdown = shutdown_signal(status_map.clone(), shutdown_tx, shutdown_request);
+    match tls_config {
+        Some(tls) => {
+            tracing::info!("Web Server listening on https://{}", addr);
+            if let Err(e) = tls::serve(listener, app, tls, shutdown).await {
+                tracing::error!("Failed to start HTTPS server: {:#}", e);
             }
         }
-        Err(e) => tracing::error!("Failed to serialize cache: {}", e),
-    }
-}
-
-fn load_cache(state: &DashMap<String, model::MonitorStatus>) {
-    if !std::path::Path::new(CACHE_FILE).exists() {
-        return;
-    }
-    tracing::info!("Loading monitor cache from {}", CACHE_FILE);
-    match std::fs::read_to_string(CACHE_FILE) {
-        Ok(content) => {
-            match serde_json::from_str::<Vec<model::MonitorStatus>>(&content) {
-                Ok(items) => {
-                    for item in items {
-                        // 我们只恢复 targets 列表中存在的 target 的状态
-                        if let Some(mut existing) = state.get_mut(&item.target.id) {
-                            existing.records = item.records;
-                            existing.current_state = item.current_state;
-                            tracing::info!("Restored cache for target: {}", item.target.name);
-                        }
-                    }
-                }
-                Err(e) => tracing::error!("Failed to parse cache file: {}", e),
-            }
+        None => {
+            tracing::info!("Web Server listening on http://{}", addr);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown)
+                .await
+                .unwrap();
         }
-        Err(e) => tracing::error!("Failed to read cache file: {}", e),
     }
 }
 
 async fn shutdown_signal(
     state: Arc<DashMap<String, model::MonitorStatus>>,
     shutdown_tx: broadcast::Sender<()>,
+    shutdown_request: Arc<Notify>,
 ) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -187,11 +377,42 @@ async fn shutdown_signal(
     tokio::select! {
         _ = ctrl_c => {},
         _ = terminate => {},
+        _ = shutdown_request.notified() => {},
     }
 
     tracing::info!("Shutdown signal received, saving cache...");
     // Send shutdown signal to all SSE connections
     let _ = shutdown_tx.send(());
-    save_cache(&state);
+    cache::save_cache(&state);
+    probe_log::flush();
     tracing::info!("Goodbye!");
 }
+
+fn import_kuma_file(path: &str) -> anyhow::Result<()> {
+    if config::is_env_mode() {
+        anyhow::bail!("Configuration is read-only in environment mode");
+    }
+    let content = std::fs::read_to_string(path)?;
+    let backup: serde_json::Value = serde_json::from_str(&content)?;
+    let mut config = config::load_config()?;
+    let report = kuma::import(&mut config, &backup)?;
+    config::save_config(&config)?;
+    for reason in &report.skipped {
+        println!("Skipped: {}", reason);
+    }
+    println!(
+        "Imported {} targets and {} webhooks",
+        report.targets, report.webhooks
+    );
+    Ok(())
+}
+
+fn hash_password_stdin() -> anyhow::Result<String> {
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        anyhow::bail!("Empty password");
+    }
+    auth::hash_password(password)
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Local};
-use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -11,6 +12,32 @@ pub enum Protocol {
     Dns,
     Http,
     Https,
+    Vnc,
+    Smb,
+    Nfs,
+    Exec,
+    Domain,
+    Dnsbl,
+    Watchdog,
+    Push,
+}
+
+/// TCP 探测方式：完整连接 或 仅发送 SYN (需启用 `syn-probe` feature)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TcpMode {
+    #[default]
+    Connect,
+    Syn,
+}
+
+/// HTTP 探测使用的请求方法；HEAD 不下载响应体，适合计量带宽的链路
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Head,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,13 +48,141 @@ pub struct Target {
     pub name: String,
     #[serde(default = "default_proto")]
     pub protocol: Protocol,
+    // 停用后不再探测，但保留配置与历史记录
+    #[serde(default = "default_true")]
+    pub enabled: bool,
 
-    #[serde(default = "default_threshold")]
-    pub threshold: u8,
+    // 状态切换所需的连续确认次数，为空时使用全局设置；
+    // threshold_down (UP→DOWN) / threshold_up (DOWN→UP) 可分别覆盖
+    #[serde(default)]
+    pub threshold: Option<u8>,
+    #[serde(default)]
+    pub threshold_up: Option<u8>,
+    #[serde(default)]
+    pub threshold_down: Option<u8>,
+    // 探测失败后等待该毫秒数立即重试一次，以重试结果计入状态机
+    #[serde(default)]
+    pub retry_delay_ms: Option<u64>,
+    // 依赖的父目标 ID (如上游路由器)，父目标 DOWN 时本目标的故障不告警
+    #[serde(default)]
+    pub parent: Option<String>,
+    // 添加时间 (由服务端写入)，与宽限期一起决定何时开始告警
+    #[serde(default)]
+    pub created_at: Option<DateTime<Local>>,
+    // 新目标的告警宽限期 (秒)，为空时使用全局 grace_period_secs
+    #[serde(default)]
+    pub grace_period_secs: Option<u64>,
+    // 探测成功但延迟超过该值 (毫秒) 时判定为 DEGRADED
+    #[serde(default)]
+    pub degraded_latency_ms: Option<u32>,
+    // ICMP 每次探测发送的包数，部分丢包判定为 DEGRADED，默认 1
+    #[serde(default)]
+    pub ping_count: Option<u8>,
+    // HTTP/HTTPS 响应体包含该字符串时判定为 DEGRADED (如 `"status":"yellow"`)
+    #[serde(default)]
+    pub degraded_body: Option<String>,
 
     // 状态持久化
     #[serde(default)]
-    pub last_known_state: Option<bool>,
+    pub last_known_state: Option<TargetState>,
+
+    // 每次探测结果都推送到该地址 (与告警通道分离)
+    #[serde(default)]
+    pub report_url: Option<String>,
+
+    // HTTP 探测: 覆盖全局 User-Agent / 追加请求头
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub cookies: BTreeMap<String, String>,
+    #[serde(default)]
+    pub http_method: HttpMethod,
+    #[serde(default)]
+    pub expect_headers: Vec<HeaderAssertion>,
+    // 记录响应体 SHA-256，与上次不同即视为内容变更 (页面篡改、误发布)
+    #[serde(default)]
+    pub watch_content: bool,
+    // HTTPS 证书校验，自签名证书的目标可关闭
+    #[serde(default = "default_true")]
+    pub verify_tls: bool,
+    #[serde(default)]
+    pub tcp_mode: TcpMode,
+    #[serde(default)]
+    pub keep_alive_probe: bool,
+    // HTTP 分阶段计时 (DNS / TCP / TLS / TTFB)
+    #[serde(default)]
+    pub phase_timing: bool,
+    // 解析 host 时使用的 DNS 服务器 (IP 或 IP:port)，为空则使用系统配置
+    #[serde(default)]
+    pub resolver: Vec<String>,
+    // 探测使用的本地 IP 或出口网卡 (覆盖全局 bind)
+    #[serde(default)]
+    pub bind: Option<String>,
+
+    // EXEC 探测: 程序及参数，可选地把 stdout 中的 JSON 映射为探测结果
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub result_mapping: Option<ResultMapping>,
+
+    // DOMAIN 探测: 距注册到期少于该天数即判定失败
+    #[serde(default = "default_expiry_warn_days")]
+    pub expiry_warn_days: u32,
+    // DNSBL 探测: 查询的黑名单区域，为空时使用内置列表
+    #[serde(default)]
+    pub dnsbl_zones: Vec<String>,
+
+    // 所属租户命名空间，为空表示全局 (仅管理员可见)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    // 所属分组，同组目标在服务端汇总为一个分组状态
+    #[serde(default)]
+    pub group: Option<String>,
+    // 在公开状态页中显示
+    #[serde(default)]
+    pub public: bool,
+    // 关键目标：告警不受通道静默时段限制
+    #[serde(default)]
+    pub critical: bool,
+    // 探测间隔 (秒)，为空时使用全局 probe_interval_secs
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    // 探测时间窗 (cron 表达式，本地时间，分钟粒度)，为空表示全天探测
+    #[serde(default)]
+    pub active_cron: Option<String>,
+    // PUSH 目标: 心跳地址 `/api/push/{token}` 中的令牌，为空时自动生成
+    #[serde(default)]
+    pub push_token: Option<String>,
+    // PUSH 目标: 超过该时长 (秒) 未收到心跳判定为 DOWN，为空时使用探测间隔
+    #[serde(default)]
+    pub push_period_secs: Option<u64>,
+}
+
+/// HTTP 响应头断言：默认要求存在；`value` 为不区分大小写的子串匹配；`absent` 要求不存在
+#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+pub struct HeaderAssertion {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub absent: bool,
+}
+
+/// 将 EXEC 探测 stdout 输出的 JSON 映射为结构化结果，各字段均为 JSON Pointer (如 `/data/ok`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Hash)]
+pub struct ResultMapping {
+    // 布尔值，"up"/"ok"/"true" 字符串，或 0 (Nagios 风格) 视为成功；未设置时以退出码判断
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub latency_ms: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    // 指标名 -> JSON Pointer，取到的数值记录在 ProbeRecord.metrics 中
+    #[serde(default)]
+    pub metrics: BTreeMap<String, String>,
 }
 
 fn default_proto() -> Protocol {
@@ -36,6 +191,61 @@ fn default_proto() -> Protocol {
 fn default_threshold() -> u8 {
     3
 }
+fn default_true() -> bool {
+    true
+}
+fn default_expiry_warn_days() -> u32 {
+    30
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            host: String::new(),
+            port: None,
+            name: String::new(),
+            protocol: default_proto(),
+            enabled: true,
+            threshold: None,
+            threshold_up: None,
+            threshold_down: None,
+            retry_delay_ms: None,
+            parent: None,
+            created_at: None,
+            grace_period_secs: None,
+            degraded_latency_ms: None,
+            ping_count: None,
+            degraded_body: None,
+            last_known_state: None,
+            report_url: None,
+            user_agent: None,
+            headers: BTreeMap::new(),
+            cookies: BTreeMap::new(),
+            http_method: HttpMethod::Get,
+            expect_headers: Vec::new(),
+            watch_content: false,
+            verify_tls: true,
+            tcp_mode: TcpMode::Connect,
+            keep_alive_probe: false,
+            phase_timing: false,
+            resolver: Vec::new(),
+            bind: None,
+            command: Vec::new(),
+            result_mapping: None,
+            expiry_warn_days: default_expiry_warn_days(),
+            dnsbl_zones: Vec::new(),
+            namespace: None,
+            group: None,
+            public: false,
+            critical: false,
+            interval_secs: None,
+            active_cron: None,
+            push_token: None,
+            push_period_secs: None,
+        }
+    }
+}
 
 impl std::hash::Hash for Target {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -45,8 +255,43 @@ impl std::hash::Hash for Target {
             port,
             name,
             protocol,
+            enabled,
             threshold,
+            threshold_up,
+            threshold_down,
+            retry_delay_ms,
+            parent,
+            created_at: _, // 创建后不变，不参与比较
+            grace_period_secs,
+            degraded_latency_ms,
+            ping_count,
+            degraded_body,
             last_known_state: _, // Explicitly ignored for hash calculation
+            report_url,
+            user_agent,
+            headers,
+            cookies,
+            http_method,
+            expect_headers,
+            watch_content,
+            verify_tls,
+            tcp_mode,
+            keep_alive_probe,
+            phase_timing,
+            resolver,
+            bind,
+            command,
+            result_mapping,
+            expiry_warn_days,
+            dnsbl_zones,
+            namespace,
+            group,
+            public,
+            critical,
+            interval_secs,
+            active_cron,
+            push_token,
+            push_period_secs,
         } = self;
 
         id.hash(state);
@@ -54,7 +299,41 @@ impl std::hash::Hash for Target {
         port.hash(state);
         name.hash(state);
         protocol.hash(state);
+        enabled.hash(state);
         threshold.hash(state);
+        threshold_up.hash(state);
+        threshold_down.hash(state);
+        retry_delay_ms.hash(state);
+        parent.hash(state);
+        grace_period_secs.hash(state);
+        degraded_latency_ms.hash(state);
+        ping_count.hash(state);
+        degraded_body.hash(state);
+        report_url.hash(state);
+        user_agent.hash(state);
+        headers.hash(state);
+        cookies.hash(state);
+        http_method.hash(state);
+        expect_headers.hash(state);
+        watch_content.hash(state);
+        verify_tls.hash(state);
+        tcp_mode.hash(state);
+        keep_alive_probe.hash(state);
+        phase_timing.hash(state);
+        resolver.hash(state);
+        bind.hash(state);
+        command.hash(state);
+        result_mapping.hash(state);
+        expiry_warn_days.hash(state);
+        dnsbl_zones.hash(state);
+        namespace.hash(state);
+        group.hash(state);
+        public.hash(state);
+        critical.hash(state);
+        interval_secs.hash(state);
+        active_cron.hash(state);
+        push_token.hash(state);
+        push_period_secs.hash(state);
     }
 }
 
@@ -63,6 +342,15 @@ pub struct AlertConfig {
     pub enabled: bool,
     #[serde(default)]
     pub webhooks: Vec<WebhookConfig>,
+    // 仪表盘的外部访问地址，用于告警消息中链接回目标 (如 ntfy 的按钮)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dashboard_url: Option<String>,
+    // 告警冷却：同一目标同一状态的告警在此时间内只发送一次，为空或 0 时不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+    // 每个通道最多尝试投递的次数 (含首次，失败后指数退避)，默认 5；1 表示不重试
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_attempts: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,10 +358,279 @@ pub struct WebhookConfig {
     #[serde(default = "generate_uuid")]
     pub id: String,
     pub name: String,
+    // 通道类型，默认为通用 Webhook (POST JSON 到 url)
+    #[serde(default)]
+    pub kind: ChannelKind,
+    #[serde(default)]
     pub url: String,
     #[serde(default)]
     pub template: Option<String>, // Optional override
     pub enabled: bool,
+    // 心跳通道：不接收普通告警，只接收 WATCHDOG 目标的周期性投递
+    #[serde(default)]
+    pub heartbeat: bool,
+    // 机器人的签名密钥 (钉钉 `SEC...`、飞书)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    // kind = pagerduty 时服务集成的 Integration Key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_key: Option<String>,
+    // kind = ntfy 时的服务器与主题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ntfy: Option<NtfyConfig>,
+    // kind = mqtt 时的 broker 与主题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mqtt: Option<MqttConfig>,
+    // kind = exec 时执行的程序及参数
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    // 静默时段：期间非关键目标的告警不发送，可在结束时汇总发送
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    // kind = email 时的 SMTP 设置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp: Option<SmtpConfig>,
+    // kind = telegram 时的机器人与会话
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<TelegramConfig>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            id: generate_uuid(),
+            name: String::new(),
+            kind: ChannelKind::default(),
+            url: String::new(),
+            template: None,
+            enabled: true,
+            heartbeat: false,
+            secret: None,
+            routing_key: None,
+            ntfy: None,
+            mqtt: None,
+            command: Vec::new(),
+            quiet_hours: None,
+            smtp: None,
+            telegram: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    #[default]
+    Webhook,
+    // 邮件，`template` 为纯文本正文模版
+    Email,
+    // Telegram 机器人，默认发送 MarkdownV2 格式的消息
+    Telegram,
+    // Slack Incoming Webhook (url)，默认发送 Block Kit 消息
+    Slack,
+    // Discord Webhook (url)，默认发送 embed 消息
+    Discord,
+    // 钉钉自定义机器人 (url)，默认发送 markdown 消息；设置 `secret` 时加签
+    DingTalk,
+    // 企业微信群机器人 (url)，默认发送 markdown 消息
+    WeCom,
+    // 飞书 / Lark 自定义机器人 (url)，默认发送消息卡片；设置 `secret` 时签名
+    Feishu,
+    // PagerDuty Events API v2 (routing_key)，DOWN 时触发事件，恢复时自动解决
+    PagerDuty,
+    // ntfy 推送 (ntfy)，带链接回仪表盘的按钮
+    Ntfy,
+    // 发布到 MQTT broker (mqtt)，保留消息供智能家居等系统订阅
+    Mqtt,
+    // 执行本地命令 (command)，状态通过环境变量传入，需要 NETWATCH_ALLOW_EXEC=1
+    Exec,
+}
+
+/// 邮件通道的 SMTP 服务器与收发件人
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    // 默认按 tls 取 587 (starttls)、465 (tls) 或 25 (none)
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    // 主题模版，占位符与 `template` 相同；默认 `[NetWatch] {{STATUS}} {{TARGET}}`
+    #[serde(default)]
+    pub subject: Option<String>,
+}
+
+/// ntfy 服务器与主题，自建服务器需要认证时填写访问令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NtfyConfig {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    // 1 (min) ~ 5 (max)，用于 DOWN / DEGRADED；恢复通知使用默认优先级 3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    // 访问令牌 (`tk_...`)，以 Bearer 方式认证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// 通道的静默时段 (cron 表达式，本地时间，分钟粒度，任一匹配即处于静默)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    // 如 `* 23,0-6 * * *` (23:00–07:00)、`* * * * SAT,SUN` (周末)
+    pub windows: Vec<String>,
+    // 静默结束时把期间被拦下的告警汇总为一条发送，否则直接丢弃
+    #[serde(default)]
+    pub summary: bool,
+}
+
+/// MQTT broker 与发布的主题，状态变化以 JSON 发布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+    // `mqtt://host:1883` 或 `mqtts://host:8883`
+    pub broker: String,
+    // 可包含 `{{TARGET_ID}}` 等占位符，如 `netwatch/{{TARGET_ID}}/state`
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    // 默认 `netwatch-<随机>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default = "default_mqtt_qos")]
+    pub qos: u8,
+    // 保留消息：新订阅者 (如重启后的 Home Assistant) 立即收到最新状态
+    #[serde(default = "default_true")]
+    pub retain: bool,
+}
+
+fn default_mqtt_qos() -> u8 {
+    1
+}
+
+/// Telegram 机器人令牌与接收消息的会话 (用户、群组或频道)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    // 数字 ID 或 `@channelname`，配置中可写为数字
+    #[serde(deserialize_with = "string_or_number")]
+    pub chat_id: String,
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        String(String),
+        Number(i64),
+    }
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::String(s) => s,
+        Repr::Number(n) => n.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    // 明文连接后升级 (STARTTLS)
+    #[default]
+    Starttls,
+    // 隐式 TLS (SMTPS)
+    Tls,
+    // 不加密，仅用于内网中继
+    None,
+}
+
+/// HTTP 探测的全局请求标识，可被 Target 上的同名字段覆盖
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// S3 兼容对象存储备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    pub endpoint: String, // 例如 https://s3.amazonaws.com 或 http://minio:9000
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    #[serde(default = "default_backup_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_backup_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_true")]
+    pub path_style: bool,
+    // 本地没有缓存文件时，启动时从备份恢复历史记录
+    #[serde(default = "default_true")]
+    pub restore_on_startup: bool,
+    // 恢复时同时用备份中的配置覆盖本地配置
+    #[serde(default)]
+    pub restore_config: bool,
+}
+
+/// InfluxDB 写入配置：设置 `bucket` 时使用 v2 API (`/api/v2/write`)，
+/// 否则使用 v1 兼容的 `/write?db=` (同样适用于 VictoriaMetrics 等接受 line protocol 的服务)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+    pub url: String, // 例如 http://influxdb:8086
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub org: Option<String>,
+    #[serde(default)]
+    pub bucket: Option<String>,
+    #[serde(default)]
+    pub database: Option<String>,
+    #[serde(default = "default_influx_measurement")]
+    pub measurement: String,
+}
+
+/// Prometheus remote_write (Mimir、VictoriaMetrics 等)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteWriteConfig {
+    pub url: String,
+    #[serde(default = "default_remote_write_interval")]
+    pub interval_secs: u64,
+    // 附加到所有序列上的标签，例如 {"instance": "home"}
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+}
+
+fn default_remote_write_interval() -> u64 {
+    30
+}
+
+fn default_influx_measurement() -> String {
+    "netwatch".to_string()
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+fn default_backup_prefix() -> String {
+    "netwatch/".to_string()
+}
+fn default_backup_interval() -> u64 {
+    3600
 }
 
 fn generate_uuid() -> String {
@@ -86,34 +643,602 @@ pub struct AppConfig {
     pub alert: AlertConfig,
     #[serde(default = "default_retention_days")]
     pub data_retention_days: u64,
+    // 每个目标最多保留的记录条数，设置后代替 data_retention_days 按条数保留
+    #[serde(default)]
+    pub data_retention_records: Option<usize>,
+    // 实时事件 (`init` / `status`) 中每个目标最多携带的最近记录条数，更早的记录通过记录接口按需查询；0 表示不限制
+    #[serde(default = "default_live_init_records")]
+    pub live_init_records: usize,
+    #[serde(default)]
+    pub http: HttpConfig,
+    // 共享 DNS 解析器的缓存条目数 (重启后生效)
+    #[serde(default = "default_dns_cache_size")]
+    pub dns_cache_size: usize,
+    #[serde(default)]
+    pub backup: Option<BackupConfig>,
+    // 每次探测结果写入 InfluxDB
+    #[serde(default)]
+    pub influx: Option<InfluxConfig>,
+    // 定期推送 up/latency 序列到 Prometheus remote_write 端点
+    #[serde(default)]
+    pub remote_write: Option<RemoteWriteConfig>,
+    // 全局探测出口：本地 IP 或网卡名
+    #[serde(default)]
+    pub bind: Option<String>,
+    // 多租户：各命名空间的 API Key 与独立告警通道
+    #[serde(default)]
+    pub namespaces: Vec<Namespace>,
+    // 可访问全部命名空间与全局设置的 API Key
+    #[serde(default)]
+    pub admin_api_keys: Vec<String>,
+    // 只读 API Key：可查看全部目标的状态与历史 (不含凭据)，不能修改
+    #[serde(default)]
+    pub viewer_api_keys: Vec<String>,
+    // 用户名/密码登录 (Cookie 会话)，为空时不启用
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    // 公开状态页 (`/status`)，为空时不启用
+    #[serde(default)]
+    pub status_page: Option<StatusPageConfig>,
+    // 经反向代理挂在子路径下时的 URL 前缀 (如 `/netwatch`)，启动时读取，修改后需重启
+    #[serde(default)]
+    pub base_path: Option<String>,
+    // gRPC 管理接口 (需以 `grpc` feature 编译)，启动时读取，修改后需重启
+    #[serde(default)]
+    pub grpc: Option<GrpcConfig>,
+    // 配置文件无法写入 (只读文件系统、磁盘满) 时的处理方式
+    #[serde(default)]
+    pub persistence_failure: PersistenceFailurePolicy,
+    // 分组汇总规则，未列出的分组按 all 汇总
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+    // 默认探测间隔 (秒)，可被 Target 的 interval_secs 覆盖
+    #[serde(default = "default_probe_interval_secs")]
+    pub probe_interval_secs: u64,
+    // 将各目标的探测错开分布在探测间隔内，而不是同时发起
+    #[serde(default = "default_true")]
+    pub stagger_probes: bool,
+    // 全局状态切换确认次数，可被 Target 覆盖
+    #[serde(default = "default_threshold")]
+    pub threshold: u8,
+    #[serde(default)]
+    pub threshold_up: Option<u8>,
+    #[serde(default)]
+    pub threshold_down: Option<u8>,
+    // 全局维护模式
+    #[serde(default)]
+    pub maintenance: Maintenance,
+    // 新目标添加后不告警的宽限期 (秒)，0 表示不启用
+    #[serde(default)]
+    pub grace_period_secs: u64,
+    // 旧探测记录的降采样 (压缩) 规则
+    #[serde(default)]
+    pub downsample: Downsample,
+}
+
+/// `memory_only`: 切换为仅内存模式并发送系统告警，定期重试写入；`log`: 仅记录错误日志
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PersistenceFailurePolicy {
+    #[default]
+    MemoryOnly,
+    Log,
+}
+
+impl AppConfig {
+    /// 未归属命名空间的目标使用全局告警；命名空间的目标只使用该命名空间的告警，
+    /// 未配置时不发送，避免租户的事件发到运维的全局通道
+    pub fn alert_for(&self, target: &Target) -> &AlertConfig {
+        static NO_ALERT: AlertConfig = AlertConfig {
+            enabled: false,
+            webhooks: Vec::new(),
+            dashboard_url: None,
+            cooldown_secs: None,
+            retry_attempts: None,
+        };
+        let Some(ns) = &target.namespace else {
+            return &self.alert;
+        };
+        self.namespaces
+            .iter()
+            .find(|n| &n.name == ns)
+            .and_then(|ns| ns.alert.as_ref())
+            .unwrap_or(&NO_ALERT)
+    }
+
+    /// 按天数保留时最早保留的时间点；按条数保留时不按时间删除，返回 None。
+    /// 保留天数大到无法表示 (例如导入的配置) 时同样不按时间删除
+    pub fn retention_cutoff(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.data_retention_records {
+            Some(n) if n > 0 => None,
+            _ => {
+                let days = i64::try_from(self.data_retention_days).ok()?;
+                now.checked_sub_signed(chrono::Duration::try_days(days)?)
+            }
+        }
+    }
+
+    /// 每个目标内存中最多保留的记录数：按条数保留时为配置值，
+    /// 否则为保留天数内按该目标的探测间隔可产生的记录数
+    pub fn record_limit(&self, target: &Target) -> usize {
+        if let Some(n) = self.data_retention_records.filter(|n| *n > 0) {
+            return n;
+        }
+        let limit = self.data_retention_days.saturating_mul(24 * 3600)
+            / self.interval_for(target).as_secs();
+        if limit == 0 {
+            60 // 至少保留一点
+        } else {
+            usize::try_from(limit).unwrap_or(usize::MAX)
+        }
+    }
+
+    /// 目标的探测间隔，至少 1 秒
+    pub fn interval_for(&self, target: &Target) -> std::time::Duration {
+        let secs = target.interval_secs.unwrap_or(self.probe_interval_secs);
+        std::time::Duration::from_secs(secs.max(1))
+    }
+
+    /// 切换到 `to_up` 状态所需的连续确认次数：
+    /// Target 的方向设置 > Target 的 threshold > 全局方向设置 > 全局 threshold
+    pub fn confirmations(&self, target: &Target, to_up: bool) -> usize {
+        let (target_dir, global_dir) = if to_up {
+            (target.threshold_up, self.threshold_up)
+        } else {
+            (target.threshold_down, self.threshold_down)
+        };
+        let count = target_dir
+            .or(target.threshold)
+            .or(global_dir)
+            .unwrap_or(self.threshold);
+        count.max(1) as usize
+    }
+
+    /// 新目标告警宽限期的结束时间，未启用宽限期、创建时间未知或结束时间超出可表示范围时为 None
+    pub fn grace_until(&self, target: &Target) -> Option<DateTime<Local>> {
+        let secs = target.grace_period_secs.unwrap_or(self.grace_period_secs);
+        if secs == 0 {
+            return None;
+        }
+        // created_at 可由客户端提交，时长与相加结果都可能溢出
+        let grace = chrono::Duration::try_seconds(i64::try_from(secs).ok()?)?;
+        target.created_at?.checked_add_signed(grace)
+    }
+
+    /// 是否配置了任何 API Key 或登录用户；都未配置时 Web/API 保持无鉴权的单租户行为
+    pub fn auth_enabled(&self) -> bool {
+        self.api_keys_enabled() || self.login_enabled()
+    }
+
+    pub fn api_keys_enabled(&self) -> bool {
+        !self.admin_api_keys.is_empty()
+            || !self.viewer_api_keys.is_empty()
+            || self.namespaces.iter().any(|n| !n.api_keys.is_empty())
+    }
+
+    pub fn login_enabled(&self) -> bool {
+        self.auth.as_ref().is_some_and(|a| !a.users.is_empty())
+    }
+}
+
+/// 全局维护模式：期间不发送目标告警，探测记录带 `maintenance` 标记；`until` 到期后自动失效
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Maintenance {
+    pub enabled: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Local>>,
+}
+
+impl Maintenance {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.enabled && self.until.is_none_or(|until| now < until)
+    }
+}
+
+/// 降采样：早于 `raw_hours` 的记录按分钟合并，早于 `minute_hours` 的按小时合并。
+/// `raw_hours` 为 0 时不压缩。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Downsample {
+    #[serde(default)]
+    pub raw_hours: u64,
+    #[serde(default = "default_minute_hours")]
+    pub minute_hours: u64,
+}
+
+impl Default for Downsample {
+    fn default() -> Self {
+        Self {
+            raw_hours: 0,
+            minute_hours: default_minute_hours(),
+        }
+    }
+}
+
+fn default_minute_hours() -> u64 {
+    48
+}
+
+/// 租户命名空间：持有各自 API Key 的用户只能看到和修改本命名空间的目标与告警通道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Namespace {
+    pub name: String,
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    // 为空时该命名空间的目标不发送告警 (不会回落到全局告警通道)
+    #[serde(default)]
+    pub alert: Option<AlertConfig>,
+}
+
+/// 登录配置：密码以 Argon2id 的 PHC 字符串保存 (`netwatch hash-password` 生成)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub users: Vec<UserAccount>,
+    #[serde(default = "default_session_hours")]
+    pub session_hours: u64,
+    // 未登录时也禁止查看仪表盘与只读 API
+    #[serde(default)]
+    pub protect_dashboard: bool,
+}
+
+/// 公开状态页：无需登录，只显示 `public` 目标的状态与可用率，不含地址与错误信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPageConfig {
+    #[serde(default = "default_status_page_title")]
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    // 条形图显示的天数
+    #[serde(default = "default_status_page_days")]
+    pub days: u32,
+}
+
+fn default_status_page_title() -> String {
+    "Service Status".to_string()
+}
+
+fn default_status_page_days() -> u32 {
+    90
+}
+
+/// gRPC 管理接口的监听地址，与 Web 服务分开监听 (明文 HTTP/2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    pub listen: String,
+}
+
+fn default_session_hours() -> u64 {
+    24 * 7
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub username: String,
+    pub password_hash: String,
+    // 设置后该用户只能访问此命名空间，否则可访问全部
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// 用户角色：`admin` 可修改配置、告警通道与维护模式，`viewer` 只能查看状态与历史
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    Admin,
+    Viewer,
+}
+
+/// 分组汇总规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupConfig {
+    pub name: String,
+    #[serde(default)]
+    pub rollup: RollupMode,
+    // quorum 模式下判定为 UP 所需的最少在线成员数，默认过半
+    #[serde(default)]
+    pub quorum: Option<usize>,
+}
+
+/// `all`: 全部在线为 UP，全部离线为 DOWN，其余为 DEGRADED；`worst_of`: 任一成员 DOWN 即 DOWN；
+/// `quorum`: 在线成员达到法定数为 UP，全部离线为 DOWN，其余为 DEGRADED
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupMode {
+    #[default]
+    All,
+    WorstOf,
+    Quorum,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupState {
+    Up,
+    Degraded,
+    Down,
+}
+
+/// 分组汇总状态，随 SSE `groups` / `group` 事件与 `/api/groups` 下发
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupStatus {
+    pub group: String,
+    pub state: GroupState,
+    pub rollup: RollupMode,
+    pub members: usize,
+    pub up: usize,
+    pub degraded: usize,
+    pub down: usize,
+    pub member_ids: Vec<String>,
 }
 
 fn default_retention_days() -> u64 {
     3
 }
 
+fn default_probe_interval_secs() -> u64 {
+    10
+}
+
+fn default_live_init_records() -> usize {
+    360
+}
+
+fn default_dns_cache_size() -> usize {
+    256
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProbeRecord {
     pub timestamp: DateTime<Local>,
     pub latency_ms: Option<f32>,
     pub success: bool,
     pub message: Option<String>, // 错误信息或 DNS 解析结果
+    // HTTP keep-alive 对比探测时，复用连接的请求耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reuse_latency_ms: Option<f32>,
+    // EXEC 探测通过 result_mapping 提取的自定义指标
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, f64>,
+    // watch_content 开启时的响应体 SHA-256 (hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    // phase_timing 开启时各阶段耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<HttpTiming>,
+    // HTTP 状态码与响应体大小 (字节；未读取响应体时取 Content-Length)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_bytes: Option<u64>,
+    // ICMP 回复的 TTL (IPv6 为 hop limit) 及据此推算的跳数，跳数突变通常意味着路由变化
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hops: Option<u8>,
+    // 开启 retry_delay_ms 且首次探测失败时，首次失败的原因 (本条记录为重试结果)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_attempt_error: Option<String>,
+    // 采集于全局维护期间
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub maintenance: bool,
+    // 探测成功但判定为 DEGRADED 的原因 (延迟过高、部分丢包、响应体匹配)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<String>,
+    // 降采样后的聚合记录：timestamp 为时间桶起点，latency_ms 为成功探测的平均延迟
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RecordRollup>,
+}
+
+/// 一个时间桶内原始探测的汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordRollup {
+    pub span_secs: u32,
+    // 参与统计的探测次数 (不含维护期间的记录，除非整桶都在维护期间)
+    pub samples: u32,
+    pub ok: u32,
+    pub uptime_ratio: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_latency_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_latency_ms: Option<f32>,
+}
+
+impl ProbeRecord {
+    /// 单次探测观察到的状态
+    pub fn state(&self) -> TargetState {
+        match (self.success, &self.degraded) {
+            (false, _) => TargetState::Down,
+            (true, Some(_)) => TargetState::Degraded,
+            (true, None) => TargetState::Up,
+        }
+    }
+}
+
+/// HTTP 探测各阶段耗时 (毫秒)，用于区分网络、TLS 还是应用本身变慢
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpTiming {
+    pub dns_ms: f32,
+    pub connect_ms: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ms: Option<f32>,
+    pub ttfb_ms: f32,
+}
+
+/// 目标的确认状态，按从差到好的顺序排列 (可直接比较好坏)。
+/// 旧版本的缓存与配置中以 bool 保存，反序列化时兼容。
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetState {
+    #[default]
+    Down,
+    Degraded,
+    Up,
+}
+
+impl TargetState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TargetState::Up => "UP",
+            TargetState::Degraded => "DEGRADED",
+            TargetState::Down => "DOWN",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Named {
+            Down,
+            Degraded,
+            Up,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Legacy(bool),
+            Named(Named),
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Legacy(true) | Repr::Named(Named::Up) => TargetState::Up,
+            Repr::Legacy(false) | Repr::Named(Named::Down) => TargetState::Down,
+            Repr::Named(Named::Degraded) => TargetState::Degraded,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorStatus {
     pub target: Target,
     pub records: VecDeque<ProbeRecord>,
-    pub current_state: bool,
+    pub current_state: TargetState,
+    // 因父目标故障而 DOWN (告警已抑制)
+    #[serde(default)]
+    pub unreachable: bool,
+    // 最近 24 小时的可用率 (%)，每次探测后更新
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_24h: Option<f64>,
+    #[serde(default)]
+    pub totals: Totals,
+    // 进行中的故障 (离开 UP 时创建，恢复 UP 时清除)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incident: Option<OngoingIncident>,
+    // 最近一次确认的状态切换时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_change: Option<DateTime<Local>>,
+}
+
+/// 一次进行中的故障。被确认后，恢复前的后续告警 (如 DEGRADED → DOWN) 不再发送，恢复告警照常发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OngoingIncident {
+    pub id: String,
+    pub started: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged: Option<Acknowledgement>,
+}
+
+impl OngoingIncident {
+    pub fn open() -> Self {
+        Self {
+            id: format!("i_{}", Uuid::new_v4().simple()),
+            started: Local::now(),
+            acknowledged: None,
+        }
+    }
+}
+
+/// 对故障的确认：确认人、备注与确认时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub at: DateTime<Local>,
+}
+
+/// 经广播通道分发给 SSE / WebSocket 连接的实时推送
+#[derive(Debug, Clone)]
+pub enum LiveUpdate {
+    // 一次探测的增量 (`update` 事件)
+    Probe(Arc<StatusUpdate>),
+    // 完整状态 (`status` 事件)：目标新增或配置变更、导入历史后整体替换
+    Snapshot(Arc<MonitorStatus>),
+}
+
+impl LiveUpdate {
+    pub fn target_id(&self) -> &str {
+        match self {
+            LiveUpdate::Probe(u) => &u.target_id,
+            LiveUpdate::Snapshot(s) => &s.target.id,
+        }
+    }
+}
+
+/// 探测增量：只含新记录与状态，客户端把 `record` 插入 `init` 中该目标记录的最前面
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusUpdate {
+    pub target_id: String,
+    pub record: ProbeRecord,
+    pub state: TargetState,
+    // 本次探测导致状态切换时为切换前的状态
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_state: Option<TargetState>,
+    pub unreachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_24h: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incident: Option<OngoingIncident>,
+}
+
+/// 累计计数，随缓存持久化，不受记录保留策略与降采样影响。
+/// 每条记录的时长要到下一次探测才确定，因此在下一次探测时计入
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Totals {
+    pub since: Option<DateTime<Local>>,
+    pub probes: u64,
+    pub failures: u64,
+    pub up_secs: u64,
+    pub downtime_secs: u64,
+    pub latency_sum_ms: f64,
+    pub latency_samples: u64,
 }
 
 impl MonitorStatus {
     pub fn new(target: Target) -> Self {
-        let initial_state = target.last_known_state.unwrap_or(false);
+        let initial_state = target.last_known_state.unwrap_or_default();
         Self {
             target,
             records: VecDeque::with_capacity(60),
             current_state: initial_state,
+            unreachable: false,
+            uptime_24h: None,
+            totals: Totals::default(),
+            incident: None,
+            last_change: None,
         }
     }
 }
+
+/// 调度器可见性：每个目标的探测时间线，用于排查"为什么很久没探测"
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ScheduleInfo {
+    pub target_id: String,
+    pub target_name: String,
+    pub last_probe: Option<DateTime<Local>>,
+    pub last_duration_ms: Option<f32>,
+    pub next_probe: Option<DateTime<Local>>,
+    pub running: bool,
+    pub backoff_secs: u64,
+    pub worker: String,
+    // 当前处于 active_cron 时间窗之外
+    pub outside_window: bool,
+}
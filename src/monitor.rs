@@ -1,98 +1,448 @@
+use crate::alert::AlertContext;
 use crate::config;
-use crate::model::{AppConfig, MonitorStatus, ProbeRecord, Protocol, Target};
-use chrono::Local;
+use crate::dns::{lookup_a, resolve_ip};
+use crate::live::LiveHub;
+use crate::model::{
+    AlertConfig, AppConfig, HttpConfig, HttpMethod, HttpTiming, LiveUpdate, MonitorStatus,
+    OngoingIncident, ProbeRecord, Protocol, ScheduleInfo, StatusUpdate, Target, TargetState,
+    TcpMode,
+};
+use crate::report::{ReportPoint, Sink};
+use chrono::{Local, Timelike};
+use croner::Cron;
 use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::IpAddr;
 use std::sync::Arc;
-use tokio::net::TcpStream;
-use tokio::sync::{broadcast, mpsc, watch};
-use tokio::time::{sleep, Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 use trust_dns_resolver::TokioAsyncResolver;
+use uuid::Uuid;
 
 // Protocol Probes
+#[derive(Default)]
+struct ProbeResult {
+    success: bool,
+    latency_ms: Option<f32>,
+    message: Option<String>, // 错误信息或附加结果
+    reuse_latency_ms: Option<f32>,
+    metrics: BTreeMap<String, f64>,
+    content_hash: Option<String>,
+    timing: Option<HttpTiming>,
+    status_code: Option<u16>,
+    response_bytes: Option<u64>,
+    ttl: Option<u8>,
+    hops: Option<u8>,
+    degraded: Option<String>, // 探测成功但服务降级的原因
+}
+
+impl ProbeResult {
+    fn up(latency_ms: f32, message: Option<String>) -> Self {
+        Self {
+            success: true,
+            latency_ms: Some(latency_ms),
+            message,
+            ..Default::default()
+        }
+    }
+
+    fn down(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: Some(message.into()),
+            ..Default::default()
+        }
+    }
+
+    fn with_http(self, status: reqwest::StatusCode, response_bytes: Option<u64>) -> Self {
+        Self {
+            status_code: Some(status.as_u16()),
+            response_bytes,
+            ..self
+        }
+    }
+}
+
 #[async_trait::async_trait]
 trait Probe {
-    async fn probe(&self, target: &Target) -> (bool, Option<f32>, Option<String>);
+    async fn probe(&self, target: &Target) -> ProbeResult;
+}
+
+/// `bind` 字段：本地 IP 地址 或 网卡名 (网卡绑定仅支持 Linux)
+enum BindSpec<'a> {
+    Addr(IpAddr),
+    Interface(&'a str),
+}
+
+fn parse_bind(bind: Option<&str>) -> Option<BindSpec<'_>> {
+    let bind = bind.filter(|b| !b.is_empty())?;
+    Some(match bind.parse::<IpAddr>() {
+        Ok(ip) => BindSpec::Addr(ip),
+        Err(_) => BindSpec::Interface(bind),
+    })
+}
+
+// 解析 host 并拼接端口
+async fn target_addr(target: &Target, default_port: u16) -> Result<SocketAddr, String> {
+    let ip = resolve_ip(&target.host, &target.resolver).await?;
+    Ok(SocketAddr::new(ip, target.port.unwrap_or(default_port)))
+}
+
+/// HTTP/HTTPS 与 DNS 探测的客户端只能绑定本地 IP，不能绑定网卡
+pub fn supports_interface_bind(protocol: &Protocol) -> bool {
+    !matches!(protocol, Protocol::Http | Protocol::Https | Protocol::Dns)
+}
+
+// 建立 TCP 连接，按需绑定源地址或出口网卡
+async fn connect_tcp(addr: SocketAddr, bind: Option<&str>) -> std::io::Result<TcpStream> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    match parse_bind(bind) {
+        Some(BindSpec::Addr(ip)) => socket.bind(SocketAddr::new(ip, 0))?,
+        Some(BindSpec::Interface(name)) => bind_device(&socket, name)?,
+        None => {}
+    }
+    socket.connect(addr).await
+}
+
+#[cfg(target_os = "linux")]
+fn bind_device(socket: &TcpSocket, name: &str) -> std::io::Result<()> {
+    socket.bind_device(Some(name.as_bytes()))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_device(_socket: &TcpSocket, _name: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Interface binding is only supported on Linux",
+    ))
 }
 
 struct TcpProbe;
 #[async_trait::async_trait]
 impl Probe for TcpProbe {
-    async fn probe(&self, target: &Target) -> (bool, Option<f32>, Option<String>) {
-        let port = target.port.unwrap_or(80);
-        let addr = format!("{}:{}", target.host, port);
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let addr = match target_addr(target, 80).await {
+            Ok(addr) => addr,
+            Err(e) => return ProbeResult::down(e),
+        };
         let start = Instant::now();
+        let connect = connect_tcp(addr, target.bind.as_deref());
+
+        match tokio::time::timeout(Duration::from_secs(3), connect).await {
+            Ok(Ok(_)) => ProbeResult::up(start.elapsed().as_micros() as f32 / 1000.0, None),
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+}
+
+struct TcpSynProbe;
+#[async_trait::async_trait]
+impl Probe for TcpSynProbe {
+    #[cfg(feature = "syn-probe")]
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        use crate::syn::{syn_probe, SynResult};
+
+        let port = target.port.unwrap_or(80);
+        let ip = if !target.resolver.is_empty() {
+            match resolve_ip(&target.host, &target.resolver).await {
+                Ok(IpAddr::V4(ip)) => ip,
+                Ok(IpAddr::V6(_)) => return ProbeResult::down("No IPv4 address for SYN probe"),
+                Err(e) => return ProbeResult::down(e),
+            }
+        } else {
+            match tokio::net::lookup_host((target.host.as_str(), port)).await {
+                Ok(mut addrs) => match addrs.find_map(|a| match a.ip() {
+                    IpAddr::V4(ip) => Some(ip),
+                    IpAddr::V6(_) => None,
+                }) {
+                    Some(ip) => ip,
+                    None => return ProbeResult::down("No IPv4 address for SYN probe"),
+                },
+                Err(e) => return ProbeResult::down(format!("DNS error: {}", e)),
+            }
+        };
 
-        match tokio::time::timeout(Duration::from_secs(3), TcpStream::connect(&addr)).await {
-            Ok(Ok(_)) => (
-                true,
-                Some(start.elapsed().as_micros() as f32 / 1000.0),
-                None,
+        let start = Instant::now();
+        let bind = target.bind.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            syn_probe(ip, port, bind.as_deref(), Duration::from_secs(3))
+        })
+        .await;
+        match result {
+            Ok(Ok(SynResult::Open)) => ProbeResult::up(
+                start.elapsed().as_micros() as f32 / 1000.0,
+                Some("SYN-ACK".to_string()),
             ),
-            Ok(Err(e)) => (false, None, Some(e.to_string())),
-            Err(_) => (false, None, Some("Timeout".to_string())),
+            Ok(Ok(SynResult::Closed)) => ProbeResult::down("Connection refused (RST)"),
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::TimedOut => ProbeResult::down("Timeout"),
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(e) => ProbeResult::down(e.to_string()),
         }
     }
+
+    #[cfg(not(feature = "syn-probe"))]
+    async fn probe(&self, _target: &Target) -> ProbeResult {
+        ProbeResult::down("SYN probe not available: build with --features syn-probe")
+    }
 }
 
 struct IcmpProbe;
 #[async_trait::async_trait]
 impl Probe for IcmpProbe {
-    async fn probe(&self, target: &Target) -> (bool, Option<f32>, Option<String>) {
+    async fn probe(&self, target: &Target) -> ProbeResult {
         // ICMP requires raw socket, might fail without root.
         // surge-ping 0.8 usage:
         // Pinger::new(_host)?.ping(seq, identifier, payload)
 
         // 解析 IP
-        let ip = match target.host.parse::<IpAddr>() {
+        let ip = match resolve_ip(&target.host, &target.resolver).await {
             Ok(ip) => ip,
-            Err(_) => {
-                // 尝试解析域名
-                match TokioAsyncResolver::tokio_from_system_conf() {
-                    Ok(resolver) => match resolver.lookup_ip(target.host.as_str()).await {
-                        Ok(ips) => {
-                            if let Some(ip) = ips.iter().next() {
-                                ip
-                            } else {
-                                return (false, None, Some("DNS resolution failed".into()));
-                            }
-                        }
-                        Err(e) => return (false, None, Some(format!("DNS error: {}", e))),
-                    },
-                    Err(_) => return (false, None, Some("Resolver init failed".into())),
-                }
-            }
+            Err(e) => return ProbeResult::down(e),
+        };
+
+        let mut builder = surge_ping::Config::builder();
+        if ip.is_ipv6() {
+            builder = builder.kind(surge_ping::ICMP::V6);
+        }
+        match parse_bind(target.bind.as_deref()) {
+            Some(BindSpec::Addr(local)) => builder = builder.bind(SocketAddr::new(local, 0)),
+            Some(BindSpec::Interface(name)) => builder = builder.interface(name),
+            None => {}
+        }
+        let client = match surge_ping::Client::new(&builder.build()) {
+            Ok(client) => client,
+            Err(e) => return ProbeResult::down(e.to_string()),
         };
 
         let payload = [0; 8];
-        match surge_ping::ping(ip, &payload).await {
-            Ok((_, duration)) => (true, Some(duration.as_micros() as f32 / 1000.0), None),
-            Err(e) => (false, None, Some(e.to_string())),
+        let ident = surge_ping::PingIdentifier(rand_xid() as u16);
+        let mut pinger = client.pinger(ip, ident).await;
+        let count = target.ping_count.unwrap_or(1).max(1);
+        let mut replies = Vec::new();
+        let mut last_error = None;
+        for seq in 0..count {
+            match pinger
+                .ping(surge_ping::PingSequence(seq as u16), &payload)
+                .await
+            {
+                Ok(reply) => replies.push(reply),
+                Err(e) => last_error = Some(e.to_string()),
+            }
+        }
+        let Some((packet, _)) = replies.last() else {
+            return ProbeResult::down(last_error.unwrap_or_else(|| "No reply".to_string()));
+        };
+
+        // 非特权 (DGRAM) ICMP socket 收不到 IP 头，此时没有 TTL
+        let ttl = match packet {
+            surge_ping::IcmpPacket::V4(p) => p.get_ttl(),
+            surge_ping::IcmpPacket::V6(p) => Some(p.get_max_hop_limit()),
+        };
+        let hops = ttl.map(estimate_hops);
+        let message = ttl
+            .zip(hops)
+            .map(|(t, h)| format!("TTL {} (~{} hops)", t, h));
+        let avg = replies
+            .iter()
+            .map(|(_, d)| d.as_micros() as f32)
+            .sum::<f32>()
+            / replies.len() as f32
+            / 1000.0;
+        let lost = count as usize - replies.len();
+        let loss_pct = lost as f64 * 100.0 / count as f64;
+        let mut metrics = BTreeMap::new();
+        if count > 1 {
+            metrics.insert("packet_loss_pct".to_string(), loss_pct);
+        }
+        ProbeResult {
+            ttl,
+            hops,
+            metrics,
+            degraded: (lost > 0).then(|| format!("Packet loss {:.0}%", loss_pct)),
+            ..ProbeResult::up(avg, message)
         }
     }
 }
 
+// 常见系统的初始 TTL 为 64 (Linux/macOS)、128 (Windows)、255 (网络设备)，
+// 取不小于收到值的最近一个作为初始值推算经过的跳数
+fn estimate_hops(ttl: u8) -> u8 {
+    let initial = [32u8, 64, 128, 255]
+        .into_iter()
+        .find(|&i| i >= ttl)
+        .unwrap_or(255);
+    initial - ttl
+}
+
 use once_cell::sync::Lazy;
 use std::net::SocketAddr;
 use trust_dns_resolver::config::{
     NameServerConfig, Protocol as DnsProtocol, ResolverConfig, ResolverOpts,
 };
 
-static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+fn http_client_builder(verify_tls: bool, keep_alive: bool) -> reqwest::ClientBuilder {
+    // 默认禁用连接池，避免复用到已失效的连接导致超时；keep-alive 对比探测时保留 1 条连接
+    let pool_idle = if keep_alive { 1 } else { 0 };
     reqwest::Client::builder()
         .timeout(Duration::from_secs(10))
-        .danger_accept_invalid_certs(true) // Allow self-signed certs for monitoring flexibility
+        .danger_accept_invalid_certs(!verify_tls)
         .no_gzip() // We only care about headers/status mostly
-        .pool_max_idle_per_host(0) // Disable connection pooling to prevent stale connection timeouts
+        .pool_max_idle_per_host(pool_idle)
         .user_agent(concat!("NetWatch/", env!("CARGO_PKG_VERSION")))
+}
+
+fn build_http_client(verify_tls: bool, keep_alive: bool) -> reqwest::Client {
+    http_client_builder(verify_tls, keep_alive)
         .build()
         .expect("Failed to build HTTP client")
-});
+}
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| build_http_client(true, false));
+// 仅用于 verify_tls = false 的目标 (自签名证书等)
+static HTTP_CLIENT_INSECURE: Lazy<reqwest::Client> = Lazy::new(|| build_http_client(false, false));
+
+struct HttpProbe {
+    http: HttpConfig,
+}
+
+impl HttpProbe {
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        target: &Target,
+        url: &str,
+    ) -> reqwest::RequestBuilder {
+        let method = match target.http_method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Head => reqwest::Method::HEAD,
+        };
+        let mut req = client.request(method, url);
+
+        // 全局请求头在前，Target 自定义请求头覆盖同名项
+        let mut headers = self.http.headers.clone();
+        headers.extend(target.headers.clone());
+
+        // cookies 合并进 Cookie 请求头 (与手写的 Cookie 头共存)
+        if !target.cookies.is_empty() {
+            let mut cookie = target
+                .cookies
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if let Some(key) = headers
+                .keys()
+                .find(|k| k.eq_ignore_ascii_case("cookie"))
+                .cloned()
+            {
+                let existing = headers.remove(&key).unwrap_or_default();
+                cookie = format!("{}; {}", existing, cookie);
+            }
+            headers.insert("Cookie".to_string(), cookie);
+        }
+
+        for (name, value) in &headers {
+            req = req.header(name.as_str(), value.as_str());
+        }
+
+        if let Some(ua) = target.user_agent.as_ref().or(self.http.user_agent.as_ref()) {
+            req = req.header(reqwest::header::USER_AGENT, ua.as_str());
+        }
+        req
+    }
+
+    // 同一连接上连续请求两次：第一次包含 DNS/TCP/TLS 建连开销，第二次复用连接，
+    // 两者之差即为建连耗时，可区分网络/TLS 劣化与应用本身变慢。
+    async fn probe_keep_alive(
+        &self,
+        client: &reqwest::Client,
+        target: &Target,
+        url: &str,
+    ) -> ProbeResult {
+        let start = Instant::now();
+        let res = match self.build_request(client, target, url).send().await {
+            Ok(res) => res,
+            Err(e) => return ProbeResult::down(e.to_string()),
+        };
+        let fresh = start.elapsed().as_micros() as f32 / 1000.0;
+        let status = res.status();
+        let declared = content_length(res.headers());
+        if !status.is_success() {
+            return ProbeResult::down(format!("HTTP Error: {}", status))
+                .with_http(status, declared);
+        }
+        if let Err(e) = check_headers(target, res.headers()) {
+            return ProbeResult::down(e).with_http(status, declared);
+        }
+        let closes = res
+            .headers()
+            .get(reqwest::header::CONNECTION)
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"close"));
+        // 必须读完响应体，连接才会归还连接池
+        let body = res.bytes().await;
+        let size = body.as_ref().ok().map(|b| b.len() as u64).or(declared);
+        let degraded = body.as_ref().ok().and_then(|b| body_degraded(target, b));
+        let content_hash = match body {
+            Ok(body) if target.watch_content => Some(hex::encode(Sha256::digest(&body))),
+            Err(e) if target.watch_content => {
+                return ProbeResult::down(format!("Failed to read body: {}", e))
+                    .with_http(status, declared)
+            }
+            _ => None,
+        };
+
+        if closes {
+            return ProbeResult {
+                content_hash,
+                degraded,
+                ..ProbeResult::up(
+                    fresh,
+                    Some(format!("Status: {} (keep-alive not supported)", status)),
+                )
+            }
+            .with_http(status, size);
+        }
+
+        let start = Instant::now();
+        match self.build_request(client, target, url).send().await {
+            Ok(res) if res.status().is_success() => {
+                let reused = start.elapsed().as_micros() as f32 / 1000.0;
+                let _ = res.bytes().await;
+                ProbeResult {
+                    reuse_latency_ms: Some(reused),
+                    content_hash,
+                    degraded,
+                    ..ProbeResult::up(
+                        fresh,
+                        Some(format!(
+                            "Status: {}, fresh {:.1}ms / reused {:.1}ms",
+                            status, fresh, reused
+                        )),
+                    )
+                }
+                .with_http(status, size)
+            }
+            Ok(res) => {
+                ProbeResult::down(format!("HTTP Error on reused connection: {}", res.status()))
+                    .with_http(res.status(), content_length(res.headers()))
+            }
+            Err(e) => ProbeResult::down(format!("Reused connection failed: {}", e)),
+        }
+    }
+}
 
-struct HttpProbe;
 #[async_trait::async_trait]
 impl Probe for HttpProbe {
-    async fn probe(&self, target: &Target) -> (bool, Option<f32>, Option<String>) {
+    async fn probe(&self, target: &Target) -> ProbeResult {
         let protocol = match target.protocol {
             Protocol::Https => "https",
             _ => "http",
@@ -105,38 +455,353 @@ impl Probe for HttpProbe {
             format!("{}://{}{}", protocol, target.host, port_str)
         };
 
+        if target.phase_timing {
+            return self.probe_phases(target, &host).await;
+        }
+
+        // 指定了 DNS 服务器或需要 keep-alive 时使用独立 client，否则复用全局 client
+        let bind = parse_bind(target.bind.as_deref());
+        let client = if target.resolver.is_empty() && !target.keep_alive_probe && bind.is_none() {
+            if target.verify_tls {
+                HTTP_CLIENT.clone()
+            } else {
+                HTTP_CLIENT_INSECURE.clone()
+            }
+        } else {
+            let mut builder = http_client_builder(target.verify_tls, target.keep_alive_probe);
+            match bind {
+                Some(BindSpec::Addr(local)) => builder = builder.local_address(local),
+                Some(BindSpec::Interface(_)) => {
+                    return ProbeResult::down("HTTP probes can only bind to a local IP address")
+                }
+                None => {}
+            }
+            if !target.resolver.is_empty() {
+                let url = match reqwest::Url::parse(&host) {
+                    Ok(url) => url,
+                    Err(e) => return ProbeResult::down(format!("Invalid URL: {}", e)),
+                };
+                if let (Some(domain), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+                    match resolve_ip(domain, &target.resolver).await {
+                        Ok(ip) => builder = builder.resolve(domain, SocketAddr::new(ip, port)),
+                        Err(e) => return ProbeResult::down(e),
+                    }
+                }
+            }
+            match builder.build() {
+                Ok(client) => client,
+                Err(e) => return ProbeResult::down(e.to_string()),
+            }
+        };
+
+        if target.keep_alive_probe {
+            return self.probe_keep_alive(&client, target, &host).await;
+        }
+
         let start = Instant::now();
-        match HTTP_CLIENT.get(&host).send().await {
+        match self.build_request(&client, target, &host).send().await {
             Ok(res) => {
+                // send() 在收到响应头后即返回，这里不读取响应体，drop 时直接断开连接
                 let duration = start.elapsed().as_micros() as f32 / 1000.0;
                 let status = res.status();
-                if status.is_success() {
-                    (true, Some(duration), Some(format!("Status: {}", status)))
+                let declared = content_length(res.headers());
+                if !status.is_success() {
+                    ProbeResult::down(format!("HTTP Error: {}", status)).with_http(status, declared)
+                } else if let Err(e) = check_headers(target, res.headers()) {
+                    ProbeResult::down(e).with_http(status, declared)
+                } else if reads_body(target) {
+                    // 内容监控需要读取完整响应体，latency 仍以收到响应头为准
+                    match res.bytes().await {
+                        Ok(body) => ProbeResult {
+                            content_hash: target
+                                .watch_content
+                                .then(|| hex::encode(Sha256::digest(&body))),
+                            degraded: body_degraded(target, &body),
+                            ..ProbeResult::up(duration, Some(format!("Status: {}", status)))
+                        }
+                        .with_http(status, Some(body.len() as u64)),
+                        Err(e) => ProbeResult::down(format!("Failed to read body: {}", e))
+                            .with_http(status, declared),
+                    }
                 } else {
-                    (false, None, Some(format!("HTTP Error: {}", status)))
+                    ProbeResult::up(duration, Some(format!("Status: {}", status)))
+                        .with_http(status, declared)
                 }
             }
-            Err(e) => (false, None, Some(e.to_string())),
+            Err(e) => ProbeResult::down(e.to_string()),
+        }
+    }
+}
+
+// 分阶段计时需要自行建连：reqwest 不暴露 DNS / TCP / TLS 各阶段耗时
+static TLS_CONFIG: Lazy<Arc<rustls::ClientConfig>> = Lazy::new(|| {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    let mut config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Arc::new(config)
+});
+
+// 仅用于 verify_tls = false 的目标
+static TLS_CONFIG_INSECURE: Lazy<Arc<rustls::ClientConfig>> = Lazy::new(|| {
+    let mut config = (**TLS_CONFIG).clone();
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(NoCertVerification));
+    Arc::new(config)
+});
+
+struct NoCertVerification;
+impl rustls::client::ServerCertVerifier for NoCertVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f32 {
+    start.elapsed().as_micros() as f32 / 1000.0
+}
+
+impl HttpProbe {
+    async fn probe_phases(&self, target: &Target, url: &str) -> ProbeResult {
+        match tokio::time::timeout(Duration::from_secs(10), self.send_timed(target, url)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => ProbeResult::down(e),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+
+    async fn send_timed(&self, target: &Target, url: &str) -> Result<ProbeResult, String> {
+        // 复用 build_request 的方法 / 请求头 / Cookie 逻辑
+        let request = self
+            .build_request(&HTTP_CLIENT, target, url)
+            .build()
+            .map_err(|e| format!("Invalid request: {}", e))?;
+        let url = request.url().clone();
+        // IPv6 地址在 URL 中带方括号
+        let host = url
+            .host_str()
+            .map(|h| h.trim_start_matches('[').trim_end_matches(']').to_string())
+            .ok_or_else(|| "URL has no host".to_string())?;
+        let port = url.port_or_known_default().unwrap_or(80);
+
+        let start = Instant::now();
+        let ip = resolve_ip(&host, &target.resolver).await?;
+        let dns_ms = elapsed_ms(start);
+
+        let phase = Instant::now();
+        let tcp = connect_tcp(SocketAddr::new(ip, port), target.bind.as_deref())
+            .await
+            .map_err(|e| format!("Connect failed: {}", e))?;
+        let connect_ms = elapsed_ms(phase);
+
+        let (mut sender, tls_ms) = if url.scheme() == "https" {
+            let phase = Instant::now();
+            let config = if target.verify_tls {
+                TLS_CONFIG.clone()
+            } else {
+                TLS_CONFIG_INSECURE.clone()
+            };
+            let server_name = match host.parse::<IpAddr>() {
+                Ok(ip) => rustls::ServerName::IpAddress(ip),
+                Err(_) => rustls::ServerName::try_from(host.as_str())
+                    .map_err(|e| format!("Invalid server name: {}", e))?,
+            };
+            let tls = tokio_rustls::TlsConnector::from(config)
+                .connect(server_name, tcp)
+                .await
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+            let tls_ms = elapsed_ms(phase);
+            let (sender, conn) = hyper::client::conn::handshake(tls)
+                .await
+                .map_err(|e| e.to_string())?;
+            tokio::spawn(conn);
+            (sender, Some(tls_ms))
+        } else {
+            let (sender, conn) = hyper::client::conn::handshake(tcp)
+                .await
+                .map_err(|e| e.to_string())?;
+            tokio::spawn(conn);
+            (sender, None)
+        };
+
+        let path = match url.query() {
+            Some(q) => format!("{}?{}", url.path(), q),
+            None => url.path().to_string(),
+        };
+        let mut req = hyper::Request::builder()
+            .method(request.method().clone())
+            .uri(path)
+            .body(hyper::Body::empty())
+            .map_err(|e| e.to_string())?;
+        *req.headers_mut() = request.headers().clone();
+        // 直接使用 hyper 连接时需要自己补上 Host 与默认 User-Agent
+        let host_header = match url.port() {
+            Some(p) => format!("{}:{}", url.host_str().unwrap_or_default(), p),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let headers = req.headers_mut();
+        if let Ok(value) = host_header.parse() {
+            headers.insert(reqwest::header::HOST, value);
+        }
+        if !headers.contains_key(reqwest::header::USER_AGENT) {
+            headers.insert(
+                reqwest::header::USER_AGENT,
+                reqwest::header::HeaderValue::from_static(concat!(
+                    "NetWatch/",
+                    env!("CARGO_PKG_VERSION")
+                )),
+            );
+        }
+
+        let phase = Instant::now();
+        let res = sender.send_request(req).await.map_err(|e| e.to_string())?;
+        let ttfb_ms = elapsed_ms(phase);
+        let latency = elapsed_ms(start);
+        let timing = Some(HttpTiming {
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            ttfb_ms,
+        });
+
+        let status = res.status();
+        let declared = content_length(res.headers());
+        let failure = if !status.is_success() {
+            Some(format!("HTTP Error: {}", status))
+        } else {
+            check_headers(target, res.headers()).err()
+        };
+        if let Some(message) = failure {
+            return Ok(ProbeResult {
+                timing,
+                ..ProbeResult::down(message)
+            }
+            .with_http(status, declared));
+        }
+
+        let (content_hash, degraded, size) = if reads_body(target) {
+            let body = hyper::body::to_bytes(res.into_body())
+                .await
+                .map_err(|e| format!("Failed to read body: {}", e))?;
+            (
+                target
+                    .watch_content
+                    .then(|| hex::encode(Sha256::digest(&body))),
+                body_degraded(target, &body),
+                Some(body.len() as u64),
+            )
+        } else {
+            (None, None, declared)
+        };
+        let message = match tls_ms {
+            Some(tls) => format!(
+                "Status: {}, dns {:.1} / connect {:.1} / tls {:.1} / ttfb {:.1} ms",
+                status, dns_ms, connect_ms, tls, ttfb_ms
+            ),
+            None => format!(
+                "Status: {}, dns {:.1} / connect {:.1} / ttfb {:.1} ms",
+                status, dns_ms, connect_ms, ttfb_ms
+            ),
+        };
+        Ok(ProbeResult {
+            timing,
+            content_hash,
+            degraded,
+            ..ProbeResult::up(latency, Some(message))
         }
+        .with_http(status, size))
     }
 }
 
+fn reads_body(target: &Target) -> bool {
+    target.watch_content || target.degraded_body.is_some()
+}
+
+/// 响应体包含 `degraded_body` 时返回 DEGRADED 原因 (如 Elasticsearch 的 yellow 集群状态)
+fn body_degraded(target: &Target, body: &[u8]) -> Option<String> {
+    let needle = target.degraded_body.as_deref().filter(|n| !n.is_empty())?;
+    String::from_utf8_lossy(body)
+        .contains(needle)
+        .then(|| format!("Response body contains {:?}", needle))
+}
+
+fn content_length(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+// 校验响应头断言，用于发现仍返回 200 但被错误路由的反向代理配置
+fn check_headers(target: &Target, headers: &reqwest::header::HeaderMap) -> Result<(), String> {
+    for assertion in &target.expect_headers {
+        let actual = headers
+            .get(assertion.name.as_str())
+            .map(|v| String::from_utf8_lossy(v.as_bytes()).to_string());
+        match (&actual, assertion.absent) {
+            (Some(v), true) => {
+                return Err(format!(
+                    "Header {} should be absent, got '{}'",
+                    assertion.name, v
+                ))
+            }
+            (None, false) => return Err(format!("Missing header {}", assertion.name)),
+            _ => {}
+        }
+        if let (Some(actual), Some(expected)) = (&actual, &assertion.value) {
+            if !actual.to_lowercase().contains(&expected.to_lowercase()) {
+                return Err(format!(
+                    "Header {} is '{}', expected '{}'",
+                    assertion.name, actual, expected
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 struct DnsProbe;
 #[async_trait::async_trait]
 impl Probe for DnsProbe {
-    async fn probe(&self, target: &Target) -> (bool, Option<f32>, Option<String>) {
+    async fn probe(&self, target: &Target) -> ProbeResult {
         // Parse target host as IP for custom Name Server
         let ip = match target.host.parse::<IpAddr>() {
             Ok(ip) => ip,
-            Err(e) => return (false, None, Some(format!("Invalid DNS Server IP: {}", e))),
+            Err(e) => return ProbeResult::down(format!("Invalid DNS Server IP: {}", e)),
         };
 
         let port = target.port.unwrap_or(53);
         let socket_addr = SocketAddr::new(ip, port);
 
         // Configure resolver to use the target as Name Server
+        let mut name_server = NameServerConfig::new(socket_addr, DnsProtocol::Udp);
+        match parse_bind(target.bind.as_deref()) {
+            Some(BindSpec::Addr(local)) => name_server.bind_addr = Some(SocketAddr::new(local, 0)),
+            Some(BindSpec::Interface(_)) => {
+                return ProbeResult::down("DNS probes can only bind to a local IP address")
+            }
+            None => {}
+        }
         let mut config = ResolverConfig::new();
-        config.add_name_server(NameServerConfig::new(socket_addr, DnsProtocol::Udp));
+        config.add_name_server(name_server);
 
         // Create resolver
         let resolver = TokioAsyncResolver::tokio(config, ResolverOpts::default());
@@ -153,41 +818,610 @@ impl Probe for DnsProbe {
                     .map(|ip| ip.to_string())
                     .collect::<Vec<_>>()
                     .join(", ");
-                (true, Some(duration), Some(result))
+                ProbeResult::up(duration, Some(result))
+            }
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+}
+
+struct VncProbe;
+#[async_trait::async_trait]
+impl Probe for VncProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let addr = match target_addr(target, 5900).await {
+            Ok(addr) => addr,
+            Err(e) => return ProbeResult::down(e),
+        };
+        let start = Instant::now();
+
+        // RFB 服务端在连接建立后会主动发送 12 字节的版本号，例如 "RFB 003.008\n"
+        let handshake = async {
+            let mut stream = connect_tcp(addr, target.bind.as_deref()).await?;
+            let mut banner = [0u8; 12];
+            stream.read_exact(&mut banner).await?;
+            Ok::<_, std::io::Error>(banner)
+        };
+
+        match tokio::time::timeout(Duration::from_secs(3), handshake).await {
+            Ok(Ok(banner)) => {
+                let duration = start.elapsed().as_micros() as f32 / 1000.0;
+                let banner = String::from_utf8_lossy(&banner).trim_end().to_string();
+                if banner.starts_with("RFB ") {
+                    ProbeResult::up(duration, Some(banner))
+                } else {
+                    ProbeResult::down(format!("Unexpected banner: {:?}", banner))
+                }
+            }
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+}
+
+struct SmbProbe;
+#[async_trait::async_trait]
+impl Probe for SmbProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let addr = match target_addr(target, 445).await {
+            Ok(addr) => addr,
+            Err(e) => return ProbeResult::down(e),
+        };
+        let start = Instant::now();
+
+        let negotiate = async {
+            let mut stream = connect_tcp(addr, target.bind.as_deref()).await?;
+            stream.write_all(&smb2_negotiate_request()).await?;
+
+            // NetBIOS Session Service 头: 1 字节类型 + 3 字节长度
+            let mut nbss = [0u8; 4];
+            stream.read_exact(&mut nbss).await?;
+            let len = u32::from_be_bytes([0, nbss[1], nbss[2], nbss[3]]) as usize;
+            let mut resp = vec![0u8; len.min(4096)];
+            stream.read_exact(&mut resp).await?;
+            Ok::<_, std::io::Error>(resp)
+        };
+
+        match tokio::time::timeout(Duration::from_secs(3), negotiate).await {
+            Ok(Ok(resp)) => {
+                let duration = start.elapsed().as_micros() as f32 / 1000.0;
+                if resp.len() < 70 || &resp[0..4] != b"\xFESMB" {
+                    return ProbeResult::down("Invalid SMB2 response");
+                }
+                let status = u32::from_le_bytes([resp[8], resp[9], resp[10], resp[11]]);
+                if status != 0 {
+                    return ProbeResult::down(format!("SMB negotiate failed: 0x{:08X}", status));
+                }
+                let dialect = u16::from_le_bytes([resp[68], resp[69]]);
+                ProbeResult::up(
+                    duration,
+                    Some(format!(
+                        "SMB dialect {}.{}.{}",
+                        dialect >> 8,
+                        (dialect >> 4) & 0xF,
+                        dialect & 0xF
+                    )),
+                )
+            }
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+}
+
+// SMB2 NEGOTIATE 请求 (带 NetBIOS 头)，声明 2.0.2 ~ 3.0.2 方言
+fn smb2_negotiate_request() -> Vec<u8> {
+    const DIALECTS: [u16; 4] = [0x0202, 0x0210, 0x0300, 0x0302];
+
+    let mut msg = Vec::with_capacity(108);
+    // SMB2 Header (64 bytes)
+    msg.extend_from_slice(b"\xFESMB");
+    msg.extend_from_slice(&64u16.to_le_bytes()); // StructureSize
+    msg.extend_from_slice(&0u16.to_le_bytes()); // CreditCharge
+    msg.extend_from_slice(&0u32.to_le_bytes()); // Status
+    msg.extend_from_slice(&0u16.to_le_bytes()); // Command: NEGOTIATE
+    msg.extend_from_slice(&1u16.to_le_bytes()); // CreditRequest
+    msg.extend_from_slice(&0u32.to_le_bytes()); // Flags
+    msg.extend_from_slice(&0u32.to_le_bytes()); // NextCommand
+    msg.extend_from_slice(&0u64.to_le_bytes()); // MessageId
+    msg.extend_from_slice(&0u32.to_le_bytes()); // Reserved
+    msg.extend_from_slice(&0u32.to_le_bytes()); // TreeId
+    msg.extend_from_slice(&0u64.to_le_bytes()); // SessionId
+    msg.extend_from_slice(&[0u8; 16]); // Signature
+                                       // NEGOTIATE Request
+    msg.extend_from_slice(&36u16.to_le_bytes()); // StructureSize
+    msg.extend_from_slice(&(DIALECTS.len() as u16).to_le_bytes());
+    msg.extend_from_slice(&1u16.to_le_bytes()); // SecurityMode: signing enabled
+    msg.extend_from_slice(&0u16.to_le_bytes()); // Reserved
+    msg.extend_from_slice(&0u32.to_le_bytes()); // Capabilities
+    msg.extend_from_slice(Uuid::new_v4().as_bytes()); // ClientGuid
+    msg.extend_from_slice(&0u64.to_le_bytes()); // ClientStartTime
+    for d in DIALECTS {
+        msg.extend_from_slice(&d.to_le_bytes());
+    }
+
+    let mut packet = Vec::with_capacity(msg.len() + 4);
+    packet.extend_from_slice(&(msg.len() as u32).to_be_bytes());
+    packet.extend_from_slice(&msg);
+    packet
+}
+
+struct NfsProbe;
+#[async_trait::async_trait]
+impl Probe for NfsProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        const NFS_PROGRAM: u32 = 100003;
+        const NFS_VERSION: u32 = 3;
+
+        let addr = match target_addr(target, 2049).await {
+            Ok(addr) => addr,
+            Err(e) => return ProbeResult::down(e),
+        };
+        let xid: u32 = rand_xid();
+        let start = Instant::now();
+
+        // ONC RPC CALL: NFS NULL procedure, AUTH_NONE
+        let mut call = Vec::with_capacity(44);
+        for word in [xid, 0, 2, NFS_PROGRAM, NFS_VERSION, 0, 0, 0, 0, 0] {
+            call.extend_from_slice(&word.to_be_bytes());
+        }
+        let mut packet = Vec::with_capacity(call.len() + 4);
+        packet.extend_from_slice(&(0x8000_0000 | call.len() as u32).to_be_bytes()); // last fragment
+        packet.extend_from_slice(&call);
+
+        let rpc = async {
+            let mut stream = connect_tcp(addr, target.bind.as_deref()).await?;
+            stream.write_all(&packet).await?;
+            let mut mark = [0u8; 4];
+            stream.read_exact(&mut mark).await?;
+            let len = (u32::from_be_bytes(mark) & 0x7FFF_FFFF) as usize;
+            let mut resp = vec![0u8; len.min(4096)];
+            stream.read_exact(&mut resp).await?;
+            Ok::<_, std::io::Error>(resp)
+        };
+
+        match tokio::time::timeout(Duration::from_secs(3), rpc).await {
+            Ok(Ok(resp)) => {
+                let duration = start.elapsed().as_micros() as f32 / 1000.0;
+                let word = |i: usize| {
+                    resp.get(i * 4..i * 4 + 4)
+                        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+                };
+                // xid, REPLY(1), MSG_ACCEPTED(0), verf flavor, verf length
+                if word(0) != Some(xid) || word(1) != Some(1) {
+                    return ProbeResult::down("Invalid RPC reply");
+                }
+                if word(2) != Some(0) {
+                    return ProbeResult::down("RPC call denied");
+                }
+                let verf_words = word(4).map(|l| l.div_ceil(4) as usize).unwrap_or(0);
+                match word(5 + verf_words) {
+                    Some(0) => {
+                        ProbeResult::up(duration, Some(format!("NFSv{} NULL OK", NFS_VERSION)))
+                    }
+                    // 服务存在但不支持 v3，同样说明 NFS 服务在线
+                    Some(2) => ProbeResult::up(
+                        duration,
+                        Some(format!(
+                            "NFS supports v{}-v{}",
+                            word(6 + verf_words).unwrap_or(0),
+                            word(7 + verf_words).unwrap_or(0)
+                        )),
+                    ),
+                    Some(stat) => ProbeResult::down(format!("RPC accept_stat {}", stat)),
+                    None => ProbeResult::down("Truncated RPC reply"),
+                }
+            }
+            Ok(Err(e)) => ProbeResult::down(e.to_string()),
+            Err(_) => ProbeResult::down("Timeout"),
+        }
+    }
+}
+
+/// 配置接口可被远程修改，执行本地命令 (EXEC 探测与告警通道) 必须由运维显式设置
+/// `NETWATCH_ALLOW_EXEC=1` 开启，其他值 (包括空值与 0) 均视为关闭
+pub fn exec_allowed() -> bool {
+    std::env::var("NETWATCH_ALLOW_EXEC").is_ok_and(|v| v == "1")
+}
+
+struct ExecProbe;
+#[async_trait::async_trait]
+impl Probe for ExecProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        if !exec_allowed() {
+            return ProbeResult::down("EXEC probes are disabled (set NETWATCH_ALLOW_EXEC=1)");
+        }
+        let Some((program, args)) = target.command.split_first() else {
+            return ProbeResult::down("No command configured");
+        };
+
+        let start = Instant::now();
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(args).kill_on_drop(true);
+        let output = match tokio::time::timeout(Duration::from_secs(10), cmd.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return ProbeResult::down(e.to_string()),
+            Err(_) => return ProbeResult::down("Timeout"),
+        };
+        let duration = start.elapsed().as_micros() as f32 / 1000.0;
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let Some(mapping) = &target.result_mapping else {
+            return if output.status.success() {
+                ProbeResult::up(duration, Some(stdout).filter(|s| !s.is_empty()))
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                if stderr.is_empty() {
+                    ProbeResult::down(output.status.to_string())
+                } else {
+                    ProbeResult::down(format!("{}: {}", output.status, stderr))
+                }
+            };
+        };
+
+        let json: serde_json::Value = match serde_json::from_str(&stdout) {
+            Ok(v) => v,
+            Err(e) => return ProbeResult::down(format!("Invalid JSON output: {}", e)),
+        };
+        let lookup = |pointer: &Option<String>| pointer.as_deref().and_then(|p| json.pointer(p));
+
+        let success = match lookup(&mapping.state) {
+            Some(serde_json::Value::Bool(b)) => *b,
+            Some(serde_json::Value::Number(n)) => n.as_f64() == Some(0.0),
+            Some(serde_json::Value::String(s)) => {
+                matches!(s.to_ascii_lowercase().as_str(), "up" | "ok" | "true")
+            }
+            Some(_) => false,
+            None => output.status.success(),
+        };
+        let message = lookup(&mapping.message).map(|v| match v {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        });
+        let latency = lookup(&mapping.latency_ms)
+            .and_then(|v| v.as_f64())
+            .map(|v| v as f32)
+            .unwrap_or(duration);
+        let metrics = mapping
+            .metrics
+            .iter()
+            .filter_map(|(name, pointer)| {
+                json.pointer(pointer)
+                    .and_then(|v| v.as_f64())
+                    .map(|v| (name.clone(), v))
+            })
+            .collect();
+
+        if success {
+            ProbeResult {
+                metrics,
+                ..ProbeResult::up(latency, message)
+            }
+        } else {
+            ProbeResult {
+                metrics,
+                ..ProbeResult::down(message.unwrap_or_else(|| "Check reported failure".to_string()))
+            }
+        }
+    }
+}
+
+struct DomainLookup {
+    fetched: Instant,
+    latency_ms: f32,
+    result: Result<(chrono::DateTime<chrono::Utc>, &'static str), String>,
+}
+
+// 注册信息变化很慢，查询结果缓存一段时间，避免每轮探测都请求 RDAP/WHOIS 而被限流
+const DOMAIN_REFRESH: Duration = Duration::from_secs(6 * 3600);
+const DOMAIN_RETRY: Duration = Duration::from_secs(600);
+static DOMAIN_CACHE: Lazy<DashMap<String, DomainLookup>> = Lazy::new(DashMap::new);
+
+struct DomainProbe;
+#[async_trait::async_trait]
+impl Probe for DomainProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let key = target.host.to_ascii_lowercase();
+        let fresh = DOMAIN_CACHE.get(&key).is_some_and(|c| {
+            let ttl = if c.result.is_ok() {
+                DOMAIN_REFRESH
+            } else {
+                DOMAIN_RETRY
+            };
+            c.fetched.elapsed() < ttl
+        });
+        if !fresh {
+            let start = Instant::now();
+            let result = crate::domain::expiry_date(&key).await;
+            DOMAIN_CACHE.insert(
+                key.clone(),
+                DomainLookup {
+                    fetched: Instant::now(),
+                    latency_ms: start.elapsed().as_micros() as f32 / 1000.0,
+                    result,
+                },
+            );
+        }
+
+        let Some(lookup) = DOMAIN_CACHE.get(&key) else {
+            return ProbeResult::down("Lookup cache missing");
+        };
+        let (expiry, source) = match &lookup.result {
+            Ok(v) => *v,
+            Err(e) => return ProbeResult::down(e.clone()),
+        };
+        let days = (expiry - chrono::Utc::now()).num_days();
+        let metrics = BTreeMap::from([("days_remaining".to_string(), days as f64)]);
+        if days < target.expiry_warn_days as i64 {
+            ProbeResult {
+                metrics,
+                ..ProbeResult::down(format!(
+                    "Domain expires in {} days ({})",
+                    days,
+                    expiry.format("%Y-%m-%d")
+                ))
+            }
+        } else {
+            ProbeResult {
+                metrics,
+                ..ProbeResult::up(
+                    lookup.latency_ms,
+                    Some(format!(
+                        "Expires {} ({} days, via {})",
+                        expiry.format("%Y-%m-%d"),
+                        days,
+                        source
+                    )),
+                )
+            }
+        }
+    }
+}
+
+const DEFAULT_DNSBL_ZONES: &[&str] = &[
+    "zen.spamhaus.org",
+    "bl.spamcop.net",
+    "b.barracudacentral.org",
+];
+
+struct DnsblProbe;
+#[async_trait::async_trait]
+impl Probe for DnsblProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let ip = match resolve_ip(&target.host, &target.resolver).await {
+            Ok(ip) => ip,
+            Err(e) => return ProbeResult::down(e),
+        };
+        // 1.2.3.4 -> 4.3.2.1；IPv6 按 nibble 逆序
+        let reversed = match ip {
+            IpAddr::V4(v4) => {
+                let o = v4.octets();
+                format!("{}.{}.{}.{}", o[3], o[2], o[1], o[0])
+            }
+            IpAddr::V6(v6) => v6
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|b| [b & 0x0f, b >> 4])
+                .map(|n| format!("{:x}", n))
+                .collect::<Vec<_>>()
+                .join("."),
+        };
+        let zones: Vec<String> = if target.dnsbl_zones.is_empty() {
+            DEFAULT_DNSBL_ZONES.iter().map(|z| z.to_string()).collect()
+        } else {
+            target.dnsbl_zones.clone()
+        };
+
+        let start = Instant::now();
+        let lookups = zones.iter().map(|zone| {
+            // 末尾加 "." 避免被追加搜索域
+            let name = format!("{}.{}.", reversed, zone.trim_end_matches('.'));
+            async move {
+                let result =
+                    tokio::time::timeout(Duration::from_secs(5), lookup_a(&name, &target.resolver))
+                        .await
+                        .unwrap_or_else(|_| Err("Timeout".to_string()));
+                (zone, result)
+            }
+        });
+        let results = futures::future::join_all(lookups).await;
+        let duration = start.elapsed().as_micros() as f32 / 1000.0;
+
+        let mut listed = Vec::new();
+        let mut errors = Vec::new();
+        for (zone, result) in results {
+            match result {
+                Ok(None) => {}
+                Ok(Some(codes)) => {
+                    // 只有 127.0.0.0/8 (除 127.255.255.x 外) 才是列入；
+                    // 127.255.255.x 是 Spamhaus 拒绝公共解析器等查询错误
+                    let hits: Vec<String> = codes
+                        .iter()
+                        .filter(|a| a.octets()[0] == 127)
+                        .map(|a| a.to_string())
+                        .collect();
+                    if hits.iter().any(|a| a.starts_with("127.255.255.")) {
+                        errors.push(format!("{}: query refused ({})", zone, hits.join(",")));
+                    } else if !hits.is_empty() {
+                        listed.push(format!("{} ({})", zone, hits.join(",")));
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", zone, e)),
+            }
+        }
+
+        let metrics = BTreeMap::from([("listed_count".to_string(), listed.len() as f64)]);
+        if !listed.is_empty() {
+            ProbeResult {
+                metrics,
+                ..ProbeResult::down(format!("{} listed on {}", ip, listed.join(", ")))
+            }
+        } else if errors.len() == zones.len() {
+            ProbeResult::down(errors.join("; "))
+        } else {
+            let mut message = format!(
+                "{} not listed on {} zone(s)",
+                ip,
+                zones.len() - errors.len()
+            );
+            if !errors.is_empty() {
+                message.push_str(&format!(", failed: {}", errors.join("; ")));
+            }
+            ProbeResult {
+                metrics,
+                ..ProbeResult::up(duration, Some(message))
+            }
+        }
+    }
+}
+
+// 心跳投递间隔，与 healthchecks.io 等服务的最小周期一致；期间复用上次结果
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+static LAST_HEARTBEAT: Lazy<DashMap<String, Heartbeat>> = Lazy::new(DashMap::new);
+
+struct Heartbeat {
+    sent: Instant,
+    latency_ms: f32,
+    result: Result<usize, String>,
+}
+
+/// 端到端检查告警通道本身：渲染告警模版并投递到心跳通道，
+/// 外部心跳服务在收不到投递时告警，从而发现"告警静默失效"
+struct WatchdogProbe {
+    alert: AlertConfig,
+}
+#[async_trait::async_trait]
+impl Probe for WatchdogProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let due = LAST_HEARTBEAT
+            .get(&target.id)
+            .is_none_or(|last| last.sent.elapsed() >= HEARTBEAT_INTERVAL);
+        if due {
+            let start = Instant::now();
+            let result = crate::alert::send_heartbeat(target, &self.alert).await;
+            let duration = start.elapsed().as_micros() as f32 / 1000.0;
+            LAST_HEARTBEAT.insert(
+                target.id.clone(),
+                Heartbeat {
+                    sent: Instant::now(),
+                    latency_ms: duration,
+                    result,
+                },
+            );
+        }
+
+        let Some(last) = LAST_HEARTBEAT.get(&target.id) else {
+            return ProbeResult::down("Heartbeat state missing");
+        };
+        match &last.result {
+            Ok(count) => ProbeResult::up(
+                last.latency_ms,
+                Some(format!(
+                    "Heartbeat delivered to {} channel(s) {}s ago",
+                    count,
+                    last.sent.elapsed().as_secs()
+                )),
+            ),
+            Err(e) => ProbeResult::down(format!("Heartbeat failed: {}", e)),
+        }
+    }
+}
+
+/// 被动心跳 (dead man's switch)：不主动探测，检查被监控方是否在周期内请求过 `/api/push/:token`，
+/// 用于 cron 任务、备份脚本以及位于 NAT 之后无法探测的设备
+struct PushProbe {
+    period: Duration,
+}
+#[async_trait::async_trait]
+impl Probe for PushProbe {
+    async fn probe(&self, target: &Target) -> ProbeResult {
+        let period = chrono::Duration::from_std(self.period).unwrap_or(chrono::Duration::MAX);
+        let now = chrono::Local::now();
+        let Some(last) = crate::push::last(&target.id) else {
+            // 系统时钟回拨时起点可能晚于当前时间，按刚开始等待处理
+            let waiting = (now - crate::push::waiting_since(target)).max(chrono::Duration::zero());
+            if let Some(left) = period
+                .checked_sub(&waiting)
+                .filter(|left| *left > chrono::Duration::zero())
+            {
+                return ProbeResult {
+                    success: true,
+                    message: Some(format!(
+                        "Waiting for first heartbeat ({}s left)",
+                        left.num_seconds()
+                    )),
+                    ..Default::default()
+                };
             }
-            Ok(Err(e)) => (false, None, Some(e.to_string())),
-            Err(_) => (false, None, Some("Timeout".to_string())),
+            return ProbeResult::down(format!(
+                "No heartbeat received in {}s",
+                waiting.num_seconds()
+            ));
+        };
+
+        let age = (now - last.received).num_seconds();
+        if now - last.received > period {
+            return ProbeResult::down(format!(
+                "Last heartbeat {}s ago (period {}s)",
+                age,
+                self.period.as_secs()
+            ));
+        }
+        let message = match last.message {
+            Some(msg) => format!("Heartbeat {}s ago: {}", age, msg),
+            None => format!("Heartbeat {}s ago", age),
+        };
+        // 心跳未附带 ping 时不记录延迟
+        if last.success {
+            ProbeResult {
+                success: true,
+                latency_ms: last.latency_ms,
+                message: Some(message),
+                ..Default::default()
+            }
+        } else {
+            ProbeResult::down(message)
         }
     }
 }
 
+fn rand_xid() -> u32 {
+    let bytes = Uuid::new_v4().into_bytes();
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
 // ----------------------------------------------------------------
 
+// 当前所有探测都在本进程内执行
+const LOCAL_WORKER: &str = "local";
+
 pub enum MonitorEvent {
-    StateChanged(String, bool), // id, new_state
+    StateChanged(String, TargetState), // id, new_state
 }
 
 pub async fn start_monitor_loop(
     state: Arc<DashMap<String, MonitorStatus>>,
     tx: mpsc::Sender<MonitorEvent>,
     mut config_rx: watch::Receiver<AppConfig>,
-    broadcast_tx: broadcast::Sender<String>,
+    live: Arc<LiveHub>,
+    report_tx: mpsc::Sender<(Sink, ReportPoint)>,
+    schedule: Arc<DashMap<String, ScheduleInfo>>,
 ) {
     tracing::info!("Starting monitoring engine...");
 
-    // 我们需要一个循环来管理"探测循环"。
-    // 当配置更新时，我们需要更新探测列表。
-    // 简单的做法是：每次循环检查是否有配置更新，如果有，更新本地 targets 列表。
+    // 每个目标一个独立的探测任务，各自计时，慢探测不会拖累其他目标。
+    // 配置变更时只增删或重建发生变化的目标任务。
+    let mut tasks: HashMap<String, (u64, JoinHandle<()>)> = HashMap::new();
 
-    // 初始化 targets
-    let mut current_targets_hash = hash_targets(&config_rx.borrow().targets);
-    let mut targets = config_rx.borrow().targets.clone();
+    loop {
+        let targets = config_rx.borrow_and_update().targets.clone();
 
-    // 初始同步
-    {
-        let new_ids: std::collections::HashSet<String> =
-            targets.iter().map(|t| t.id.clone()).collect();
+        // 同步 DashMap
+        let new_ids: HashSet<String> = targets.iter().map(|t| t.id.clone()).collect();
         state.retain(|k, _| new_ids.contains(k));
+        let mut changed = Vec::new();
         for target in &targets {
             if !state.contains_key(&target.id) {
                 tracing::debug!(
@@ -196,90 +1430,266 @@ pub async fn start_monitor_loop(
                     target.last_known_state
                 );
                 state.insert(target.id.clone(), MonitorStatus::new(target.clone()));
+                changed.push(target.id.clone());
             } else if let Some(mut entry) = state.get_mut(&target.id) {
+                if hash_target(&entry.value().target) != hash_target(target) {
+                    changed.push(target.id.clone());
+                }
                 entry.value_mut().target = target.clone();
             }
         }
-    }
+        // 增量推送不含目标属性，新增或修改的目标推送一次完整状态
+        if live.receiver_count() > 0 {
+            for id in &changed {
+                if let Some(entry) = state.get(id) {
+                    let snapshot = Arc::new(entry.value().clone());
+                    live.send(LiveUpdate::Snapshot(snapshot));
+                }
+            }
+        }
 
-    loop {
-        // 1. 执行探测
-        let mut handles = vec![];
-        let retention_days = config_rx.borrow().data_retention_days; // 获取 retention
-
-        for target in targets.clone() {
-            let state_clone = state.clone();
-            let tx_clone = tx.clone();
-            let alert_config = config_rx.borrow().alert.clone();
-            let broadcast_tx_clone = broadcast_tx.clone();
-
-            handles.push(tokio::spawn(async move {
-                probe_target(
-                    &state_clone,
-                    target,
-                    tx_clone,
-                    &alert_config,
-                    retention_days,
-                    broadcast_tx_clone,
-                )
-                .await;
-            }));
+        // 停止已删除、已停用或属性已变更的目标任务 (last_known_state 不参与 hash，回写状态不会触发重建)
+        let before = tasks.len();
+        tasks.retain(|id, (hash, handle)| {
+            let keep = targets
+                .iter()
+                .any(|t| &t.id == id && t.enabled && hash_target(t) == *hash);
+            if !keep {
+                handle.abort();
+            }
+            keep
+        });
+        let stopped = before - tasks.len();
+        schedule.retain(|id, _| tasks.contains_key(id));
+
+        let mut started = 0;
+        for target in targets {
+            if !target.enabled || tasks.contains_key(&target.id) {
+                continue;
+            }
+            let hash = hash_target(&target);
+            let id = target.id.clone();
+            let handle = tokio::spawn(target_task(
+                target,
+                state.clone(),
+                tx.clone(),
+                config_rx.clone(),
+                live.clone(),
+                report_tx.clone(),
+                schedule.clone(),
+            ));
+            tasks.insert(id, (hash, handle));
+            started += 1;
+        }
+        if stopped > 0 || started > 0 {
+            tracing::info!(
+                "Monitors reloaded: {} started, {} stopped, {} running",
+                started,
+                stopped,
+                tasks.len()
+            );
         }
 
-        // 等待所有探测完成
-        for handle in handles {
-            let _ = handle.await;
+        if config_rx.changed().await.is_err() {
+            break;
         }
+    }
+}
 
-        // 2. 休眠或等待配置变更
-        let reload = tokio::select! {
-            _ = sleep(Duration::from_secs(10)) => {
-                false
-            },
-            res = config_rx.changed() => {
-                res.is_ok()
+// 单个目标的探测循环：每次探测使用最新的配置 (alert / http / retention / 间隔)
+async fn target_task(
+    target: Target,
+    state: Arc<DashMap<String, MonitorStatus>>,
+    tx: mpsc::Sender<MonitorEvent>,
+    config_rx: watch::Receiver<AppConfig>,
+    live: Arc<LiveHub>,
+    report_tx: mpsc::Sender<(Sink, ReportPoint)>,
+    schedule: Arc<DashMap<String, ScheduleInfo>>,
+) {
+    let id = target.id.clone();
+    let window = target
+        .active_cron
+        .as_deref()
+        .filter(|expr| !expr.trim().is_empty())
+        .and_then(|expr| match Cron::new(expr).parse() {
+            Ok(cron) => Some(cron),
+            Err(e) => {
+                tracing::warn!(
+                    "Invalid active_cron for {}: {}, probing continuously",
+                    target.name,
+                    e
+                );
+                None
             }
-        };
+        });
 
-        if reload {
-            let new_config = config_rx.borrow_and_update();
-            let new_targets_hash = hash_targets(&new_config.targets);
-
-            // 只有当 targets 列表的关键属性发生变化时才重载
-            if new_targets_hash != current_targets_hash {
-                tracing::info!("Configuration changed, reloading monitors...");
-                current_targets_hash = new_targets_hash;
-                targets = new_config.targets.clone();
+    // 按目标 ID 错开首次探测的相位，避免所有目标在同一时刻集中发起探测
+    let offset = {
+        let config = config_rx.borrow();
+        if config.stagger_probes {
+            stagger_offset(&id, config.interval_for(&target))
+        } else {
+            Duration::ZERO
+        }
+    };
+    if !offset.is_zero() {
+        schedule_entry(&schedule, &id).next_probe = chrono::Duration::from_std(offset)
+            .ok()
+            .map(|d| Local::now() + d);
+        sleep(offset).await;
+    }
 
-                // Sync DashMap
-                let new_ids: std::collections::HashSet<String> =
-                    targets.iter().map(|t| t.id.clone()).collect();
-                state.retain(|k, _| new_ids.contains(k));
-                for target in &targets {
-                    if !state.contains_key(&target.id) {
-                        state.insert(target.id.clone(), MonitorStatus::new(target.clone()));
-                    } else if let Some(mut entry) = state.get_mut(&target.id) {
-                        entry.value_mut().target = target.clone();
-                    }
+    loop {
+        // 时间窗之外不探测，既不产生记录也不触发告警
+        if let Some(cron) = &window {
+            let now = Local::now();
+            if !in_window(cron, now) {
+                let next = cron.find_next_occurrence(&now, false).ok();
+                {
+                    let mut info = schedule_entry(&schedule, &id);
+                    info.target_name = target.name.clone();
+                    info.running = false;
+                    info.outside_window = true;
+                    info.next_probe = next;
                 }
-            } else {
-                // 如果只是状态变更（例如持久化任务写回了 last_known_state），
-                // 或者是 retention days 变更，我们不需要完全重载 targets，
-                // 但我们可能需要更新 loop 中的 retention days 变量（下一轮循环会自动获取）。
-                // 所以这里什么都不做，或者只更新 targets 变量以防万一。
-                targets = new_config.targets.clone();
+                let wait = next
+                    .and_then(|n| (n - now).to_std().ok())
+                    .unwrap_or(Duration::from_secs(60));
+                sleep(wait).await;
+                continue;
             }
         }
+
+        let config = config_rx.borrow().clone();
+        {
+            let mut info = schedule_entry(&schedule, &id);
+            info.running = true;
+            info.outside_window = false;
+        }
+        let start = Instant::now();
+
+        probe_target(
+            &state,
+            target.clone(),
+            tx.clone(),
+            &config,
+            live.clone(),
+            report_tx.clone(),
+        )
+        .await;
+
+        // 探测耗时超过间隔时立即开始下一次
+        let next = start + config.interval_for(&target);
+        if let Some(mut info) = schedule.get_mut(&id) {
+            info.target_name = target.name.clone();
+            info.running = false;
+            info.last_probe = Some(Local::now());
+            info.last_duration_ms = Some(start.elapsed().as_micros() as f32 / 1000.0);
+            info.next_probe =
+                chrono::Duration::from_std(next.saturating_duration_since(Instant::now()))
+                    .ok()
+                    .map(|d| Local::now() + d);
+        }
+        sleep_until(next).await;
     }
 }
 
-// 简单的 hash 函数，用于比较 targets 是否实质性变更
-fn hash_targets(targets: &[Target]) -> u64 {
+fn stagger_offset(id: &str, interval: Duration) -> Duration {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
-    targets.hash(&mut hasher); // Target now implements Hash (ignoring last_known_state)
+    id.hash(&mut hasher);
+    let millis = interval.as_millis() as u64;
+    Duration::from_millis(hasher.finish() % millis.max(1))
+}
+
+fn schedule_entry<'a>(
+    schedule: &'a DashMap<String, ScheduleInfo>,
+    id: &str,
+) -> dashmap::mapref::one::RefMut<'a, String, ScheduleInfo> {
+    schedule
+        .entry(id.to_string())
+        .or_insert_with(|| ScheduleInfo {
+            target_id: id.to_string(),
+            worker: LOCAL_WORKER.to_string(),
+            ..Default::default()
+        })
+}
+
+// cron 表达式按分钟粒度匹配 (本地时间)
+pub fn in_window(cron: &Cron, now: chrono::DateTime<Local>) -> bool {
+    let minute = now
+        .with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now);
+    cron.is_time_matching(&minute).unwrap_or(true)
+}
+
+fn spawn_alert(
+    target: &Target,
+    config: &AppConfig,
+    state: TargetState,
+    message: Option<String>,
+    context: AlertContext,
+) {
+    let alert_config = config.alert_for(target).clone();
+    if !alert_config.enabled {
+        return;
+    }
+    if config.maintenance.is_active(Local::now()) {
+        tracing::info!("Alert suppressed for {}: maintenance mode", target.name);
+        return;
+    }
+    if config
+        .grace_until(target)
+        .is_some_and(|end| Local::now() < end)
+    {
+        tracing::info!("Alert suppressed for {}: warm-up grace period", target.name);
+        return;
+    }
+    let target = target.clone();
+    tokio::spawn(async move {
+        let _ =
+            crate::alert::send_alert(&target, state, &alert_config, message.as_deref(), &context)
+                .await;
+    });
+}
+
+// 告警中附带的最近一次延迟与故障开始时间
+fn alert_context(status: &MonitorStatus) -> AlertContext {
+    AlertContext {
+        latency_ms: status.records.front().and_then(|r| r.latency_ms),
+        incident_started: status.incident.as_ref().map(|i| i.started),
+    }
+}
+
+// 当前故障已被确认，恢复前不再重复告警
+fn acknowledged(status: &MonitorStatus) -> bool {
+    status
+        .incident
+        .as_ref()
+        .is_some_and(|i| i.acknowledged.is_some())
+}
+
+fn record_transition(target: &Target, from: TargetState, to: TargetState, message: Option<String>) {
+    crate::events::record(crate::events::StateEvent {
+        timestamp: Local::now(),
+        target_id: target.id.clone(),
+        target_name: target.name.clone(),
+        from,
+        to,
+        message,
+    });
+}
+
+// 简单的 hash 函数，用于比较 target 是否实质性变更
+fn hash_target(target: &Target) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    target.hash(&mut hasher); // Target implements Hash (ignoring last_known_state)
     hasher.finish()
 }
 
@@ -287,19 +1697,94 @@ async fn probe_target(
     state: &Arc<DashMap<String, MonitorStatus>>,
     target: Target,
     tx: mpsc::Sender<MonitorEvent>,
-    alert_config: &crate::model::AlertConfig,
-    retention_days: u64,
-    broadcast_tx: broadcast::Sender<String>,
+    config: &AppConfig,
+    live: Arc<LiveHub>,
+    report_tx: mpsc::Sender<(Sink, ReportPoint)>,
 ) {
     // 根据协议选择 Probe
     let probe_impl: Box<dyn Probe + Send + Sync> = match target.protocol {
-        Protocol::Tcp => Box::new(TcpProbe),
+        Protocol::Tcp => match target.tcp_mode {
+            TcpMode::Connect => Box::new(TcpProbe),
+            TcpMode::Syn => Box::new(TcpSynProbe),
+        },
         Protocol::Icmp => Box::new(IcmpProbe),
         Protocol::Dns => Box::new(DnsProbe),
-        Protocol::Http | Protocol::Https => Box::new(HttpProbe),
+        Protocol::Http | Protocol::Https => Box::new(HttpProbe {
+            http: config.http.clone(),
+        }),
+        Protocol::Vnc => Box::new(VncProbe),
+        Protocol::Smb => Box::new(SmbProbe),
+        Protocol::Nfs => Box::new(NfsProbe),
+        Protocol::Exec => Box::new(ExecProbe),
+        Protocol::Domain => Box::new(DomainProbe),
+        Protocol::Dnsbl => Box::new(DnsblProbe),
+        Protocol::Watchdog => Box::new(WatchdogProbe {
+            alert: config.alert_for(&target).clone(),
+        }),
+        Protocol::Push => Box::new(PushProbe {
+            period: target
+                .push_period_secs
+                .map(|s| Duration::from_secs(s.clamp(1, crate::push::MAX_PERIOD_SECS)))
+                .unwrap_or_else(|| config.interval_for(&target)),
+        }),
     };
 
-    let (success, latency, message) = probe_impl.probe(&target).await;
+    // 未单独设置 bind 的 Target 使用全局出口；全局出口为网卡名时，
+    // 不支持按网卡绑定的探测 (HTTP/DNS) 仍走默认出口，而不是全部失败
+    let mut effective = target.clone();
+    if effective.bind.is_none() {
+        effective.bind = config.bind.clone().filter(|bind| {
+            matches!(parse_bind(Some(bind)), Some(BindSpec::Addr(_)))
+                || supports_interface_bind(&target.protocol)
+        });
+    }
+
+    let mut result = probe_impl.probe(&effective).await;
+
+    // 失败时短暂等待后立即重试一次，以重试结果为准，过滤偶发丢包。
+    // 等待时间不超过探测间隔，否则过大的配置值会让该目标的调度长期停滞
+    let mut first_attempt_error = None;
+    if let Some(delay) = target.retry_delay_ms.filter(|_| !result.success) {
+        sleep(Duration::from_millis(delay).min(config.interval_for(&target))).await;
+        first_attempt_error = Some(result.message.unwrap_or_else(|| "Probe failed".to_string()));
+        result = probe_impl.probe(&effective).await;
+    }
+
+    let ProbeResult {
+        mut success,
+        latency_ms: latency,
+        mut message,
+        reuse_latency_ms,
+        metrics,
+        content_hash,
+        timing,
+        status_code,
+        response_bytes,
+        ttl,
+        hops,
+        mut degraded,
+    } = result;
+
+    // 探测成功但延迟超过阈值同样视为降级
+    if let (Some(limit), Some(latency)) = (target.degraded_latency_ms, latency) {
+        if success && degraded.is_none() && latency > limit as f32 {
+            degraded = Some(format!("Latency {:.1}ms > {}ms", latency, limit));
+        }
+    }
+
+    // 父目标已 DOWN (或最近一次探测已失败) 时，本目标的故障视为依赖导致的不可达。
+    // 须在取得本目标的可变引用之前读取，避免 DashMap 同分片死锁。
+    let parent = target
+        .parent
+        .as_deref()
+        .filter(|p| *p != target.id)
+        .and_then(|p| state.get(p));
+    let has_parent = parent.is_some();
+    let parent_down = parent.is_some_and(|p| {
+        p.current_state == TargetState::Down
+            || p.unreachable
+            || p.records.front().is_some_and(|r| !r.success)
+    });
 
     if let Some(mut entry) = state.get_mut(&target.id) {
         let status = entry.value_mut();
@@ -317,109 +1802,259 @@ async fn probe_target(
         // 这里我们在 push record 之前判断。
 
         let is_first_record = status.records.is_empty();
+        let previous_probe = status.records.front().map(|r| r.timestamp);
+
+        // 内容监控：与最近一次成功取得的哈希比较，不同则本次记为失败并立即告警
+        let mut content_changed = false;
+        if let Some(hash) = content_hash.as_ref() {
+            let previous = status.records.iter().find_map(|r| r.content_hash.as_ref());
+            if let Some(previous) = previous.filter(|p| *p != hash) {
+                content_changed = true;
+                success = false;
+                degraded = None;
+                message = Some(format!(
+                    "Content changed: {} -> {}",
+                    &previous[..12.min(previous.len())],
+                    &hash[..12.min(hash.len())]
+                ));
+            }
+        }
 
         let record = ProbeRecord {
             timestamp: Local::now(),
             latency_ms: latency,
             success,
             message: message.clone(),
+            reuse_latency_ms,
+            metrics,
+            content_hash,
+            timing,
+            status_code,
+            response_bytes,
+            ttl,
+            hops,
+            first_attempt_error,
+            maintenance: config.maintenance.is_active(Local::now()),
+            degraded: degraded.clone(),
+            rollup: None,
         };
+        let observed = record.state();
 
-        status.records.push_front(record);
+        // 逐点上报 (不阻塞探测，队列满时直接丢弃)
+        let url = target.report_url.as_ref().filter(|u| !u.is_empty());
+        let sinks = url
+            .map(|u| Sink::Url(u.clone()))
+            .into_iter()
+            .chain(config.influx.as_ref().map(|_| Sink::Influx));
+        for sink in sinks {
+            let point = ReportPoint::new(&target, &record);
+            if report_tx.try_send((sink, point)).is_err() {
+                tracing::warn!("Report channel full, dropping point for {}", target.name);
+            }
+        }
+        if let Some(path) = crate::probe_log::path() {
+            crate::probe_log::append(path, &ReportPoint::new(&target, &record));
+        }
 
-        // 计算 limit: days * 24h * 60m * 6 (10s interval)
-        // 10s interval is hardcoded in loop currently.
-        let limit = retention_days * 24 * 3600 / 10;
-        let limit = if limit == 0 { 60 } else { limit as usize }; // 至少保留一点
+        crate::stats::accumulate(
+            &mut status.totals,
+            &status.records,
+            record.timestamp,
+            config.interval_for(&target),
+        );
+        status.records.push_front(record);
 
-        if status.records.len() > limit {
-            status.records.pop_back();
-        }
+        status.records.truncate(config.record_limit(&target));
+        status.uptime_24h = crate::stats::uptime(
+            &status.records,
+            chrono::Duration::hours(24),
+            Local::now(),
+            config.interval_for(&target),
+        )
+        .uptime_pct;
 
-        // 防抖动逻辑：连续失败/成功 3 次才切换状态
-        // 但是这里我们只存储了 ProbeRecord，每次 probe 只产生一个 record。
-        // 我们需要检查最近 3 次记录。
-        // 如果当前状态是 UP (true)，我们需要连续 3 次失败 (false) 才切换为 DOWN。
-        // 如果当前状态是 DOWN (false)，我们需要连续 3 次成功 (true) 才切换为 UP。
+        // 防抖动逻辑：连续 N 次观察到更差/更好的状态才切换 (默认 3 次，两个方向可分别配置)
+        // 三种状态按 DOWN < DEGRADED < UP 排序：
+        // 当前为 UP，最近 N 次均为 DEGRADED/DOWN 时切换为其中最好的 (全部 DOWN 才直接切到 DOWN)；
+        // 当前为 DOWN，最近 N 次均为 DEGRADED/UP 时切换为其中最差的。
 
-        let check_count = if target.threshold == 0 {
-            1
-        } else {
-            target.threshold as usize
-        };
-        let mut should_switch = false;
+        let current = status.current_state;
+        let check_count = config.confirmations(&target, observed > current);
+        let mut next_state = None;
 
         if is_first_record {
             // 首次探测特殊处理：
             // 1. 如果 last_known_state 为 None，我们需要确立初始状态并持久化（无论成功失败）。
             // 2. 如果探测结果与当前默认状态不一致，需要修正并持久化。
-            if target.last_known_state.is_none() || status.current_state != success {
+            if target.last_known_state.is_none() || current != observed {
                 tracing::info!(
                     "Initial state confirmed for {}: {}",
                     target.name,
-                    if success { "UP" } else { "DOWN" }
+                    observed.label()
                 );
-                status.current_state = success;
+                status.current_state = observed;
+                // 程序停止期间发生的变化同样记入事件日志
+                if target.last_known_state.is_some() {
+                    let reason = match observed {
+                        TargetState::Degraded => degraded.clone(),
+                        _ => message.clone(),
+                    };
+                    record_transition(&target, current, observed, reason);
+                }
                 let _ = tx
-                    .send(MonitorEvent::StateChanged(target.id.clone(), success))
+                    .send(MonitorEvent::StateChanged(target.id.clone(), observed))
                     .await;
             }
+        } else if content_changed {
+            // 哈希变化本身已是确定事件，不需要连续多次确认
+            if current != TargetState::Down {
+                next_state = Some(TargetState::Down);
+            }
         } else if status.records.len() >= check_count {
-            let recent_records: Vec<bool> = status
+            let recent: Vec<TargetState> = status
                 .records
                 .iter()
                 .take(check_count)
-                .map(|r| r.success)
+                .map(|r| r.state())
                 .collect();
-            if recent_records.len() == check_count {
-                // 如果所有最近记录都与当前状态相反，则切换
-                // 比如当前是 true，最近是 [false, false, false]，则切换。
-                if recent_records.iter().all(|&s| s != status.current_state) {
-                    should_switch = true;
-                }
-            }
-        } else {
-            // 初始阶段，数据不足 3 次，如果状态不一致，直接切换（快速启动）
-            if status.current_state != success {
-                should_switch = true;
+            if recent.iter().all(|&s| s < current) {
+                next_state = recent.iter().max().copied();
+            } else if recent.iter().all(|&s| s > current) {
+                next_state = recent.iter().min().copied();
             }
+        } else if current != observed {
+            // 初始阶段，数据不足 N 次，如果状态不一致，直接切换（快速启动）
+            next_state = Some(observed);
         }
 
-        if should_switch {
+        if let Some(new_state) = next_state {
             tracing::info!(
                 "State changed for {}: {} -> {}",
                 target.name,
-                status.current_state,
-                !status.current_state
+                current.label(),
+                new_state.label()
             );
-            status.current_state = !status.current_state;
-
-            // 1. 发送 Webhook
-            let target_clone = target.clone();
-            let alert_config_clone = alert_config.clone();
-            let message_clone = message.clone();
-
-            if alert_config_clone.enabled {
-                tokio::spawn(async move {
-                    let _ = crate::alert::send_alert(
-                        &target_clone,
-                        success,
-                        &alert_config_clone,
-                        message_clone.as_deref(),
-                    )
-                    .await;
+            status.current_state = new_state;
+            let previous_change = status.last_change.replace(Local::now());
+            let reason = match new_state {
+                TargetState::Degraded => degraded.clone(),
+                _ => message.clone(),
+            };
+            record_transition(&target, current, new_state, reason);
+
+            // 1. 发送 Webhook (父目标故障导致的连带故障及其恢复不告警)
+            if new_state == TargetState::Down && has_parent {
+                // 父目标可能尚未探测到同一次故障，先按不可达处理，
+                // 下一次探测时父目标仍正常才补发 DOWN 告警
+                status.unreachable = true;
+                if parent_down {
+                    tracing::info!(
+                        "Alert suppressed for {}: unreachable (dependency down)",
+                        target.name
+                    );
+                } else {
+                    tracing::info!(
+                        "DOWN alert for {} deferred until dependency is rechecked",
+                        target.name
+                    );
+                }
+            } else if new_state != TargetState::Down && status.unreachable {
+                status.unreachable = false;
+                tracing::info!(
+                    "Recovery alert suppressed for {}: DOWN alert was suppressed",
+                    target.name
+                );
+            } else if new_state != TargetState::Up && acknowledged(status) {
+                tracing::info!(
+                    "Alert suppressed for {}: incident acknowledged",
+                    target.name
+                );
+            } else {
+                // DEGRADED 告警附带降级原因，其余沿用探测消息
+                let detail = match new_state {
+                    TargetState::Degraded => degraded.clone(),
+                    _ => message.clone(),
+                };
+                // 故障在本次探测后才开始记录：刚离开 UP 时以本次切换作为故障开始；
+                // 恢复时没有故障记录 (如旧版本的缓存) 则以离开 UP 的那次切换为准
+                let mut context = alert_context(status);
+                context.incident_started = context.incident_started.or(match current {
+                    TargetState::Up => status.last_change,
+                    _ if new_state == TargetState::Up => previous_change,
+                    _ => None,
                 });
+                spawn_alert(&target, config, new_state, detail, context);
             }
 
             // 2. 触发持久化
             let _ = tx
-                .send(MonitorEvent::StateChanged(target.id.clone(), success))
+                .send(MonitorEvent::StateChanged(target.id.clone(), new_state))
                 .await;
+        } else if status.unreachable && status.current_state == TargetState::Down && !parent_down {
+            // 父目标正常 (或已恢复) 而本目标仍然 DOWN：不是连带故障，补发告警
+            status.unreachable = false;
+            if acknowledged(status) {
+                tracing::info!(
+                    "Alert suppressed for {}: incident acknowledged",
+                    target.name
+                );
+            } else {
+                let detail = format!(
+                    "Down while dependency is up: {}",
+                    message.as_deref().unwrap_or("probe failed")
+                );
+                spawn_alert(
+                    &target,
+                    config,
+                    TargetState::Down,
+                    Some(detail),
+                    alert_context(status),
+                );
+            }
         }
 
-        // Broadcast update
-        let status_json = serde_json::to_string(entry.value()).unwrap_or_default();
-        let _ = broadcast_tx.send(status_json);
+        // 宽限期在本次探测前刚结束而目标仍然 DOWN：补发一次期间被抑制的告警
+        if let Some(grace_end) = config.grace_until(&target) {
+            let crossed =
+                previous_probe.is_some_and(|p| p < grace_end) && Local::now() >= grace_end;
+            if crossed
+                && status.current_state == TargetState::Down
+                && !status.unreachable
+                && !acknowledged(status)
+            {
+                let detail = format!(
+                    "Still down after grace period: {}",
+                    message.as_deref().unwrap_or("probe failed")
+                );
+                spawn_alert(
+                    &target,
+                    config,
+                    TargetState::Down,
+                    Some(detail),
+                    alert_context(status),
+                );
+            }
+        }
+
+        // 离开 UP 时开始一次故障，恢复 UP 时结束
+        match (status.current_state, &status.incident) {
+            (TargetState::Up, Some(_)) => status.incident = None,
+            (TargetState::Up, None) | (_, Some(_)) => {}
+            (_, None) => status.incident = Some(OngoingIncident::open()),
+        }
+
+        // 只推送本次探测的增量，完整记录在连接建立时随 init 下发
+        if let Some(record) = status.records.front() {
+            live.send(LiveUpdate::Probe(Arc::new(StatusUpdate {
+                target_id: target.id.clone(),
+                record: record.clone(),
+                state: status.current_state,
+                previous_state: (status.current_state != current).then_some(current),
+                unreachable: status.unreachable,
+                uptime_24h: status.uptime_24h,
+                incident: status.incident.clone(),
+            })));
+        }
     }
 }
 
@@ -435,6 +2070,7 @@ pub async fn config_persistence_task(
         }
 
         // 获取当前完整配置快照
+        let _guard = config::UPDATE_LOCK.lock().await;
         let mut current_config = config_watch.borrow().clone();
 
         // 更新 targets 中的状态
@@ -445,7 +2081,7 @@ pub async fn config_persistence_task(
             }
         }
 
-        // 保存
+        // 保存 (按 persistence_failure 策略，写入失败时可能降级为仅内存)
         if let Err(e) = config::save_config(&current_config) {
             tracing::error!("Failed to save config with new state: {}", e);
         } else {
@@ -455,7 +2091,11 @@ pub async fn config_persistence_task(
             // 我们已经在 loop 中处理了 hash 比较，所以这里触发是安全的。
             let _ = config_watch.send(current_config);
 
-            tracing::info!("Config saved with updated state for target {}", id);
+            if config::is_degraded() {
+                tracing::debug!("State for target {} kept in memory only", id);
+            } else {
+                tracing::info!("Config saved with updated state for target {}", id);
+            }
         }
     }
 }
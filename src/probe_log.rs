@@ -0,0 +1,90 @@
+use crate::report::ReportPoint;
+use once_cell::sync::{Lazy, OnceCell};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Pending {
+    path: Option<String>,
+    buf: Vec<u8>,
+}
+
+static PENDING: Lazy<Mutex<Pending>> = Lazy::new(|| Mutex::new(Pending::default()));
+
+// 只在启动时从命令行 `--probe-log` 或环境变量 `NETWATCH_PROBE_LOG` 读取，不属于可通过
+// API 修改的配置，否则编辑者可以让服务向任意可写的文件追加内容
+static PATH: OnceCell<String> = OnceCell::new();
+
+/// 设置探测日志路径，命令行参数优先于环境变量，都未设置时不写探测日志
+pub fn init(cli_path: Option<String>) {
+    let path = cli_path
+        .or_else(|| std::env::var("NETWATCH_PROBE_LOG").ok())
+        .filter(|p| !p.is_empty());
+    if let Some(path) = path {
+        tracing::info!("Appending probe results to {}", path);
+        let _ = PATH.set(path);
+    }
+}
+
+pub fn path() -> Option<&'static str> {
+    PATH.get().map(String::as_str)
+}
+
+/// 把一条探测结果追加到内存缓冲，由 `flush_task` 每秒写入文件。
+/// 非正常退出时最多丢失约一秒的数据。
+pub fn append(path: &str, point: &ReportPoint) {
+    let Ok(mut line) = serde_json::to_vec(point) else {
+        return;
+    };
+    line.push(b'\n');
+    let mut pending = PENDING.lock().unwrap();
+    if pending.path.as_deref() != Some(path) {
+        // 路径变更前先把旧路径的缓冲写出
+        flush_locked(&mut pending);
+        pending.path = Some(path.to_string());
+    }
+    pending.buf.extend_from_slice(&line);
+}
+
+pub async fn flush_task() {
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let _ = tokio::task::spawn_blocking(flush).await;
+    }
+}
+
+/// 立即写出缓冲 (退出前调用)
+pub fn flush() {
+    flush_locked(&mut PENDING.lock().unwrap());
+}
+
+// 每次写入都重新以追加模式打开文件，外部工具轮转 (mv + 新建) 后自动写入新文件
+fn flush_locked(pending: &mut Pending) {
+    if pending.buf.is_empty() {
+        return;
+    }
+    let Some(path) = pending.path.as_deref() else {
+        pending.buf.clear();
+        return;
+    };
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&pending.buf));
+    match result {
+        Ok(()) => pending.buf.clear(),
+        Err(e) => {
+            tracing::error!("Failed to write probe log {}: {}", path, e);
+            // 持续失败时只保留最近的数据，避免无限增长
+            if pending.buf.len() > 16 * 1024 * 1024 {
+                pending.buf.clear();
+            }
+        }
+    }
+}
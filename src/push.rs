@@ -0,0 +1,65 @@
+use crate::model::{AppConfig, Protocol, Target};
+use chrono::{DateTime, Local};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+// 每个 PUSH 目标最近一次收到的心跳 (按目标 id)，仅保存在内存中
+static LAST_PUSH: Lazy<DashMap<String, Push>> = Lazy::new(DashMap::new);
+// 进程启动后的首个心跳周期内尚未收到心跳不判定为 DOWN
+static STARTED: Lazy<DateTime<Local>> = Lazy::new(Local::now);
+
+/// 被监控方通过 `/api/push/:token` 上报的一次心跳
+#[derive(Debug, Clone)]
+pub struct Push {
+    pub received: DateTime<Local>,
+    // 上报 `status=down` 时为 false
+    pub success: bool,
+    pub message: Option<String>,
+    pub latency_ms: Option<f32>,
+}
+
+pub fn init() {
+    Lazy::force(&STARTED);
+}
+
+pub fn record(target_id: &str, push: Push) {
+    LAST_PUSH.insert(target_id.to_string(), push);
+}
+
+pub fn last(target_id: &str) -> Option<Push> {
+    LAST_PUSH.get(target_id).map(|p| p.value().clone())
+}
+
+/// 心跳周期 (`push_period_secs`) 上限 (90 天)，更大的值按上限处理
+pub const MAX_PERIOD_SECS: u64 = 90 * 24 * 3600;
+
+/// 等待首个心跳的起点：进程启动与目标添加中较晚的一个
+pub fn waiting_since(target: &Target) -> DateTime<Local> {
+    target.created_at.map_or(*STARTED, |c| c.max(*STARTED))
+}
+
+/// 按令牌查找启用的 PUSH 目标
+pub fn find_target<'a>(targets: &'a [Target], token: &str) -> Option<&'a Target> {
+    targets.iter().find(|t| {
+        t.enabled && t.protocol == Protocol::Push && t.push_token.as_deref() == Some(token)
+    })
+}
+
+/// PUSH 目标未设置令牌时生成一个，已有令牌保持不变；返回是否生成了新令牌
+pub fn ensure_token(target: &mut Target) -> bool {
+    let missing =
+        target.protocol == Protocol::Push && target.push_token.as_deref().is_none_or(str::is_empty);
+    if missing {
+        target.push_token = Some(uuid::Uuid::new_v4().simple().to_string());
+    }
+    missing
+}
+
+/// 为配置中所有缺少令牌的 PUSH 目标生成令牌，返回是否有改动
+pub fn ensure_tokens(config: &mut AppConfig) -> bool {
+    config
+        .targets
+        .iter_mut()
+        .map(ensure_token)
+        .fold(false, |changed, generated| changed | generated)
+}
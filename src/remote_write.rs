@@ -0,0 +1,169 @@
+use crate::model::{AppConfig, MonitorStatus, RemoteWriteConfig, TargetState};
+use chrono::Local;
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// 按配置的间隔把每个目标的确认状态与最近一次延迟推送到 remote_write 端点。
+/// 推送失败只记录日志，下一轮推送最新值。
+pub async fn remote_write_task(
+    state: Arc<DashMap<String, MonitorStatus>>,
+    config_rx: watch::Receiver<AppConfig>,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to build remote_write client");
+    loop {
+        let Some(remote) = config_rx.borrow().remote_write.clone() else {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            continue;
+        };
+        tokio::time::sleep(Duration::from_secs(remote.interval_secs.max(5))).await;
+
+        let series = collect_series(&state, &remote);
+        if series.is_empty() {
+            continue;
+        }
+        match push(&client, &remote, &series).await {
+            Ok(()) => tracing::debug!("Pushed {} series to {}", series.len(), remote.url),
+            Err(e) => tracing::error!("remote_write to {} failed: {}", remote.url, e),
+        }
+    }
+}
+
+struct Series {
+    labels: BTreeMap<String, String>,
+    value: f64,
+    timestamp_ms: i64,
+}
+
+/// 每个启用的目标两条序列：`netwatch_up` (确认状态不为 DOWN 时为 1) 与 `netwatch_latency_ms`
+fn collect_series(
+    state: &DashMap<String, MonitorStatus>,
+    remote: &RemoteWriteConfig,
+) -> Vec<Series> {
+    let now = Local::now().timestamp_millis();
+    let mut series = Vec::new();
+    for entry in state.iter() {
+        let status = entry.value();
+        let target = &status.target;
+        if !target.enabled {
+            continue;
+        }
+        let mut labels = remote.labels.clone();
+        labels.insert("target".to_string(), target.name.clone());
+        labels.insert("target_id".to_string(), target.id.clone());
+        labels.insert(
+            "protocol".to_string(),
+            format!("{:?}", target.protocol).to_uppercase(),
+        );
+        labels.insert("host".to_string(), target.host.clone());
+        if let Some(group) = &target.group {
+            labels.insert("group".to_string(), group.clone());
+        }
+
+        let metric = |name: &str, value: f64| {
+            let mut labels = labels.clone();
+            labels.insert("__name__".to_string(), name.to_string());
+            Series {
+                labels,
+                value,
+                timestamp_ms: now,
+            }
+        };
+        let up = status.current_state != TargetState::Down;
+        series.push(metric("netwatch_up", if up { 1.0 } else { 0.0 }));
+        if let Some(latency) = status.records.front().and_then(|r| r.latency_ms) {
+            series.push(metric("netwatch_latency_ms", latency as f64));
+        }
+    }
+    series
+}
+
+async fn push(
+    client: &reqwest::Client,
+    remote: &RemoteWriteConfig,
+    series: &[Series],
+) -> anyhow::Result<()> {
+    let body = snappy_literal(&encode_write_request(series));
+    let mut request = client
+        .post(&remote.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+        .header(reqwest::header::CONTENT_ENCODING, "snappy")
+        .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+        .body(body);
+    if let Some(token) = remote.bearer_token.as_deref().filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let res = request.send().await?;
+    if !res.status().is_success() {
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        anyhow::bail!("HTTP {}: {}", status, text.trim());
+    }
+    Ok(())
+}
+
+// prometheus.WriteRequest 的 protobuf 编码：
+// WriteRequest { repeated TimeSeries timeseries = 1; }
+// TimeSeries { repeated Label labels = 1; repeated Sample samples = 2; }
+// Label { string name = 1; string value = 2; }
+// Sample { double value = 1; int64 timestamp = 2; }
+fn encode_write_request(series: &[Series]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for s in series {
+        let mut ts = Vec::new();
+        // 标签须按名称排序，BTreeMap 已保证
+        for (name, value) in &s.labels {
+            let mut label = Vec::new();
+            put_bytes(&mut label, 1, name.as_bytes());
+            put_bytes(&mut label, 2, value.as_bytes());
+            put_bytes(&mut ts, 1, &label);
+        }
+        let mut sample = Vec::new();
+        sample.push(1 << 3 | 1); // field 1, fixed64
+        sample.extend_from_slice(&s.value.to_le_bytes());
+        sample.push(2 << 3); // field 2, varint
+        put_varint(&mut sample, s.timestamp_ms as u64);
+        put_bytes(&mut ts, 2, &sample);
+        put_bytes(&mut out, 1, &ts);
+    }
+    out
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u32, data: &[u8]) {
+    put_varint(buf, (field << 3 | 2) as u64);
+    put_varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+/// snappy block 格式，只使用 literal 块 (不压缩)。数据量很小，换取不引入压缩依赖
+fn snappy_literal(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 60 + 8);
+    put_varint(&mut out, data.len() as u64);
+    for chunk in data.chunks(65536) {
+        let n = chunk.len() - 1;
+        if n < 60 {
+            out.push((n as u8) << 2);
+        } else if n < 256 {
+            out.push(60 << 2);
+            out.push(n as u8);
+        } else {
+            out.push(61 << 2);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        out.extend_from_slice(chunk);
+    }
+    out
+}
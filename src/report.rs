@@ -0,0 +1,164 @@
+use crate::model::{AppConfig, ProbeRecord, Protocol, Target};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep, Duration};
+
+// 每个 URL 最多缓存的待发送数据点，超出后丢弃最旧的
+const MAX_PENDING: usize = 1000;
+const BATCH_SIZE: usize = 50;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: u32 = 3;
+
+/// 数据点的去向：目标的 `report_url` (JSON 数组) 或全局 InfluxDB (line protocol)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Sink {
+    Url(String),
+    Influx,
+}
+
+impl std::fmt::Display for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sink::Url(url) => f.write_str(url),
+            Sink::Influx => f.write_str("InfluxDB"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportPoint {
+    pub target_id: String,
+    pub target_name: String,
+    pub host: String,
+    pub protocol: Protocol,
+    #[serde(flatten)]
+    pub record: ProbeRecord,
+}
+
+impl ReportPoint {
+    pub fn new(target: &Target, record: &ProbeRecord) -> Self {
+        Self {
+            target_id: target.id.clone(),
+            target_name: target.name.clone(),
+            host: target.host.clone(),
+            protocol: target.protocol.clone(),
+            record: record.clone(),
+        }
+    }
+}
+
+/// 数据点上报任务：按去向聚合后批量 POST，失败时指数退避重试。
+/// 与告警通道完全独立，避免每次探测都刷屏告警。
+pub async fn report_task(
+    mut rx: mpsc::Receiver<(Sink, ReportPoint)>,
+    config_rx: watch::Receiver<AppConfig>,
+) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to build report client");
+    let mut pending: HashMap<Sink, Vec<ReportPoint>> = HashMap::new();
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            msg = rx.recv() => {
+                let Some((sink, point)) = msg else { break };
+                let queue = pending.entry(sink.clone()).or_default();
+                if queue.len() >= MAX_PENDING {
+                    queue.remove(0);
+                    tracing::warn!("Report queue full for {}, dropping oldest point", sink);
+                }
+                queue.push(point);
+                if queue.len() >= BATCH_SIZE {
+                    let batch = std::mem::take(queue);
+                    let config = config_rx.borrow().clone();
+                    tokio::spawn(deliver(client.clone(), config, sink, batch));
+                }
+            }
+            _ = ticker.tick() => {
+                let config = config_rx.borrow().clone();
+                for (sink, queue) in pending.iter_mut() {
+                    if queue.is_empty() {
+                        continue;
+                    }
+                    let batch = std::mem::take(queue);
+                    tokio::spawn(deliver(client.clone(), config.clone(), sink.clone(), batch));
+                }
+            }
+        }
+    }
+}
+
+fn build_request(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    sink: &Sink,
+    batch: &[ReportPoint],
+) -> anyhow::Result<reqwest::RequestBuilder> {
+    match sink {
+        Sink::Url(url) => Ok(client.post(url).json(batch)),
+        Sink::Influx => {
+            let influx = config
+                .influx
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("InfluxDB is no longer configured"))?;
+            let body = batch
+                .iter()
+                .map(|p| crate::influx::line(&influx.measurement, p))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let mut request = client
+                .post(crate::influx::write_url(influx)?)
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(body);
+            if let Some(token) = influx.token.as_deref().filter(|t| !t.is_empty()) {
+                request =
+                    request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+            }
+            Ok(request)
+        }
+    }
+}
+
+async fn deliver(client: reqwest::Client, config: AppConfig, sink: Sink, batch: Vec<ReportPoint>) {
+    let url = sink.to_string();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let request = match build_request(&client, &config, &sink, &batch) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::error!("Dropping {} report points for {}: {}", batch.len(), url, e);
+                return;
+            }
+        };
+        match request.send().await {
+            Ok(res) if res.status().is_success() => {
+                tracing::debug!("Reported {} points to {}", batch.len(), url);
+                return;
+            }
+            Ok(res) => {
+                tracing::warn!(
+                    "Report to {} failed with status {} (attempt {}/{})",
+                    url,
+                    res.status(),
+                    attempt,
+                    MAX_ATTEMPTS
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Report to {} failed: {} (attempt {}/{})",
+                    url,
+                    e,
+                    attempt,
+                    MAX_ATTEMPTS
+                );
+            }
+        }
+        if attempt < MAX_ATTEMPTS {
+            sleep(Duration::from_secs(1 << attempt)).await;
+        }
+    }
+    tracing::error!("Dropping {} report points for {}", batch.len(), url);
+}
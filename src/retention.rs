@@ -0,0 +1,78 @@
+use crate::cache;
+use crate::model::{AppConfig, MonitorStatus, ProbeRecord};
+use chrono::{DateTime, Local};
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 定期 (以及保留策略变更时) 删除所有目标中超出 `data_retention_days` 或 `data_retention_records`
+/// 的记录，有记录被删除时重写缓存文件，使磁盘上的历史同步缩小
+pub async fn pruning_task(
+    state: Arc<DashMap<String, MonitorStatus>>,
+    mut config_rx: watch::Receiver<AppConfig>,
+) {
+    let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+    let mut policy = retention_policy(&config_rx.borrow());
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            changed = config_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let current = retention_policy(&config_rx.borrow());
+                if current == policy {
+                    continue;
+                }
+                policy = current;
+            }
+        }
+
+        let config = config_rx.borrow().clone();
+        let keep_since = config.retention_cutoff(Local::now());
+        let removed: usize = state
+            .iter_mut()
+            .map(|mut entry| {
+                let status = entry.value_mut();
+                let limit = config.record_limit(&status.target);
+                prune(&mut status.records, keep_since, limit)
+            })
+            .sum();
+        if removed > 0 {
+            match config.data_retention_records.filter(|n| *n > 0) {
+                Some(n) => tracing::info!("Pruned {} records beyond {} per target", removed, n),
+                None => tracing::info!(
+                    "Pruned {} records older than {} days",
+                    removed,
+                    config.data_retention_days
+                ),
+            }
+            let state = state.clone();
+            let _ = tokio::task::spawn_blocking(move || cache::save_cache(&state)).await;
+        }
+    }
+}
+
+fn retention_policy(config: &AppConfig) -> (u64, Option<usize>) {
+    (config.data_retention_days, config.data_retention_records)
+}
+
+/// 删除早于 `keep_since` 以及超出 `limit` 条的记录 (记录最新在前，从尾部删除)，返回删除条数
+pub fn prune(
+    records: &mut VecDeque<ProbeRecord>,
+    keep_since: Option<DateTime<Local>>,
+    limit: usize,
+) -> usize {
+    let before = records.len();
+    if let Some(keep_since) = keep_since {
+        while records.back().is_some_and(|r| r.timestamp < keep_since) {
+            records.pop_back();
+        }
+    }
+    records.truncate(limit);
+    before - records.len()
+}
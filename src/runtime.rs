@@ -0,0 +1,103 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, Runtime};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_PROBE_THREADS: usize = 2;
+
+struct Sampler {
+    name: &'static str,
+    handle: Handle,
+    last_busy: Duration,
+    last_at: Instant,
+    utilization: f64,
+}
+
+static RUNTIMES: Lazy<Mutex<Vec<Sampler>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 单个 runtime 的负载，`utilization` 为最近采样周期内所有 worker 的平均忙碌比例 (0~1)
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeStats {
+    pub name: &'static str,
+    pub workers: usize,
+    pub alive_tasks: usize,
+    pub global_queue_depth: usize,
+    pub utilization: f64,
+}
+
+/// 探测专用的 runtime，与 Web/SSE 所在的主 runtime 隔离，
+/// 大量探测同时进行时不会拖慢页面与事件流，反之亦然。
+/// 线程数由 `NETWATCH_PROBE_THREADS` 控制。
+pub fn build_probe_runtime() -> std::io::Result<&'static Runtime> {
+    let threads = std::env::var("NETWATCH_PROBE_THREADS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n: &usize| n > 0)
+        .unwrap_or(DEFAULT_PROBE_THREADS);
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(threads)
+        .thread_name("netwatch-probe")
+        .enable_all()
+        .build()?;
+    tracing::info!("Probe runtime started with {} worker threads", threads);
+    // runtime 不能在异步上下文中 drop，随进程存活即可
+    Ok(Box::leak(Box::new(runtime)))
+}
+
+pub fn register(name: &'static str, handle: Handle) {
+    let last_busy = total_busy(&handle);
+    RUNTIMES.lock().unwrap().push(Sampler {
+        name,
+        handle,
+        last_busy,
+        last_at: Instant::now(),
+        utilization: 0.0,
+    });
+}
+
+fn total_busy(handle: &Handle) -> Duration {
+    let metrics = handle.metrics();
+    (0..metrics.num_workers())
+        .map(|w| metrics.worker_total_busy_duration(w))
+        .sum()
+}
+
+/// 定期采样各 runtime 的 worker 忙碌时间
+pub async fn sampler_task() {
+    let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        for sampler in RUNTIMES.lock().unwrap().iter_mut() {
+            let busy = total_busy(&sampler.handle);
+            let elapsed = sampler.last_at.elapsed();
+            let workers = sampler.handle.metrics().num_workers().max(1);
+            let capacity = elapsed.as_secs_f64() * workers as f64;
+            if capacity > 0.0 {
+                sampler.utilization =
+                    ((busy - sampler.last_busy).as_secs_f64() / capacity).clamp(0.0, 1.0);
+            }
+            sampler.last_busy = busy;
+            sampler.last_at = Instant::now();
+        }
+    }
+}
+
+pub fn snapshot() -> Vec<RuntimeStats> {
+    RUNTIMES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| {
+            let metrics = s.handle.metrics();
+            RuntimeStats {
+                name: s.name,
+                workers: metrics.num_workers(),
+                alive_tasks: metrics.num_alive_tasks(),
+                global_queue_depth: metrics.global_queue_depth(),
+                utilization: s.utilization,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,228 @@
+use crate::events::StateEvent;
+use crate::model::{AppConfig, MonitorStatus, TargetState};
+use crate::stats::{latency_between, uptime_between, LatencyStats};
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 一个自然月的 SLA 报告
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyReport {
+    pub year: i32,
+    pub month: u32,
+    pub from: DateTime<Local>,
+    pub to: DateTime<Local>,
+    pub generated_at: DateTime<Local>,
+    pub targets: Vec<TargetReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetReport {
+    pub target_id: String,
+    pub target_name: String,
+    pub group: Option<String>,
+    pub uptime_pct: Option<f64>,
+    pub downtime_minutes: f64,
+    pub probes: usize,
+    pub latency_ms: Option<LatencyStats>,
+    pub incidents: Vec<Incident>,
+}
+
+/// 一次故障：从离开 UP 到恢复 UP，期间最严重的状态为 `state`
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub start: DateTime<Local>,
+    // 截至报告生成时仍未恢复时为空
+    pub end: Option<DateTime<Local>>,
+    pub state: TargetState,
+    // 落在本月内的时长 (秒)
+    pub duration_secs: i64,
+    pub message: Option<String>,
+}
+
+/// 本地时间的月份起止 `[from, to)`，月份无效或年份超出范围时返回 None
+pub fn month_range(year: i32, month: u32) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let (next_year, next_month) = if month == 12 {
+        (year.checked_add(1)?, 1)
+    } else {
+        (year, month + 1)
+    };
+    let from = Local.with_ymd_and_hms(year, month, 1, 0, 0, 0).earliest()?;
+    let to = Local
+        .with_ymd_and_hms(next_year, next_month, 1, 0, 0, 0)
+        .earliest()?;
+    Some((from, to))
+}
+
+/// 生成报告。可用率与延迟来自探测记录 (受保留策略限制)，故障列表来自状态切换事件日志
+pub fn build<'a>(
+    config: &AppConfig,
+    statuses: impl IntoIterator<Item = &'a MonitorStatus>,
+    events: &[StateEvent],
+    (from, to): (DateTime<Local>, DateTime<Local>),
+    now: DateTime<Local>,
+) -> MonthlyReport {
+    let mut by_target: HashMap<&str, Vec<&StateEvent>> = HashMap::new();
+    for event in events {
+        by_target.entry(&event.target_id).or_default().push(event);
+    }
+    let until = to.min(now);
+
+    let mut targets: Vec<TargetReport> = statuses
+        .into_iter()
+        .map(|status| {
+            let target = &status.target;
+            let uptime = uptime_between(&status.records, from, until, config.interval_for(target));
+            let events = by_target
+                .get(target.id.as_str())
+                .map_or(&[][..], Vec::as_slice);
+            TargetReport {
+                target_id: target.id.clone(),
+                target_name: target.name.clone(),
+                group: target.group.clone(),
+                uptime_pct: uptime.uptime_pct,
+                downtime_minutes: uptime.downtime_minutes,
+                probes: uptime.probes,
+                latency_ms: latency_between(&status.records, from, until),
+                incidents: incidents(events, from, until),
+            }
+        })
+        .collect();
+    targets.sort_by(|a, b| (&a.group, &a.target_name).cmp(&(&b.group, &b.target_name)));
+
+    MonthlyReport {
+        year: from.year(),
+        month: from.month(),
+        from,
+        to,
+        generated_at: now,
+        targets,
+    }
+}
+
+/// 把按时间排序的状态切换事件配对为故障，只保留与 `[from, until)` 有重叠的部分
+pub fn incidents(
+    events: &[&StateEvent],
+    from: DateTime<Local>,
+    until: DateTime<Local>,
+) -> Vec<Incident> {
+    let mut result = Vec::new();
+    let mut open: Option<Incident> = None;
+    for event in events.iter().take_while(|e| e.timestamp < until) {
+        match (&mut open, event.to) {
+            (Some(incident), TargetState::Up) => {
+                incident.end = Some(event.timestamp);
+                result.extend(open.take());
+            }
+            (Some(incident), state) => incident.state = incident.state.min(state),
+            (None, TargetState::Up) => {}
+            (None, state) => {
+                open = Some(Incident {
+                    start: event.timestamp,
+                    end: None,
+                    state,
+                    duration_secs: 0,
+                    message: event.message.clone(),
+                })
+            }
+        }
+    }
+    result.extend(open);
+
+    result.retain(|i| i.end.is_none_or(|end| end > from));
+    for incident in &mut result {
+        let end = incident.end.unwrap_or(until).min(until);
+        incident.duration_secs = (end - incident.start.max(from)).num_seconds().max(0);
+    }
+    result
+}
+
+/// 可直接发送给客户的独立 HTML 页面
+pub fn render_html(report: &MonthlyReport) -> String {
+    let mut rows = String::new();
+    let mut details = String::new();
+    for t in &report.targets {
+        let latency = t.latency_ms.as_ref();
+        let ms = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1}", v));
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}</td>\
+             <td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+             <td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            escape(&t.target_name),
+            escape(t.group.as_deref().unwrap_or("")),
+            t.uptime_pct
+                .map_or("-".to_string(), |p| format!("{:.3}%", p)),
+            t.downtime_minutes,
+            t.incidents.len(),
+            ms(latency.map(|l| l.mean)),
+            ms(latency.map(|l| l.p50)),
+            ms(latency.map(|l| l.p90)),
+            ms(latency.map(|l| l.p99)),
+        ));
+        if t.incidents.is_empty() {
+            continue;
+        }
+        details.push_str(&format!(
+            "<h3>{}</h3>\n<table>\n<tr><th>Start</th><th>End</th><th>State</th>\
+             <th>Duration</th><th>Message</th></tr>\n",
+            escape(&t.target_name)
+        ));
+        for i in &t.incidents {
+            details.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>\n",
+                i.start.format("%Y-%m-%d %H:%M:%S"),
+                i.end.map_or("ongoing".to_string(), |e| e
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()),
+                i.state.label(),
+                format_duration(i.duration_secs),
+                escape(i.message.as_deref().unwrap_or("")),
+            ));
+        }
+        details.push_str("</table>\n");
+    }
+    if details.is_empty() {
+        details.push_str("<p>No incidents.</p>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>SLA Report {year}-{month:02}</title>\n<style>\n{style}</style>\n</head>\n<body>\n\
+         <h1>SLA Report {year}-{month:02}</h1>\n\
+         <p class=\"meta\">{from} – {to} · generated {generated}</p>\n\
+         <h2>Summary</h2>\n<table>\n<tr><th>Target</th><th>Group</th><th>Uptime</th>\
+         <th>Downtime (min)</th><th>Incidents</th><th>Mean (ms)</th><th>p50</th><th>p90</th>\
+         <th>p99</th></tr>\n{rows}</table>\n<h2>Incidents</h2>\n{details}</body>\n</html>\n",
+        year = report.year,
+        month = report.month,
+        style = REPORT_STYLE,
+        from = report.from.format("%Y-%m-%d"),
+        to = (report.to - chrono::Duration::days(1)).format("%Y-%m-%d"),
+        generated = report.generated_at.format("%Y-%m-%d %H:%M:%S"),
+    )
+}
+
+const REPORT_STYLE: &str =
+    "body { font-family: -apple-system, 'Segoe UI', sans-serif; margin: 32px; color: #222; }
+table { border-collapse: collapse; margin-bottom: 24px; }
+th, td { border: 1px solid #ddd; padding: 6px 10px; text-align: left; font-size: 14px; }
+th { background: #f5f5f5; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.meta { color: #666; }
+";
+
+fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,368 @@
+use crate::model::{ProbeRecord, Totals};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+// 相邻两条记录间隔超过该值 (或两倍探测间隔，取较大者) 时视为没有数据 (程序停止、探测被跳过)，不计入时长
+const MAX_GAP_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DayState {
+    Up,
+    Partial,
+    Down,
+    NoData,
+    // 当天的记录全部采集于维护期间
+    Maintenance,
+}
+
+#[derive(Default)]
+struct Tally {
+    up_secs: i64,
+    down_secs: i64,
+    ok: usize,
+    failed: usize,
+    maintenance: usize,
+    latency_sum: f64,
+    latency_n: usize,
+}
+
+impl Tally {
+    fn add(&mut self, record: &ProbeRecord, secs: i64) {
+        // 维护期间的记录不计入可用率
+        if record.maintenance {
+            self.maintenance += 1;
+            return;
+        }
+        // 降采样后的聚合记录覆盖整个时间桶，按其中成功的比例分摊时长
+        let (samples, ok) = match &record.rollup {
+            Some(rollup) => (rollup.samples as usize, rollup.ok as usize),
+            None => (1, record.success as usize),
+        };
+        let up = secs * ok as i64 / samples.max(1) as i64;
+        self.up_secs += up;
+        self.down_secs += secs - up;
+        self.ok += ok;
+        self.failed += samples - ok;
+        if let Some(latency) = record.latency_ms.filter(|_| ok > 0) {
+            self.latency_sum += latency as f64 * ok as f64;
+            self.latency_n += ok;
+        }
+    }
+
+    fn uptime_pct(&self) -> Option<f64> {
+        let total = self.up_secs + self.down_secs;
+        (total > 0).then(|| self.up_secs as f64 * 100.0 / total as f64)
+    }
+}
+
+/// 按时间从新到旧遍历记录，附带每条记录的持续时间 (秒)：取到下一条记录为止 (最新一条到当前时间)，
+/// 超过 `max(300, 2 * interval)` 的间隔视为没有数据
+fn with_durations<'a>(
+    records: impl IntoIterator<Item = &'a ProbeRecord>,
+    now: DateTime<Local>,
+    interval: std::time::Duration,
+) -> impl Iterator<Item = (&'a ProbeRecord, i64)> {
+    let max_gap = MAX_GAP_SECS.max(interval.as_secs() as i64 * 2);
+    let mut next_ts = now;
+    // records 为 push_front，最新的在前
+    records.into_iter().map(move |record| {
+        let span = record.rollup.as_ref().map_or(0, |r| r.span_secs as i64);
+        let secs = (next_ts - record.timestamp)
+            .num_seconds()
+            .clamp(0, max_gap.max(span));
+        next_ts = record.timestamp;
+        (record, secs)
+    })
+}
+
+/// 状态页 90 天条形图中的一格
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyStatus {
+    pub date: NaiveDate,
+    pub status: DayState,
+    pub uptime_pct: Option<f64>,
+    pub downtime_minutes: f64,
+    pub probes: usize,
+}
+
+/// 按本地日期汇总最近 `days` 天的可用性，结果从旧到新排列。
+/// 每条记录的持续时间取到下一条记录为止 (最新一条到当前时间)，因此与探测间隔无关。
+pub fn daily_summary(
+    records: &VecDeque<ProbeRecord>,
+    days: u32,
+    now: DateTime<Local>,
+    interval: std::time::Duration,
+) -> Vec<DailyStatus> {
+    let mut buckets: BTreeMap<NaiveDate, Tally> = BTreeMap::new();
+    for (record, secs) in with_durations(records, now, interval) {
+        buckets
+            .entry(record.timestamp.date_naive())
+            .or_default()
+            .add(record, secs);
+    }
+
+    let today = now.date_naive();
+    (0..days as i64)
+        .rev()
+        .map(|offset| {
+            let date = today - Duration::days(offset);
+            let Some(bucket) = buckets.get(&date) else {
+                return DailyStatus {
+                    date,
+                    status: DayState::NoData,
+                    uptime_pct: None,
+                    downtime_minutes: 0.0,
+                    probes: 0,
+                };
+            };
+            let status = match (bucket.ok, bucket.failed) {
+                (0, 0) => DayState::Maintenance,
+                (_, 0) => DayState::Up,
+                (0, _) => DayState::Down,
+                _ => DayState::Partial,
+            };
+            DailyStatus {
+                date,
+                status,
+                uptime_pct: bucket.uptime_pct(),
+                downtime_minutes: bucket.down_secs as f64 / 60.0,
+                probes: bucket.ok + bucket.failed + bucket.maintenance,
+            }
+        })
+        .collect()
+}
+
+/// 一段时间窗口内的可用率 (SLA)
+#[derive(Debug, Clone, Serialize)]
+pub struct Uptime {
+    pub uptime_pct: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+    pub downtime_minutes: f64,
+    pub probes: usize,
+}
+
+/// 计算 `now - window` 至今的可用率与成功探测的平均延迟，时长的计算方式与 `daily_summary` 相同
+pub fn uptime(
+    records: &VecDeque<ProbeRecord>,
+    window: Duration,
+    now: DateTime<Local>,
+    interval: std::time::Duration,
+) -> Uptime {
+    uptime_between(records, now - window, now, interval)
+}
+
+/// 计算 `[from, to)` 区间的可用率，`to` 之前最后一条记录的时长截止到 `to`
+pub fn uptime_between(
+    records: &VecDeque<ProbeRecord>,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    interval: std::time::Duration,
+) -> Uptime {
+    let newer = records.iter().take_while(|r| r.timestamp >= to).count();
+    let mut tally = Tally::default();
+    for (record, secs) in with_durations(records.range(newer..), to, interval) {
+        if record.timestamp < from {
+            break;
+        }
+        tally.add(record, secs);
+    }
+    Uptime {
+        uptime_pct: tally.uptime_pct(),
+        avg_latency_ms: (tally.latency_n > 0).then(|| tally.latency_sum / tally.latency_n as f64),
+        downtime_minutes: tally.down_secs as f64 / 60.0,
+        probes: tally.ok + tally.failed + tally.maintenance,
+    }
+}
+
+/// 新记录加入前调用：把当前最新一条记录 (持续到 `now`) 计入累计计数。
+/// 首次调用时 (`since` 为空，如从旧版本升级) 用全部已有记录初始化
+pub fn accumulate(
+    totals: &mut Totals,
+    records: &VecDeque<ProbeRecord>,
+    now: DateTime<Local>,
+    interval: std::time::Duration,
+) {
+    let take = if totals.since.is_some() {
+        1
+    } else {
+        usize::MAX
+    };
+    let mut tally = Tally::default();
+    for (record, secs) in with_durations(records, now, interval).take(take) {
+        tally.add(record, secs);
+    }
+    if totals.since.is_none() {
+        totals.since = Some(records.back().map_or(now, |r| r.timestamp));
+    }
+    totals.probes += (tally.ok + tally.failed + tally.maintenance) as u64;
+    totals.failures += tally.failed as u64;
+    totals.up_secs += tally.up_secs as u64;
+    totals.downtime_secs += tally.down_secs as u64;
+    totals.latency_sum_ms += tally.latency_sum;
+    totals.latency_samples += tally.latency_n as u64;
+}
+
+/// 累计计数对应的可用率统计 (自 `since` 起)
+pub fn cumulative(totals: &Totals) -> Uptime {
+    let total = totals.up_secs + totals.downtime_secs;
+    Uptime {
+        uptime_pct: (total > 0).then(|| totals.up_secs as f64 * 100.0 / total as f64),
+        avg_latency_ms: (totals.latency_samples > 0)
+            .then(|| totals.latency_sum_ms / totals.latency_samples as f64),
+        downtime_minutes: totals.downtime_secs as f64 / 60.0,
+        probes: totals.probes as usize,
+    }
+}
+
+/// 一段时间窗口内成功探测的延迟分布 (毫秒)
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyStats {
+    pub samples: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+/// 统计 `now - window` 至今成功探测的延迟，没有样本时返回 None。
+/// 降采样后的聚合记录按其平均延迟、以成功次数为权重计入分位数，min/max 取其实际极值。
+pub fn latency_stats(
+    records: &VecDeque<ProbeRecord>,
+    window: Duration,
+    now: DateTime<Local>,
+) -> Option<LatencyStats> {
+    latency_between(records, now - window, now)
+}
+
+/// 统计 `[from, to)` 区间成功探测的延迟，规则同 `latency_stats`
+pub fn latency_between(
+    records: &VecDeque<ProbeRecord>,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Option<LatencyStats> {
+    let mut values: Vec<(f64, u64)> = Vec::new();
+    let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+    let in_range = records
+        .iter()
+        .skip_while(|r| r.timestamp >= to)
+        .take_while(|r| r.timestamp >= from);
+    for record in in_range {
+        let Some(latency) = record.latency_ms.map(f64::from) else {
+            continue;
+        };
+        let (weight, low, high) = match &record.rollup {
+            Some(r) => (
+                r.ok as u64,
+                r.min_latency_ms.map_or(latency, f64::from),
+                r.max_latency_ms.map_or(latency, f64::from),
+            ),
+            None if record.success => (1, latency, latency),
+            None => continue,
+        };
+        if weight == 0 {
+            continue;
+        }
+        values.push((latency, weight));
+        min = min.min(low);
+        max = max.max(high);
+    }
+
+    let samples: u64 = values.iter().map(|(_, w)| w).sum();
+    if samples == 0 {
+        return None;
+    }
+    let mean = values.iter().map(|(v, w)| v * *w as f64).sum::<f64>() / samples as f64;
+    let variance = values
+        .iter()
+        .map(|(v, w)| (v - mean).powi(2) * *w as f64)
+        .sum::<f64>()
+        / samples as f64;
+
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    // nearest-rank：第 ceil(p * n) 个样本
+    let percentile = |p: f64| {
+        let rank = ((p * samples as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        values
+            .iter()
+            .find(|(_, w)| {
+                seen += w;
+                seen >= rank
+            })
+            .map_or(max, |(v, _)| *v)
+    };
+    Some(LatencyStats {
+        samples,
+        min,
+        max,
+        mean,
+        stddev: variance.sqrt(),
+        p50: percentile(0.50),
+        p90: percentile(0.90),
+        p99: percentile(0.99),
+    })
+}
+
+/// 热力图默认的延迟区间上界 (毫秒)，最后一个区间不设上界
+pub const DEFAULT_LATENCY_BANDS: [f64; 8] = [5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+
+/// 热力图中的一个时间桶：`counts[i]` 为延迟落在第 i 个区间的成功探测次数
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapBucket {
+    pub start: DateTime<Local>,
+    pub counts: Vec<u64>,
+    pub failed: u64,
+}
+
+/// 按时间桶 × 延迟区间统计 `now - window` 至今的探测次数。时间桶按本地时间对齐
+/// (如 1h 桶从整点开始)，没有数据的桶计数为 0；维护期间的记录不计入。
+/// 降采样后的聚合记录按平均延迟整体计入一个区间
+pub fn latency_heatmap(
+    records: &VecDeque<ProbeRecord>,
+    window: Duration,
+    now: DateTime<Local>,
+    bucket: Duration,
+    bands: &[f64],
+) -> Vec<HeatmapBucket> {
+    let bucket_secs = bucket.num_seconds().max(1);
+    let offset = now.offset().local_minus_utc() as i64;
+    let since = (now - window).timestamp();
+    let first = (since + offset).div_euclid(bucket_secs) * bucket_secs - offset;
+    let count = (now.timestamp() - first) / bucket_secs + 1;
+    let mut buckets: Vec<HeatmapBucket> = (0..count)
+        .filter_map(|i| {
+            Some(HeatmapBucket {
+                start: DateTime::from_timestamp(first + i * bucket_secs, 0)?.with_timezone(&Local),
+                counts: vec![0; bands.len() + 1],
+                failed: 0,
+            })
+        })
+        .collect();
+
+    for record in records
+        .iter()
+        .take_while(|r| r.timestamp.timestamp() >= since)
+    {
+        if record.maintenance {
+            continue;
+        }
+        let index = ((record.timestamp.timestamp() - first) / bucket_secs) as usize;
+        let Some(bucket) = buckets.get_mut(index) else {
+            continue;
+        };
+        let (samples, ok) = match &record.rollup {
+            Some(rollup) => (rollup.samples as u64, rollup.ok as u64),
+            None => (1, record.success as u64),
+        };
+        bucket.failed += samples - ok.min(samples);
+        if let Some(latency) = record.latency_ms.map(f64::from).filter(|_| ok > 0) {
+            bucket.counts[bands.partition_point(|b| *b < latency)] += ok;
+        }
+    }
+    buckets
+}
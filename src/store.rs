@@ -0,0 +1,62 @@
+use anyhow::Context;
+use once_cell::sync::Lazy;
+use rusqlite::{Connection, OptionalExtension};
+use std::env;
+use std::sync::Mutex;
+
+// 设置后配置与目标状态缓存保存在该 SQLite 数据库中，代替 config.json 与 cache.json
+const STATE_DB_ENV: &str = "NETWATCH_STATE_DB";
+
+// 首次使用时打开，打开失败时下次调用重试 (例如挂载卷尚未就绪)
+static DB: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn state_db_path() -> Option<String> {
+    env::var(STATE_DB_ENV).ok().filter(|p| !p.is_empty())
+}
+
+pub fn is_enabled() -> bool {
+    state_db_path().is_some()
+}
+
+fn with_db<T>(f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> anyhow::Result<T> {
+    let path = state_db_path().context("NETWATCH_STATE_DB is not set")?;
+    let mut guard = DB.lock().unwrap();
+    if guard.is_none() {
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Failed to open state database {}", path))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS state (
+                 key TEXT PRIMARY KEY,
+                 value TEXT NOT NULL,
+                 updated_at TEXT NOT NULL
+             );",
+        )
+        .with_context(|| format!("Failed to initialize state database {}", path))?;
+        *guard = Some(conn);
+    }
+    let conn = guard.as_ref().expect("connection initialized above");
+    f(conn).with_context(|| format!("State database {} error", path))
+}
+
+pub fn get(key: &str) -> anyhow::Result<Option<String>> {
+    with_db(|conn| {
+        conn.query_row("SELECT value FROM state WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+    })
+}
+
+/// 写入 (或覆盖) 一个键，单条语句在 SQLite 中是原子的，不会留下半个值
+pub fn put(key: &str, value: &str) -> anyhow::Result<()> {
+    let updated_at = chrono::Local::now().to_rfc3339();
+    with_db(|conn| {
+        conn.execute(
+            "INSERT INTO state (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            [key, value, updated_at.as_str()],
+        )
+        .map(|_| ())
+    })
+}
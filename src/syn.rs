@@ -0,0 +1,129 @@
+//! TCP 半开 (SYN) 探测：只发送 SYN，根据 SYN-ACK / RST 判断端口状态，不完成三次握手，
+//! 因此不会在目标应用日志里留下连接记录。需要 root 或 CAP_NET_RAW，仅支持 IPv4。
+
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+const TCP_ACK: u8 = 0x10;
+
+pub enum SynResult {
+    Open,
+    Closed,
+}
+
+/// 阻塞执行一次 SYN 探测，应在 `spawn_blocking` 中调用
+/// `bind` 为本地 IPv4 地址或网卡名
+pub fn syn_probe(
+    dst: Ipv4Addr,
+    port: u16,
+    bind: Option<&str>,
+    timeout: Duration,
+) -> std::io::Result<SynResult> {
+    let socket = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::TCP))?;
+    let src = match bind.filter(|b| !b.is_empty()) {
+        Some(bind) => match bind.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                socket.bind(&SockAddr::from(SocketAddr::new(IpAddr::V4(ip), 0)))?;
+                ip
+            }
+            Ok(IpAddr::V6(_)) => return Err(std::io::ErrorKind::AddrNotAvailable.into()),
+            Err(_) => {
+                socket.bind_device(Some(bind.as_bytes()))?;
+                local_ipv4_for(dst, port, Some(bind))?
+            }
+        },
+        None => local_ipv4_for(dst, port, None)?,
+    };
+    let src_port = 40000 + (uuid::Uuid::new_v4().as_u128() % 20000) as u16;
+    let seq = uuid::Uuid::new_v4().as_u128() as u32;
+
+    let packet = build_syn(src, dst, src_port, port, seq);
+    socket.send_to(
+        &packet,
+        &SockAddr::from(SocketAddr::new(IpAddr::V4(dst), 0)),
+    )?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 1500];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let n = match (&socket).read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                return Err(std::io::ErrorKind::TimedOut.into())
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Raw socket 收到的数据包含 IP 头
+        let packet = &buf[..n];
+        if packet.len() < 20 || packet[9] != 6 || packet[12..16] != dst.octets() {
+            continue;
+        }
+        let ihl = ((packet[0] & 0x0F) as usize) * 4;
+        let Some(tcp) = packet.get(ihl..ihl + 20) else {
+            continue;
+        };
+        let sport = u16::from_be_bytes([tcp[0], tcp[1]]);
+        let dport = u16::from_be_bytes([tcp[2], tcp[3]]);
+        if sport != port || dport != src_port {
+            continue;
+        }
+
+        let flags = tcp[13];
+        if flags & (TCP_SYN | TCP_ACK) == TCP_SYN | TCP_ACK {
+            // 内核没有对应的 socket，会自动回 RST 结束半开连接
+            return Ok(SynResult::Open);
+        }
+        if flags & TCP_RST != 0 {
+            return Ok(SynResult::Closed);
+        }
+    }
+}
+
+// 通过 UDP connect 让内核选路，得到发往目标时使用的源地址
+fn local_ipv4_for(dst: Ipv4Addr, port: u16, device: Option<&str>) -> std::io::Result<Ipv4Addr> {
+    let udp = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    if let Some(device) = device {
+        udp.bind_device(Some(device.as_bytes()))?;
+    }
+    udp.connect(&SockAddr::from(SocketAddr::new(IpAddr::V4(dst), port)))?;
+    match udp.local_addr()?.as_socket().map(|a| a.ip()) {
+        Some(IpAddr::V4(ip)) => Ok(ip),
+        _ => Err(std::io::ErrorKind::AddrNotAvailable.into()),
+    }
+}
+
+fn build_syn(src: Ipv4Addr, dst: Ipv4Addr, src_port: u16, dst_port: u16, seq: u32) -> [u8; 20] {
+    let mut tcp = [0u8; 20];
+    tcp[0..2].copy_from_slice(&src_port.to_be_bytes());
+    tcp[2..4].copy_from_slice(&dst_port.to_be_bytes());
+    tcp[4..8].copy_from_slice(&seq.to_be_bytes());
+    tcp[12] = 5 << 4; // Data offset: 5 * 4 = 20 bytes
+    tcp[13] = TCP_SYN;
+    tcp[14..16].copy_from_slice(&1024u16.to_be_bytes()); // Window
+
+    // 校验和 = 伪首部 + TCP 首部
+    let mut sum: u32 = 0;
+    for chunk in src.octets().chunks(2).chain(dst.octets().chunks(2)) {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    sum += 6; // Protocol: TCP
+    sum += tcp.len() as u32;
+    for chunk in tcp.chunks(2) {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    tcp[16..18].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+    tcp
+}
@@ -0,0 +1,180 @@
+use anyhow::Context;
+use axum::Router;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use once_cell::sync::OnceCell;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+// 证书文件的检查间隔，续期工具替换文件后在该时间内生效
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+// 握手超时，防止半开连接长期占用
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HTTPS 证书与私钥 (PEM)，文件更新后自动重新加载，可配合 certbot 等工具续期
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+// 只在启动时从命令行 `--tls-cert` / `--tls-key` 或环境变量读取，不属于可通过 API 修改的配置，
+// 否则编辑者可以让服务把任意文件当作证书与私钥读取
+static CONFIG: OnceCell<TlsConfig> = OnceCell::new();
+
+/// 设置证书与私钥路径，命令行参数优先于环境变量，都未设置时以明文 HTTP 提供服务
+pub fn init(cli_cert: Option<String>, cli_key: Option<String>) -> anyhow::Result<()> {
+    let from_env = |name: &str| std::env::var(name).ok().filter(|p| !p.is_empty());
+    let cert = cli_cert.or_else(|| from_env("NETWATCH_TLS_CERT"));
+    let key = cli_key.or_else(|| from_env("NETWATCH_TLS_KEY"));
+    match (cert, key) {
+        (Some(cert_path), Some(key_path)) => {
+            let _ = CONFIG.set(TlsConfig {
+                cert_path,
+                key_path,
+            });
+            Ok(())
+        }
+        (None, None) => Ok(()),
+        _ => anyhow::bail!("Both a TLS certificate and a private key are required"),
+    }
+}
+
+pub fn config() -> Option<&'static TlsConfig> {
+    CONFIG.get()
+}
+
+/// 读取 PEM 证书链与私钥 (PKCS#8 / PKCS#1 / SEC1)
+pub fn load(tls: &TlsConfig) -> anyhow::Result<Arc<ServerConfig>> {
+    let cert_pem = std::fs::read(&tls.cert_path)
+        .with_context(|| format!("Failed to read certificate {}", tls.cert_path))?;
+    let certs: Vec<Certificate> = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .context("Invalid certificate PEM")?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    if certs.is_empty() {
+        anyhow::bail!("No certificate found in {}", tls.cert_path);
+    }
+
+    let key_pem = std::fs::read(&tls.key_path)
+        .with_context(|| format!("Failed to read private key {}", tls.key_path))?;
+    let key = rustls_pemfile::read_all(&mut key_pem.as_slice())
+        .context("Invalid private key PEM")?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .with_context(|| format!("No private key found in {}", tls.key_path))?;
+
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("Certificate and private key do not match")?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// 以 HTTPS 提供服务，`shutdown` 完成后停止接受新连接并等待现有连接结束
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    tls: TlsConfig,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let current = Arc::new(RwLock::new(load(&tls)?));
+    tokio::spawn(reload_task(tls, current.clone()));
+
+    let (close_tx, close_rx) = watch::channel(());
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    // 文件描述符耗尽等错误，稍后重试
+                    tracing::warn!("Failed to accept connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = TlsAcceptor::from(current.read().unwrap().clone());
+        let service = TowerToHyperService::new(app.clone());
+        let mut close_rx = close_rx.clone();
+        tokio::spawn(async move {
+            let stream =
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!("TLS handshake with {} failed: {}", peer, e);
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!("TLS handshake with {} timed out", peer);
+                        return;
+                    }
+                };
+            let conn = hyper1::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .with_upgrades();
+            tokio::pin!(conn);
+            tokio::select! {
+                result = conn.as_mut() => {
+                    if let Err(e) = result {
+                        tracing::debug!("Connection from {} closed: {}", peer, e);
+                    }
+                }
+                _ = close_rx.changed() => {
+                    conn.as_mut().graceful_shutdown();
+                    let _ = conn.await;
+                }
+            }
+        });
+    }
+
+    drop(listener);
+    let _ = close_tx.send(());
+    drop(close_rx);
+    close_tx.closed().await;
+    Ok(())
+}
+
+// 证书或私钥文件的修改时间变化后重新加载，新连接使用新证书
+async fn reload_task(tls: TlsConfig, current: Arc<RwLock<Arc<ServerConfig>>>) {
+    let modified = |tls: &TlsConfig| -> Option<(SystemTime, SystemTime)> {
+        let cert = std::fs::metadata(&tls.cert_path).ok()?.modified().ok()?;
+        let key = std::fs::metadata(&tls.key_path).ok()?.modified().ok()?;
+        Some((cert, key))
+    };
+    let mut last = modified(&tls);
+    let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let now = modified(&tls);
+        if now.is_none() || now == last {
+            continue;
+        }
+        // 续期工具可能分两次写入证书与私钥，不匹配时保留旧证书，下次检查再试
+        match load(&tls) {
+            Ok(config) => {
+                *current.write().unwrap() = config;
+                last = now;
+                tracing::info!("Reloaded TLS certificate from {}", tls.cert_path);
+            }
+            Err(e) => tracing::warn!("Failed to reload TLS certificate: {:#}", e),
+        }
+    }
+}
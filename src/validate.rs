@@ -0,0 +1,471 @@
+use crate::model::{AppConfig, ChannelKind, Protocol, SmtpConfig, SmtpTls, Target};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+
+// 检查告警通道连通性时每个地址的连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 配置中的一处问题，`path` 形如 `targets[2].port`
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub path: String,
+    pub message: String,
+}
+
+/// 校验结果：`errors` 会导致配置无法正常工作；`warnings` (未知字段、无法连接的告警通道)
+/// 不影响保存，但通常意味着笔误或网络问题
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<Issue>,
+    pub warnings: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(Issue {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(Issue {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// 校验候选配置 (不保存)：先按配置文件格式解析，解析成功后再检查目标与告警通道
+pub async fn validate(raw: serde_json::Value) -> (ValidationReport, Option<AppConfig>) {
+    let mut report = ValidationReport::default();
+    let config = match serde_path_to_error::deserialize::<_, AppConfig>(&raw) {
+        Ok(config) => config,
+        Err(e) => {
+            report.error(e.path().to_string(), e.inner().to_string());
+            return (report, None);
+        }
+    };
+    // 解析成功后再收集被忽略的字段 (serde 默认忽略未知字段，笔误的字段名不会生效)
+    let mut unknown = Vec::new();
+    let _ = serde_ignored::deserialize::<_, _, AppConfig>(&raw, |path| {
+        unknown.push(format_path(&path))
+    });
+    for path in unknown {
+        report.warning(path, "Unknown field, ignored");
+    }
+
+    check_targets(&config, &mut report);
+    check_auth(&config, &mut report);
+    check_webhooks(&config, &mut report).await;
+    report.valid = report.errors.is_empty();
+    (report, Some(config))
+}
+
+fn check_targets(config: &AppConfig, report: &mut ValidationReport) {
+    let ids: HashSet<&str> = config.targets.iter().map(|t| t.id.as_str()).collect();
+    let mut seen = HashSet::new();
+    let mut tokens = HashSet::new();
+    for (i, target) in config.targets.iter().enumerate() {
+        let path = |field: &str| format!("targets[{}].{}", i, field);
+        if target.id.trim().is_empty() {
+            report.error(path("id"), "Target id is required");
+        } else if !seen.insert(target.id.as_str()) {
+            report.error(path("id"), format!("Duplicate target id {}", target.id));
+        }
+        if let Err(e) = check_host(target) {
+            report.error(path("host"), e);
+        }
+        if target.port == Some(0) {
+            report.error(path("port"), "Port must be between 1 and 65535");
+        }
+        if target.interval_secs == Some(0) || target.push_period_secs == Some(0) {
+            let field = if target.interval_secs == Some(0) {
+                "interval_secs"
+            } else {
+                "push_period_secs"
+            };
+            report.error(path(field), "Must be at least 1 second");
+        }
+        if target
+            .push_period_secs
+            .is_some_and(|secs| secs > crate::push::MAX_PERIOD_SECS)
+        {
+            report.error(
+                path("push_period_secs"),
+                format!("Must be at most {} seconds", crate::push::MAX_PERIOD_SECS),
+            );
+        }
+        if let Some(delay) = target.retry_delay_ms {
+            let interval = config.interval_for(target).as_millis();
+            if u128::from(delay) > interval {
+                report.error(
+                    path("retry_delay_ms"),
+                    format!("Must not exceed the probe interval ({} ms)", interval),
+                );
+            }
+        }
+        match target.parent.as_deref() {
+            Some(parent) if parent == target.id => {
+                report.error(path("parent"), "Target cannot be its own parent")
+            }
+            Some(parent) if !ids.contains(parent) => {
+                report.error(path("parent"), format!("Unknown parent target {}", parent))
+            }
+            _ => {}
+        }
+        if let Some(expr) = target
+            .active_cron
+            .as_deref()
+            .filter(|e| !e.trim().is_empty())
+        {
+            if let Err(e) = croner::Cron::new(expr).parse() {
+                report.error(
+                    path("active_cron"),
+                    format!("Invalid cron expression: {}", e),
+                );
+            }
+        }
+        if let Some(bind) = target.bind.as_deref().filter(|b| !b.is_empty()) {
+            if bind.parse::<IpAddr>().is_err()
+                && !crate::monitor::supports_interface_bind(&target.protocol)
+            {
+                report.error(
+                    path("bind"),
+                    "HTTP and DNS probes can only bind to a local IP address",
+                );
+            }
+        }
+        if target.protocol == Protocol::Exec && target.command.is_empty() {
+            report.error(path("command"), "EXEC target requires a command");
+        }
+        if let Some(token) = target.push_token.as_deref().filter(|t| !t.is_empty()) {
+            if !tokens.insert(token) {
+                report.error(path("push_token"), "Duplicate push token");
+            }
+        }
+    }
+}
+
+fn check_auth(config: &AppConfig, report: &mut ValidationReport) {
+    let Some(auth) = &config.auth else {
+        return;
+    };
+    if !(1..=crate::auth::MAX_SESSION_HOURS).contains(&auth.session_hours) {
+        report.error(
+            "auth.session_hours",
+            format!(
+                "Must be between 1 and {} hours",
+                crate::auth::MAX_SESSION_HOURS
+            ),
+        );
+    }
+}
+
+fn check_host(target: &Target) -> Result<(), String> {
+    let host = target.host.trim();
+    match target.protocol {
+        // 仅用于显示与告警模版
+        Protocol::Exec | Protocol::Watchdog | Protocol::Push => return Ok(()),
+        _ if host.is_empty() => return Err("Host is required".to_string()),
+        Protocol::Http | Protocol::Https if host.contains("://") => {
+            return match reqwest::Url::parse(host) {
+                Ok(url) if url.host().is_some() => Ok(()),
+                Ok(_) => Err("URL has no host".to_string()),
+                Err(e) => Err(format!("Invalid URL: {}", e)),
+            };
+        }
+        _ => {}
+    }
+    let bare = host.trim_start_matches('[').trim_end_matches(']');
+    if bare.parse::<IpAddr>().is_ok() || is_hostname(host) {
+        Ok(())
+    } else {
+        Err(format!("Invalid host {}", host))
+    }
+}
+
+fn is_hostname(host: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+// 全局与各命名空间的告警通道：地址格式错误为 error，无法建立 TCP 连接为 warning。
+// 邮件通道检查 SMTP 服务器与收发件人地址，Telegram 通道检查机器人令牌与会话，
+// PagerDuty 通道检查 routing_key，MQTT 通道检查 broker 与主题。
+// 只建连不发送请求，避免校验时触发真实告警
+async fn check_webhooks(config: &AppConfig, report: &mut ValidationReport) {
+    let mut webhooks = vec![("alert".to_string(), &config.alert)];
+    for (i, ns) in config.namespaces.iter().enumerate() {
+        if let Some(alert) = &ns.alert {
+            webhooks.push((format!("namespaces[{}].alert", i), alert));
+        }
+    }
+
+    let mut checks = Vec::new();
+    for (prefix, alert) in webhooks {
+        if alert
+            .cooldown_secs
+            .is_some_and(|secs| secs > crate::alert::MAX_COOLDOWN_SECS)
+        {
+            report.error(
+                format!("{}.cooldown_secs", prefix),
+                format!(
+                    "Must be at most {} seconds",
+                    crate::alert::MAX_COOLDOWN_SECS
+                ),
+            );
+        }
+        if let Some(url) = &alert.dashboard_url {
+            check_url(url, &format!("{}.dashboard_url", prefix), report);
+        }
+        for (i, webhook) in alert.webhooks.iter().enumerate() {
+            if let Some(quiet) = &webhook.quiet_hours {
+                for (j, expr) in quiet.windows.iter().enumerate() {
+                    if let Err(e) = croner::Cron::new(expr).parse() {
+                        report.error(
+                            format!("{}.webhooks[{}].quiet_hours.windows[{}]", prefix, i, j),
+                            format!("Invalid cron expression: {}", e),
+                        );
+                    }
+                }
+            }
+            match webhook.kind {
+                ChannelKind::Webhook
+                | ChannelKind::Slack
+                | ChannelKind::Discord
+                | ChannelKind::DingTalk
+                | ChannelKind::WeCom
+                | ChannelKind::Feishu => {}
+                ChannelKind::Email => {
+                    let path = format!("{}.webhooks[{}].smtp", prefix, i);
+                    if let Some(addr) = check_smtp(webhook.smtp.as_ref(), &path, report) {
+                        if webhook.enabled {
+                            checks.push((path, addr));
+                        }
+                    }
+                    continue;
+                }
+                ChannelKind::PagerDuty => {
+                    if webhook
+                        .routing_key
+                        .as_deref()
+                        .is_none_or(|k| k.trim().is_empty())
+                    {
+                        report.error(
+                            format!("{}.webhooks[{}].routing_key", prefix, i),
+                            "PagerDuty channel requires routing_key",
+                        );
+                    }
+                    // 未填写 url 时使用默认的事件地址
+                    if webhook.url.trim().is_empty() {
+                        if webhook.enabled {
+                            let path = format!("{}.webhooks[{}]", prefix, i);
+                            checks.push((path, ("events.pagerduty.com".to_string(), 443)));
+                        }
+                        continue;
+                    }
+                }
+                ChannelKind::Ntfy => {
+                    let path = format!("{}.webhooks[{}].ntfy", prefix, i);
+                    let Some(ntfy) = &webhook.ntfy else {
+                        report.error(path, "ntfy channel requires ntfy settings");
+                        continue;
+                    };
+                    if ntfy.topic.trim().is_empty() {
+                        report.error(format!("{}.topic", path), "Topic is required");
+                    }
+                    if ntfy.priority.is_some_and(|p| !(1..=5).contains(&p)) {
+                        report.error(format!("{}.priority", path), "Priority must be 1-5");
+                    }
+                    let path = format!("{}.server", path);
+                    if let Some(addr) = check_url(&ntfy.server, &path, report) {
+                        if webhook.enabled {
+                            checks.push((path, addr));
+                        }
+                    }
+                    continue;
+                }
+                ChannelKind::Mqtt => {
+                    let path = format!("{}.webhooks[{}].mqtt", prefix, i);
+                    let Some(mqtt) = &webhook.mqtt else {
+                        report.error(path, "MQTT channel requires mqtt settings");
+                        continue;
+                    };
+                    if mqtt.topic.trim().is_empty() {
+                        report.error(format!("{}.topic", path), "Topic is required");
+                    } else if mqtt.topic.contains(['+', '#']) {
+                        report.error(format!("{}.topic", path), "Topic cannot contain wildcards");
+                    }
+                    if mqtt.qos > 2 {
+                        report.error(format!("{}.qos", path), "QoS must be 0, 1 or 2");
+                    }
+                    let path = format!("{}.broker", path);
+                    match reqwest::Url::parse(mqtt.broker.trim()) {
+                        Err(e) => report.error(path, format!("Invalid URL: {}", e)),
+                        Ok(url) if !matches!(url.scheme(), "mqtt" | "tcp" | "mqtts" | "ssl") => {
+                            report.error(path, "Broker must use mqtt:// or mqtts://")
+                        }
+                        Ok(url) => match url.host_str() {
+                            None => report.error(path, "Broker has no host"),
+                            Some(host) if webhook.enabled => {
+                                let default_port = match url.scheme() {
+                                    "mqtts" | "ssl" => 8883,
+                                    _ => 1883,
+                                };
+                                let addr = (host.to_string(), url.port().unwrap_or(default_port));
+                                checks.push((path, addr));
+                            }
+                            Some(_) => {}
+                        },
+                    }
+                    continue;
+                }
+                ChannelKind::Exec => {
+                    if webhook.command.is_empty() {
+                        report.error(
+                            format!("{}.webhooks[{}].command", prefix, i),
+                            "Exec channel requires a command",
+                        );
+                    }
+                    continue;
+                }
+                ChannelKind::Telegram => {
+                    let path = format!("{}.webhooks[{}].telegram", prefix, i);
+                    match &webhook.telegram {
+                        None => report.error(path, "Telegram channel requires telegram settings"),
+                        Some(t) if t.bot_token.trim().is_empty() || t.chat_id.trim().is_empty() => {
+                            report.error(path, "bot_token and chat_id are required")
+                        }
+                        Some(_) if webhook.enabled => {
+                            checks.push((path, ("api.telegram.org".to_string(), 443)))
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+            }
+            let path = format!("{}.webhooks[{}].url", prefix, i);
+            if let Some(addr) = check_url(&webhook.url, &path, report) {
+                if webhook.enabled {
+                    checks.push((path, addr));
+                }
+            }
+        }
+    }
+    let results = futures::future::join_all(
+        checks
+            .into_iter()
+            .map(|(path, addr)| async move { (path, connect(addr).await) }),
+    )
+    .await;
+    for (path, result) in results {
+        if let Err(e) = result {
+            report.warning(path, format!("Unreachable: {}", e));
+        }
+    }
+}
+
+// 地址须为带主机的 http(s) URL，返回需要检查连通性的地址
+fn check_url(url: &str, path: &str, report: &mut ValidationReport) -> Option<(String, u16)> {
+    let url = match reqwest::Url::parse(url.trim()) {
+        Ok(url) => url,
+        Err(e) => {
+            report.error(path, format!("Invalid URL: {}", e));
+            return None;
+        }
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        report.error(path, "URL must use http or https");
+        return None;
+    }
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        report.error(path, "URL has no host");
+        return None;
+    };
+    Some((host.trim_matches(['[', ']']).to_string(), port))
+}
+
+// 邮件通道的服务器与收发件人地址，返回需要检查连通性的 SMTP 地址
+fn check_smtp(
+    smtp: Option<&SmtpConfig>,
+    path: &str,
+    report: &mut ValidationReport,
+) -> Option<(String, u16)> {
+    let Some(smtp) = smtp else {
+        report.error(path, "Email channel requires smtp settings");
+        return None;
+    };
+    let mut valid = true;
+    if smtp.host.trim().is_empty() {
+        report.error(format!("{}.host", path), "SMTP host is required");
+        valid = false;
+    }
+    if smtp.from.parse::<lettre::message::Mailbox>().is_err() {
+        report.error(
+            format!("{}.from", path),
+            format!("Invalid address {}", smtp.from),
+        );
+    }
+    if smtp.to.is_empty() {
+        report.error(format!("{}.to", path), "At least one recipient is required");
+    }
+    for (i, to) in smtp.to.iter().enumerate() {
+        if to.parse::<lettre::message::Mailbox>().is_err() {
+            report.error(
+                format!("{}.to[{}]", path, i),
+                format!("Invalid address {}", to),
+            );
+        }
+    }
+    let port = smtp.port.unwrap_or(match smtp.tls {
+        SmtpTls::Starttls => 587,
+        SmtpTls::Tls => 465,
+        SmtpTls::None => 25,
+    });
+    valid.then(|| (smtp.host.trim().to_string(), port))
+}
+
+async fn connect(addr: (String, u16)) -> Result<(), String> {
+    match tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!(
+            "connection timed out after {}s",
+            CONNECT_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+// 与 serde_path_to_error 相同的路径格式，如 `targets[2].intervall_secs`
+fn format_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", format_path(parent), index),
+        Path::Map { parent, key } => {
+            let parent = format_path(parent);
+            if parent.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", parent, key)
+            }
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => format_path(parent),
+    }
+}
@@ -1,19 +1,62 @@
-use crate::model::{AppConfig, MonitorStatus};
+use crate::backup::Snapshot;
+use crate::export::{merge_history, HistoryExport, HISTORY_VERSION};
+use crate::live::{LiveHub, LiveReceiver};
+use crate::model::{
+    AlertConfig, AppConfig, ChannelKind, GroupStatus, LiveUpdate, Maintenance, MonitorStatus,
+    ProbeRecord, Protocol, Role, ScheduleInfo, Target, TargetState, WebhookConfig,
+};
+use crate::stats::{
+    cumulative, daily_summary, latency_heatmap, latency_stats, uptime, DailyStatus, Uptime,
+    DEFAULT_LATENCY_BANDS,
+};
 use axum::{
-    extract::State,
-    http::{header, StatusCode, Uri},
+    async_trait,
+    body::Body,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{DefaultBodyLimit, FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, Method, Request, StatusCode, Uri},
     response::sse::{Event, Sse},
-    response::IntoResponse,
-    routing::get,
+    response::{IntoResponse, Redirect, Response},
+    routing::{any, get, post},
     Json, Router,
 };
 use dashmap::DashMap;
 use futures::stream::Stream;
 use futures::StreamExt;
+use once_cell::sync::Lazy;
 use rust_embed::RustEmbed;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, watch};
+use tower::ServiceExt;
+
+const MAX_DAILY_DAYS: u32 = 365;
+// 历史导入的请求体上限，数月的原始记录可达数十 MB
+const MAX_HISTORY_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_RECORDS_LIMIT: usize = 500;
+const MAX_RECORDS_LIMIT: usize = 5000;
+// 目标详情中默认附带的最近记录条数
+const DEFAULT_DETAIL_RECORDS: usize = 100;
+// 热力图最多返回的时间桶数与延迟区间数
+const MAX_HEATMAP_BUCKETS: i64 = 2000;
+const MAX_HEATMAP_BANDS: usize = 32;
+// 延迟折线图的默认与最大尺寸 (像素)
+const DEFAULT_CHART_SIZE: (u32, u32) = (600, 120);
+const MAX_CHART_SIZE: (u32, u32) = (2000, 1000);
+const WS_PING_INTERVAL: Duration = Duration::from_secs(30);
+// 日汇总需要遍历全部历史记录，状态页轮询频繁，短时间内复用计算结果
+const DAILY_CACHE_TTL: Duration = Duration::from_secs(60);
+type DailyCacheEntry = (Instant, Arc<Vec<DailyStatus>>);
+static DAILY_CACHE: Lazy<DashMap<(String, u32), DailyCacheEntry>> = Lazy::new(DashMap::new);
+// 汇总接口中各目标 24h / 7d / 30d 的可用率，同样在 DAILY_CACHE_TTL 内复用
+type SummaryCacheEntry = (Instant, Arc<[Uptime; 3]>);
+static SUMMARY_CACHE: Lazy<DashMap<String, SummaryCacheEntry>> = Lazy::new(DashMap::new);
+// 汇总接口默认与最多返回的表现最差目标数
+const DEFAULT_SUMMARY_WORST: usize = 5;
+const MAX_SUMMARY_WORST: usize = 50;
 
 #[derive(RustEmbed)]
 #[folder = "static"]
@@ -24,25 +67,336 @@ pub struct AppState {
     pub status_map: Arc<DashMap<String, MonitorStatus>>,
     pub config_tx: watch::Sender<AppConfig>,   // 用于更新配置
     pub config_rx: watch::Receiver<AppConfig>, // 用于获取当前配置
-    pub broadcast_tx: broadcast::Sender<String>, // SSE Broadcast
+    pub live: Arc<LiveHub>,                    // SSE / WebSocket 推送
     pub shutdown_tx: broadcast::Sender<()>,    // Shutdown signal
+    pub shutdown_request: Arc<tokio::sync::Notify>, // 远程请求关闭 (/api/admin/shutdown)
+    pub schedule: Arc<DashMap<String, ScheduleInfo>>, // 调度器状态
+    pub base_path: String,                     // 反向代理子路径前缀，为空时挂在根路径
+}
+
+/// 请求方的访问范围：管理员可见全部，租户只可见本命名空间，
+/// 未登录的访客 (仅在启用登录但未保护仪表盘时) 只能只读查看状态。
+/// 绑定了命名空间的只读用户为 `NamespaceViewer`
+#[derive(Clone)]
+pub(crate) enum Access {
+    Admin,
+    Namespace(String),
+    Viewer,
+    NamespaceViewer(String),
+}
+
+impl Access {
+    pub(crate) fn can_see(&self, target: &Target) -> bool {
+        match self.namespace() {
+            None => true,
+            Some(ns) => target.namespace.as_deref() == Some(ns),
+        }
+    }
+
+    /// 限定的命名空间，不限定时为 None
+    pub(crate) fn namespace(&self) -> Option<&str> {
+        match self {
+            Access::Admin | Access::Viewer => None,
+            Access::Namespace(ns) | Access::NamespaceViewer(ns) => Some(ns),
+        }
+    }
+
+    /// 只读角色看不到目标与告警通道中的凭据
+    pub(crate) fn read_only(&self) -> bool {
+        matches!(self, Access::Viewer | Access::NamespaceViewer(_))
+    }
+}
+
+fn session_token(headers: &header::HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(k, _)| *k == crate::auth::SESSION_COOKIE)
+        .map(|(_, v)| v)
+}
+
+// 经租户路径访问时把访问范围收窄到该命名空间；其他命名空间的 Key 与用户返回 403
+fn resolve_access(
+    parts: &Parts,
+    state: &AppState,
+) -> Result<(Access, Role), (StatusCode, &'static str)> {
+    let (access, role) = resolve_credentials(parts, state)?;
+    let Some(NamespaceScope(ns)) = parts.extensions.get::<NamespaceScope>() else {
+        return Ok((access, role));
+    };
+    match access {
+        Access::Namespace(own) | Access::NamespaceViewer(own) if own != *ns => {
+            Err((StatusCode::FORBIDDEN, "Namespace not accessible"))
+        }
+        _ if access.read_only() => Ok((Access::NamespaceViewer(ns.clone()), role)),
+        _ => Ok((Access::Namespace(ns.clone()), role)),
+    }
+}
+
+// 先检查登录会话 Cookie，再检查 API Key。
+// API Key 可通过 `Authorization: Bearer`、`X-API-Key` 或 `?api_key=` 传入 (EventSource 无法设置请求头)。
+// 同时返回角色：访客、viewer 用户与只读 Key 不能修改
+fn resolve_credentials(
+    parts: &Parts,
+    state: &AppState,
+) -> Result<(Access, Role), (StatusCode, &'static str)> {
+    let config = state.config_rx.borrow();
+    if !config.auth_enabled() {
+        return Ok((Access::Admin, Role::Admin));
+    }
+
+    // 会话用户在配置中被删除后立即失效
+    let session_user = session_token(&parts.headers).and_then(crate::auth::session_user);
+    let account = session_user.and_then(|name| {
+        config
+            .auth
+            .as_ref()?
+            .users
+            .iter()
+            .find(|u| u.username == name)
+    });
+    if let Some(account) = account {
+        let access = match (&account.namespace, account.role) {
+            (Some(ns), Role::Admin) => Access::Namespace(ns.clone()),
+            (Some(ns), Role::Viewer) => Access::NamespaceViewer(ns.clone()),
+            (None, Role::Admin) => Access::Admin,
+            // 只读用户与访客一样看不到目标中的凭据
+            (None, Role::Viewer) => Access::Viewer,
+        };
+        return Ok((access, account.role));
+    }
+
+    // 查询参数需要 URL 解码，否则含保留字符的 Key 永远无法匹配
+    let query_key = parts.uri.query().and_then(|q| {
+        form_urlencoded::parse(q.as_bytes())
+            .find(|(k, _)| k == "api_key")
+            .map(|(_, v)| v.into_owned())
+    });
+    let key = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| parts.headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::to_string)
+        .or(query_key);
+    let Some(key) = key else {
+        // 只启用了登录时，未保护仪表盘则允许匿名只读访问
+        let public_read = !config.api_keys_enabled()
+            && config.auth.as_ref().is_some_and(|a| !a.protect_dashboard);
+        return if public_read && (parts.method == Method::GET || parts.method == Method::HEAD) {
+            Ok((Access::Viewer, Role::Viewer))
+        } else if config.login_enabled() {
+            Err((StatusCode::UNAUTHORIZED, "Login or API key required"))
+        } else {
+            Err((StatusCode::UNAUTHORIZED, "API key required"))
+        };
+    };
+
+    key_access(&config, &key).ok_or((StatusCode::UNAUTHORIZED, "Invalid API key"))
+}
+
+/// API Key 对应的访问范围与角色，未知的 Key 返回 None
+pub(crate) fn key_access(config: &AppConfig, key: &str) -> Option<(Access, Role)> {
+    if config.admin_api_keys.iter().any(|k| k == key) {
+        return Some((Access::Admin, Role::Admin));
+    }
+    if config.viewer_api_keys.iter().any(|k| k == key) {
+        return Some((Access::Viewer, Role::Viewer));
+    }
+    config
+        .namespaces
+        .iter()
+        .find(|ns| ns.api_keys.iter().any(|k| k == key))
+        .map(|ns| (Access::Namespace(ns.name.clone()), Role::Admin))
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Access {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        resolve_access(parts, state).map(|(access, _)| access)
+    }
+}
+
+/// 修改类接口的访问范围，只读角色返回 403
+struct Editor(Access);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Editor {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        match resolve_access(parts, state)? {
+            (_, Role::Viewer) => Err((StatusCode::FORBIDDEN, "Read-only access")),
+            (access, Role::Admin) => Ok(Editor(access)),
+        }
+    }
 }
 
 pub fn app(state: AppState) -> Router {
-    Router::new()
+    let base_path = state.base_path.clone();
+    let api = Router::new()
         .route("/api/events", get(sse_handler))
+        .route("/api/ws", get(ws_handler))
+        .route("/api/events/history", get(get_event_history))
+        .route("/api/alerts/dead-letters", get(get_dead_letters))
         .route("/api/config", get(get_config).post(update_config))
+        .route("/api/config/validate", post(validate_config))
+        .route("/api/login", post(login))
+        .route("/api/logout", post(logout))
+        .route("/api/session", get(get_session))
+        .route("/api/system/schedule", get(get_schedule))
+        .route("/api/system/status", get(get_system_status))
+        .route("/api/status", get(get_status))
+        .route("/api/summary", get(get_summary))
+        .route("/api/groups", get(get_groups))
+        .route("/api/targets", post(create_target))
+        .route("/api/targets/reorder", post(reorder_targets))
+        .route(
+            "/api/targets/:id",
+            get(get_target).put(replace_target).delete(delete_target),
+        )
+        .route(
+            "/api/maintenance",
+            get(get_maintenance).post(update_maintenance),
+        )
+        .route("/api/incidents/:id/ack", post(ack_incident))
+        .route("/healthz", get(healthz))
+        .route("/status", get(status_page_handler))
+        .route("/embed/:name", get(embed_handler))
+        .route("/api/public/status", get(get_public_status))
+        .route("/api/push/:token", get(push_handler).post(push_handler))
+        .route("/api/targets/:id/clone", post(clone_target))
+        .route("/api/targets/:id/daily", get(get_daily))
+        .route("/api/targets/:id/uptime", get(get_uptime))
+        .route("/api/targets/:id/latency", get(get_latency))
+        .route("/api/targets/:id/heatmap", get(get_heatmap))
+        .route("/api/targets/:id/chart.png", get(get_chart))
+        .route("/api/targets/:id/records", get(get_records))
+        .route("/api/targets/:id/records.csv", get(get_records_csv))
+        .route(
+            "/api/targets/:id/history",
+            get(export_history)
+                .post(import_history)
+                .layer(DefaultBodyLimit::max(MAX_HISTORY_BYTES)),
+        )
+        .route("/api/import/kuma", post(import_kuma))
+        .route("/api/reports/:year/:month", get(get_monthly_report))
+        .route("/api/backup", get(get_backup))
+        .route("/api/admin/reload", post(admin_reload))
+        .route("/api/logs/stream", get(stream_logs))
+        .route("/api/admin/shutdown", post(admin_shutdown))
+        .route(
+            "/api/restore",
+            post(restore_backup).layer(DefaultBodyLimit::max(MAX_HISTORY_BYTES)),
+        )
         .route("/", get(index_handler))
         .route("/index.html", get(index_handler))
         .route("/*file", get(static_handler))
-        .with_state(state)
+        .with_state(state.clone());
+    let router = api.clone().route(
+        "/api/ns/:namespace/*rest",
+        any({
+            let state = state.clone();
+            move |req: Request<Body>| namespaced(api.clone(), state.clone(), req)
+        }),
+    );
+    if base_path.is_empty() {
+        return router;
+    }
+    // 嵌套路由不匹配带结尾斜杠的前缀本身 (如 `/netwatch/`)，反向代理通常转发的正是这种形式
+    Router::new().nest(&base_path, router).route(
+        &format!("{}/", base_path),
+        get(index_handler).with_state(state),
+    )
 }
 
-async fn index_handler() -> impl IntoResponse {
-    static_handler(Uri::from_static("/index.html")).await
+/// 经租户路径访问时限定的命名空间
+#[derive(Clone)]
+struct NamespaceScope(String);
+
+/// 租户路径 `/api/ns/<命名空间>/...`：转发到对应的 `/api/...` 接口，访问范围限定在该命名空间，
+/// 管理员无需单独的 Key 即可以租户视角查看与修改
+async fn namespaced(api: Router, state: AppState, req: Request<Body>) -> Response {
+    let Some((ns, rest)) = req
+        .uri()
+        .path()
+        .strip_prefix("/api/ns/")
+        .and_then(|p| p.split_once('/'))
+    else {
+        return (StatusCode::NOT_FOUND, "404 Not Found").into_response();
+    };
+    let (ns, rest) = (ns.to_string(), rest.to_string());
+    let (mut parts, body) = req.into_parts();
+    // 先鉴权再检查命名空间是否存在，否则未认证的请求可以借 404 探测有哪些命名空间
+    match resolve_credentials(&parts, &state) {
+        Ok((Access::Namespace(own) | Access::NamespaceViewer(own), _)) if own != ns => {
+            return (StatusCode::FORBIDDEN, "Namespace not accessible").into_response();
+        }
+        Ok(_) => {}
+        Err(e) => return e.into_response(),
+    }
+    if !state
+        .config_rx
+        .borrow()
+        .namespaces
+        .iter()
+        .any(|n| n.name == ns)
+    {
+        return (StatusCode::NOT_FOUND, "Unknown namespace").into_response();
+    }
+    let uri = match parts.uri.query() {
+        Some(query) => format!("/api/{}?{}", rest, query),
+        None => format!("/api/{}", rest),
+    };
+    let Ok(uri) = uri.parse::<Uri>() else {
+        return (StatusCode::BAD_REQUEST, "Invalid path").into_response();
+    };
+    // 重新构造请求，丢弃外层路由的路径参数；WebSocket 升级所需的扩展保留下来
+    let upgrade = parts.extensions.remove::<hyper1::upgrade::OnUpgrade>();
+    let mut req = Request::new(body);
+    *req.method_mut() = parts.method;
+    *req.uri_mut() = uri;
+    *req.version_mut() = parts.version;
+    *req.headers_mut() = parts.headers;
+    if let Some(upgrade) = upgrade {
+        req.extensions_mut().insert(upgrade);
+    }
+    req.extensions_mut().insert(NamespaceScope(ns));
+    match api.oneshot(req).await {
+        Ok(response) => response,
+        Err(e) => match e {},
+    }
 }
 
-async fn static_handler(uri: Uri) -> impl IntoResponse {
+async fn index_handler(
+    State(state): State<AppState>,
+    access: Result<Access, (StatusCode, &'static str)>,
+) -> impl IntoResponse {
+    let protected = {
+        let config = state.config_rx.borrow();
+        config.login_enabled() && config.auth.as_ref().is_some_and(|a| a.protect_dashboard)
+    };
+    if protected && access.is_err() {
+        return Redirect::to(&format!("{}/login.html", state.base_path)).into_response();
+    }
+    static_handler(State(state), Uri::from_static("/index.html"))
+        .await
+        .into_response()
+}
+
+async fn static_handler(State(state): State<AppState>, uri: Uri) -> impl IntoResponse {
     let mut path = uri.path().trim_start_matches('/').to_string();
     if path.starts_with("static/") {
         path = path.replace("static/", "");
@@ -54,59 +408,841 @@ async fn static_handler(uri: Uri) -> impl IntoResponse {
     match Assets::get(path.as_str()) {
         Some(content) => {
             let mime = mime_guess::from_path(path).first_or_octet_stream();
+            // 页面中的链接都是相对路径，通过 <base> 指向实际的前缀
+            if mime == mime_guess::mime::TEXT_HTML {
+                let html = String::from_utf8_lossy(&content.data).replacen(
+                    "<head>",
+                    &format!("<head>\n    <base href=\"{}/\" />", state.base_path),
+                    1,
+                );
+                return ([(header::CONTENT_TYPE, mime.as_ref())], html).into_response();
+            }
             ([(header::CONTENT_TYPE, mime.as_ref())], content.data).into_response()
         }
         None => (StatusCode::NOT_FOUND, "404 Not Found").into_response(),
     }
 }
 
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+async fn login(
+    State(state): State<AppState>,
+    headers: header::HeaderMap,
+    Json(req): Json<LoginRequest>,
+) -> impl IntoResponse {
+    let Some(auth) = state
+        .config_rx
+        .borrow()
+        .auth
+        .clone()
+        .filter(|a| !a.users.is_empty())
+    else {
+        return (StatusCode::NOT_FOUND, "Login is not enabled").into_response();
+    };
+    let hash = auth
+        .users
+        .iter()
+        .find(|u| u.username == req.username)
+        .map(|u| u.password_hash.clone());
+    let password = req.password;
+    let valid =
+        tokio::task::spawn_blocking(move || crate::auth::verify_login(&password, hash.as_deref()))
+            .await
+            .unwrap_or(false);
+    if !valid {
+        tracing::warn!("Failed login attempt for user {}", req.username);
+        return (StatusCode::UNAUTHORIZED, "Invalid username or password").into_response();
+    }
+
+    let Some(ttl) = i64::try_from(auth.session_hours)
+        .ok()
+        .and_then(chrono::Duration::try_hours)
+    else {
+        tracing::error!("Invalid auth.session_hours: {}", auth.session_hours);
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Invalid session_hours").into_response();
+    };
+    let token = match crate::auth::create_session(&req.username, ttl) {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!("Failed to create session: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create session",
+            )
+                .into_response();
+        }
+    };
+    tracing::info!("User {} logged in", req.username);
+    let cookie = session_cookie(&state, &headers, &token, ttl.num_seconds());
+    (
+        [(header::SET_COOKIE, cookie)],
+        Json(serde_json::json!({ "success": true, "username": req.username })),
+    )
+        .into_response()
+}
+
+async fn logout(State(state): State<AppState>, headers: header::HeaderMap) -> impl IntoResponse {
+    if let Some(token) = session_token(&headers) {
+        crate::auth::remove_session(token);
+    }
+    (
+        [(header::SET_COOKIE, session_cookie(&state, &headers, "", 0))],
+        Json(serde_json::json!({ "success": true })),
+    )
+}
+
+/// 当前登录状态，供前端决定是否显示登录/退出入口
+async fn get_session(
+    State(state): State<AppState>,
+    headers: header::HeaderMap,
+) -> Json<serde_json::Value> {
+    let config = state.config_rx.borrow();
+    let username = session_token(&headers).and_then(crate::auth::session_user);
+    let role = username.as_ref().and_then(|name| {
+        let users = &config.auth.as_ref()?.users;
+        users.iter().find(|u| &u.username == name).map(|u| u.role)
+    });
+    Json(serde_json::json!({
+        "login_enabled": config.login_enabled(),
+        "username": username,
+        "role": role,
+    }))
+}
+
+// 原生 HTTPS 或经 HTTPS 反向代理访问时附加 Secure 属性
+fn session_cookie(
+    state: &AppState,
+    headers: &header::HeaderMap,
+    token: &str,
+    max_age: i64,
+) -> String {
+    let secure = crate::tls::config().is_some()
+        || headers
+            .get("x-forwarded-proto")
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"https"));
+    format!(
+        "{}={}; Path={}; HttpOnly; SameSite=Strict; Max-Age={}{}",
+        crate::auth::SESSION_COOKIE,
+        token,
+        if state.base_path.is_empty() {
+            "/"
+        } else {
+            &state.base_path
+        },
+        max_age,
+        if secure { "; Secure" } else { "" }
+    )
+}
+
+/// 重连时通过 `Last-Event-ID` 请求头 (或 `last_event_id` 参数) 补发断线期间的事件
+#[derive(Deserialize)]
+struct LiveQuery {
+    last_event_id: Option<String>,
+}
+
 async fn sse_handler(
     State(state): State<AppState>,
+    access: Access,
+    headers: header::HeaderMap,
+    Query(query): Query<LiveQuery>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    // Initial state
-    let mut status_list: Vec<MonitorStatus> =
-        state.status_map.iter().map(|v| v.value().clone()).collect();
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or(query.last_event_id);
+    let stream = live_events(&state, access, last_event_id.as_deref()).map(|e| {
+        let event = Event::default().event(e.event).data(e.data);
+        Ok(match e.id {
+            Some(id) => event.id(id),
+            None => event,
+        })
+    });
+    Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+/// WebSocket 版本的事件流，每条文本消息为 `{"event": "...", "data": ..., "id": "..."}`，事件与 SSE 相同
+async fn ws_handler(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<LiveQuery>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| ws_session(socket, state, access, query.last_event_id))
+}
+
+async fn ws_session(
+    mut socket: WebSocket,
+    state: AppState,
+    access: Access,
+    last_event_id: Option<String>,
+) {
+    let events = live_events(&state, access, last_event_id.as_deref());
+    tokio::pin!(events);
+    // 定期 Ping，避免代理因连接空闲而断开
+    let mut ping = tokio::time::interval(WS_PING_INTERVAL);
+    ping.tick().await;
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(LiveEvent { id, event, data }) = event else {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                // error 事件的内容是纯文本，其余均为 JSON
+                let data = if event == "error" {
+                    serde_json::Value::String(data).to_string()
+                } else {
+                    data
+                };
+                let text = match id {
+                    Some(id) => format!(
+                        "{{\"event\":\"{}\",\"data\":{},\"id\":\"{}\"}}",
+                        event, data, id
+                    ),
+                    None => format!("{{\"event\":\"{}\",\"data\":{}}}", event, data),
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            // 客户端发来的消息不做处理 (Pong 由底层自动回复)，连接关闭时退出
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+struct LiveEvent {
+    id: Option<String>,
+    event: &'static str,
+    data: String,
+}
+
+impl LiveEvent {
+    fn new(id: Option<&str>, event: &'static str, data: String) -> Self {
+        LiveEvent {
+            id: id.map(str::to_string),
+            event,
+            data,
+        }
+    }
+}
+
+/// SSE 与 WebSocket 共用的实时事件：先是 `init` (可见目标的完整状态) 与 `groups`，
+/// 之后为 `update` / `status` / `group`，广播积压时为 `error`；服务关闭时结束。
+/// `last_event_id` 仍在补发缓冲内时跳过 `init` / `groups`，直接补发其后的事件
+fn live_events(
+    state: &AppState,
+    access: Access,
+    last_event_id: Option<&str>,
+) -> impl Stream<Item = LiveEvent> + Send + 'static {
+    let live = state.live.clone();
+    let (rx, initial) = match last_event_id.and_then(|id| live.resume(id)) {
+        Some((rx, missed)) => {
+            let config = state.config_rx.borrow();
+            let events: Vec<LiveEvent> = missed
+                .iter()
+                .flat_map(|(id, update)| {
+                    live_update_events(
+                        &config,
+                        &state.status_map,
+                        &access,
+                        &live.event_id(*id),
+                        update,
+                    )
+                })
+                .collect();
+            (rx, events)
+        }
+        None => {
+            let (events, rx, last) = initial_events(state, &access);
+            let id = live.event_id(last);
+            let events = events
+                .into_iter()
+                .map(|(event, data)| LiveEvent::new(Some(&id), event, data))
+                .collect();
+            (rx, events)
+        }
+    };
+
+    let status_map = state.status_map.clone();
+    let config_rx = state.config_rx.clone();
+    let broadcast_stream = tokio_stream::wrappers::BroadcastStream::new(rx).flat_map(move |msg| {
+        let events = match msg {
+            Ok((id, update)) => live_update_events(
+                &config_rx.borrow(),
+                &status_map,
+                &access,
+                &live.event_id(id),
+                &update,
+            ),
+            Err(_) => vec![LiveEvent::new(None, "error", "stream lagged".to_string())],
+        };
+        futures::stream::iter(events)
+    });
+
+    let mut shutdown_rx = state.shutdown_tx.subscribe();
+    futures::stream::iter(initial)
+        .chain(broadcast_stream)
+        .take_until(async move {
+            let _ = shutdown_rx.recv().await;
+        })
+}
+
+// 完整的初始状态 (`init` 与 `groups`)，同时订阅广播并取得此时最后一条事件的 ID
+fn initial_events(
+    state: &AppState,
+    access: &Access,
+) -> (Vec<(&'static str, String)>, LiveReceiver, u64) {
+    let limit = state.config_rx.borrow().live_init_records;
+    let status_list = status_snapshot(state, access, limit, |_| true);
+    let config = state.config_rx.borrow();
+
+    let initial_event = (
+        "init",
+        serde_json::to_string(&status_list).unwrap_or_default(),
+    );
+    // 分组汇总作为独立事件下发，前端无需自行计算
+    let groups = crate::group::rollups(&config, &state.status_map, |t| access.can_see(t));
+    let groups_event = ("groups", serde_json::to_string(&groups).unwrap_or_default());
+    drop(config);
+
+    let (rx, last) = state.live.subscribe();
+    (vec![initial_event, groups_event], rx, last)
+}
+
+// 可见目标的状态，按配置中的顺序排列，只读角色隐藏敏感字段
+fn status_snapshot(
+    state: &AppState,
+    access: &Access,
+    limit: usize,
+    filter: impl Fn(&MonitorStatus) -> bool,
+) -> Vec<MonitorStatus> {
+    let mut status_list: Vec<MonitorStatus> = state
+        .status_map
+        .iter()
+        .filter(|v| access.can_see(&v.value().target) && filter(v.value()))
+        .map(|v| live_status(v.value(), limit))
+        .collect();
     let config = state.config_rx.borrow();
-    let order_map: std::collections::HashMap<String, usize> = config
+    let order_map: std::collections::HashMap<&str, usize> = config
         .targets
         .iter()
         .enumerate()
-        .map(|(i, t)| (t.id.clone(), i))
+        .map(|(i, t)| (t.id.as_str(), i))
         .collect();
 
     status_list.sort_by(|a, b| {
-        let idx_a = order_map.get(&a.target.id).unwrap_or(&usize::MAX);
-        let idx_b = order_map.get(&b.target.id).unwrap_or(&usize::MAX);
+        let idx_a = order_map.get(a.target.id.as_str()).unwrap_or(&usize::MAX);
+        let idx_b = order_map.get(b.target.id.as_str()).unwrap_or(&usize::MAX);
         idx_a.cmp(idx_b)
     });
+    if access.read_only() {
+        status_list
+            .iter_mut()
+            .for_each(|s| redact_target(&mut s.target));
+    }
+    status_list
+}
 
-    let initial_json = serde_json::to_string(&status_list).unwrap_or_default();
-    let initial_event = Ok(Event::default().event("init").data(initial_json));
+// 实时事件中的目标状态只携带最近 `limit` 条记录 (0 表示全部)，避免保留期较长时 init 体积过大
+fn live_status(status: &MonitorStatus, limit: usize) -> MonitorStatus {
+    if limit == 0 || status.records.len() <= limit {
+        return status.clone();
+    }
+    MonitorStatus {
+        target: status.target.clone(),
+        records: status.records.iter().take(limit).cloned().collect(),
+        current_state: status.current_state,
+        unreachable: status.unreachable,
+        uptime_24h: status.uptime_24h,
+        totals: status.totals.clone(),
+        incident: status.incident.clone(),
+        last_change: status.last_change,
+    }
+}
 
-    let rx = state.broadcast_tx.subscribe();
-    let broadcast_stream = tokio_stream::wrappers::BroadcastStream::new(rx).map(|msg| match msg {
-        Ok(json) => Ok(Event::default().event("update").data(json)),
-        Err(_) => Ok(Event::default().event("error").data("stream lagged")),
-    });
+// 单条广播转换为推送给该请求方的事件，不可见的目标返回空
+fn live_update_events(
+    config: &AppConfig,
+    status_map: &DashMap<String, MonitorStatus>,
+    access: &Access,
+    id: &str,
+    update: &LiveUpdate,
+) -> Vec<LiveEvent> {
+    let mut events = Vec::new();
+    // 租户只接收本命名空间的更新
+    let target = match update {
+        LiveUpdate::Snapshot(status) => Some(&status.target),
+        LiveUpdate::Probe(_) => config.targets.iter().find(|t| t.id == update.target_id()),
+    };
+    if !target.is_some_and(|t| access.can_see(t)) {
+        return events;
+    }
+    let (event, data) = match update {
+        LiveUpdate::Probe(u) => (
+            "update",
+            serde_json::to_string(u.as_ref()).unwrap_or_default(),
+        ),
+        LiveUpdate::Snapshot(status) => {
+            let mut status = live_status(status, config.live_init_records);
+            if access.read_only() {
+                redact_target(&mut status.target);
+            }
+            ("status", serde_json::to_string(&status).unwrap_or_default())
+        }
+    };
+    events.push(LiveEvent::new(Some(id), event, data));
+    // 成员状态变化后附带所属分组的最新汇总
+    if let Some(group) = target.and_then(|t| t.group.as_deref()) {
+        if let Some(rollup) = crate::group::rollup(config, status_map, group, |t| access.can_see(t))
+        {
+            events.push(LiveEvent::new(
+                Some(id),
+                "group",
+                serde_json::to_string(&rollup).unwrap_or_default(),
+            ));
+        }
+    }
+    events
+}
 
-    let mut shutdown_rx = state.shutdown_tx.subscribe();
-    let stream = futures::stream::once(async { initial_event })
-        .chain(broadcast_stream)
-        .take_until(async move {
-            let _ = shutdown_rx.recv().await;
-        });
+async fn get_config(
+    State(state): State<AppState>,
+    access: Access,
+    headers: header::HeaderMap,
+) -> impl IntoResponse {
+    let config = config_view(&state.config_rx.borrow(), &access);
+    let etag = config_etag(&config);
+    if etag_matches(&headers, header::IF_NONE_MATCH, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+    ([(header::ETAG, etag)], Json(config)).into_response()
+}
 
-    Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
+// 请求方可见的配置
+fn config_view(config: &AppConfig, access: &Access) -> AppConfig {
+    match access {
+        Access::Admin => {
+            let mut config = config.clone();
+            mask_secrets(&mut config);
+            config
+        }
+        Access::Namespace(ns) => {
+            let mut scoped = scoped_config(config, ns);
+            mask_alert_secrets(&mut scoped.alert);
+            scoped
+        }
+        Access::Viewer => public_config(config),
+        // 与访客相同，去掉告警通道与目标中的凭据
+        Access::NamespaceViewer(ns) => {
+            let mut scoped = scoped_config(config, ns);
+            scoped.targets.iter_mut().for_each(redact_target);
+            scoped.alert = Default::default();
+            scoped
+        }
+    }
+}
+
+/// 配置的版本号 (ETag)：不含随探测变化的 `last_known_state` 与只能通过
+/// `/api/maintenance` 修改的维护模式，二者变化时不算作配置被修改
+fn config_etag(config: &AppConfig) -> String {
+    let mut config = config.clone();
+    config
+        .targets
+        .iter_mut()
+        .for_each(|t| t.last_known_state = None);
+    config.maintenance = Default::default();
+    let bytes = serde_json::to_vec(&config).unwrap_or_default();
+    format!("\"{}\"", &hex::encode(Sha256::digest(&bytes))[..32])
+}
+
+// 乐观并发：带 If-Match 时，只有在提交方读取之后配置未被他人修改才保存，
+// 否则返回 412 与当前版本号。需在持有 UPDATE_LOCK 时调用
+fn check_if_match(
+    state: &AppState,
+    access: &Access,
+    headers: &header::HeaderMap,
+) -> Option<Response> {
+    if_match_failed(state, access, headers).then(|| precondition_failed(state, access))
+}
+
+fn if_match_failed(state: &AppState, access: &Access, headers: &header::HeaderMap) -> bool {
+    if !headers.contains_key(header::IF_MATCH) {
+        return false;
+    }
+    let current = config_etag(&config_view(&state.config_rx.borrow(), access));
+    !etag_matches(headers, header::IF_MATCH, &current)
+}
+
+fn precondition_failed(state: &AppState, access: &Access) -> Response {
+    let current = config_etag(&config_view(&state.config_rx.borrow(), access));
+    (
+        StatusCode::PRECONDITION_FAILED,
+        [(header::ETAG, current)],
+        Json(serde_json::json!({
+            "success": false,
+            "error": PRECONDITION_FAILED_MESSAGE
+        })),
+    )
+        .into_response()
+}
+
+const PRECONDITION_FAILED_MESSAGE: &str =
+    "Configuration was modified by someone else; reload and try again";
+
+// `If-Match` / `If-None-Match` 中是否包含 `etag` (或为 `*`)
+fn etag_matches(headers: &header::HeaderMap, name: header::HeaderName, etag: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim().trim_start_matches("W/"))
+        .any(|v| v == "*" || v == etag)
+}
+
+// 对象存储、InfluxDB 与 remote_write 的密钥对任何请求方都以占位符代替；
+// 提交配置时带回占位符表示保持现有值
+const SECRET_PLACEHOLDER: &str = "***";
+
+fn mask_secrets(config: &mut AppConfig) {
+    fn mask(secret: &mut Option<String>) {
+        if secret.as_deref().is_some_and(|s| !s.is_empty()) {
+            *secret = Some(SECRET_PLACEHOLDER.to_string());
+        }
+    }
+    if let Some(backup) = &mut config.backup {
+        if !backup.secret_key.is_empty() {
+            backup.secret_key = SECRET_PLACEHOLDER.to_string();
+        }
+    }
+    if let Some(influx) = &mut config.influx {
+        mask(&mut influx.token);
+    }
+    if let Some(remote_write) = &mut config.remote_write {
+        mask(&mut remote_write.bearer_token);
+    }
+    mask_alert_secrets(&mut config.alert);
+    for ns in &mut config.namespaces {
+        if let Some(alert) = &mut ns.alert {
+            mask_alert_secrets(alert);
+        }
+    }
+}
+
+// 告警通道中的密码、令牌与签名密钥
+fn mask_alert_secrets(alert: &mut AlertConfig) {
+    fn mask(secret: &mut Option<String>) {
+        if secret.as_deref().is_some_and(|s| !s.is_empty()) {
+            *secret = Some(SECRET_PLACEHOLDER.to_string());
+        }
+    }
+    for webhook in &mut alert.webhooks {
+        mask(&mut webhook.secret);
+        mask(&mut webhook.routing_key);
+        if let Some(smtp) = &mut webhook.smtp {
+            mask(&mut smtp.password);
+        }
+        if let Some(mqtt) = &mut webhook.mqtt {
+            mask(&mut mqtt.password);
+        }
+        if let Some(ntfy) = &mut webhook.ntfy {
+            mask(&mut ntfy.token);
+        }
+        if let Some(telegram) = &mut webhook.telegram {
+            if !telegram.bot_token.is_empty() {
+                telegram.bot_token = SECRET_PLACEHOLDER.to_string();
+            }
+        }
+    }
+}
+
+// 把提交的配置中仍为占位符的密钥换回当前值，当前没有对应设置时清空
+fn restore_secrets(config: &mut AppConfig, current: &AppConfig) {
+    fn restore(secret: &mut Option<String>, current: Option<&String>) {
+        if secret.as_deref() == Some(SECRET_PLACEHOLDER) {
+            *secret = current.cloned();
+        }
+    }
+    if let Some(backup) = &mut config.backup {
+        if backup.secret_key == SECRET_PLACEHOLDER {
+            backup.secret_key = current
+                .backup
+                .as_ref()
+                .map(|b| b.secret_key.clone())
+                .unwrap_or_default();
+        }
+    }
+    if let Some(influx) = &mut config.influx {
+        let current = current.influx.as_ref().and_then(|i| i.token.as_ref());
+        restore(&mut influx.token, current);
+    }
+    if let Some(remote_write) = &mut config.remote_write {
+        let current = current
+            .remote_write
+            .as_ref()
+            .and_then(|r| r.bearer_token.as_ref());
+        restore(&mut remote_write.bearer_token, current);
+    }
+    restore_alert_secrets(&mut config.alert, Some(&current.alert));
+    for ns in &mut config.namespaces {
+        if let Some(alert) = &mut ns.alert {
+            let current = current
+                .namespaces
+                .iter()
+                .find(|n| n.name == ns.name)
+                .and_then(|n| n.alert.as_ref());
+            restore_alert_secrets(alert, current);
+        }
+    }
+}
+
+// 按通道 ID 找到当前的通道还原密钥，新增的通道没有对应的当前值
+fn restore_alert_secrets(alert: &mut AlertConfig, current: Option<&AlertConfig>) {
+    fn restore(secret: &mut Option<String>, current: Option<&String>) {
+        if secret.as_deref() == Some(SECRET_PLACEHOLDER) {
+            *secret = current.cloned();
+        }
+    }
+    for webhook in &mut alert.webhooks {
+        let current = current.and_then(|alert| alert.webhooks.iter().find(|w| w.id == webhook.id));
+        restore(&mut webhook.secret, current.and_then(|w| w.secret.as_ref()));
+        restore(
+            &mut webhook.routing_key,
+            current.and_then(|w| w.routing_key.as_ref()),
+        );
+        if let Some(smtp) = &mut webhook.smtp {
+            let current = current.and_then(|w| w.smtp.as_ref()?.password.as_ref());
+            restore(&mut smtp.password, current);
+        }
+        if let Some(mqtt) = &mut webhook.mqtt {
+            let current = current.and_then(|w| w.mqtt.as_ref()?.password.as_ref());
+            restore(&mut mqtt.password, current);
+        }
+        if let Some(ntfy) = &mut webhook.ntfy {
+            let current = current.and_then(|w| w.ntfy.as_ref()?.token.as_ref());
+            restore(&mut ntfy.token, current);
+        }
+        if let Some(telegram) = &mut webhook.telegram {
+            if telegram.bot_token == SECRET_PLACEHOLDER {
+                telegram.bot_token = current
+                    .and_then(|w| w.telegram.as_ref())
+                    .map(|t| t.bot_token.clone())
+                    .unwrap_or_default();
+            }
+        }
+    }
+}
+
+// 访客视角的配置：保留目标与显示相关设置，去掉告警通道、密钥和可能含凭据的目标字段
+fn public_config(config: &AppConfig) -> AppConfig {
+    let mut public = config.clone();
+    public.targets.iter_mut().for_each(redact_target);
+    public.alert = Default::default();
+    public.http.headers.clear();
+    public.backup = None;
+    public.influx = None;
+    public.remote_write = None;
+    public.namespaces.clear();
+    public.admin_api_keys.clear();
+    public.viewer_api_keys.clear();
+    public.auth = None;
+    public.grpc = None;
+    public
+}
+
+// 去掉目标中可能含凭据的字段 (请求头、Cookie、上报地址、命令行)
+pub(crate) fn redact_target(target: &mut Target) {
+    target.headers.clear();
+    target.cookies.clear();
+    target.report_url = None;
+    target.command.clear();
+    target.push_token = None;
+}
+
+// 租户视角的配置：只含本命名空间的目标与告警通道，不暴露全局设置和其他租户的 Key
+fn scoped_config(config: &AppConfig, ns: &str) -> AppConfig {
+    let access = Access::Namespace(ns.to_string());
+    let targets: Vec<Target> = config
+        .targets
+        .iter()
+        .filter(|t| access.can_see(t))
+        .cloned()
+        .collect();
+    // 只带出本命名空间目标引用到的分组规则
+    let groups = config
+        .groups
+        .iter()
+        .filter(|g| targets.iter().any(|t| t.group.as_ref() == Some(&g.name)))
+        .cloned()
+        .collect();
+    AppConfig {
+        targets,
+        alert: config
+            .namespaces
+            .iter()
+            .find(|n| n.name == ns)
+            .and_then(|n| n.alert.clone())
+            .unwrap_or_default(),
+        data_retention_days: config.data_retention_days,
+        data_retention_records: config.data_retention_records,
+        live_init_records: config.live_init_records,
+        http: Default::default(),
+        dns_cache_size: config.dns_cache_size,
+        backup: None,
+        influx: None,
+        remote_write: None,
+        bind: None,
+        namespaces: Vec::new(),
+        admin_api_keys: Vec::new(),
+        viewer_api_keys: Vec::new(),
+        auth: None,
+        status_page: None,
+        base_path: None,
+        grpc: None,
+        persistence_failure: config.persistence_failure,
+        groups,
+        probe_interval_secs: config.probe_interval_secs,
+        stagger_probes: config.stagger_probes,
+        threshold: config.threshold,
+        threshold_up: config.threshold_up,
+        threshold_down: config.threshold_down,
+        maintenance: config.maintenance.clone(),
+        grace_period_secs: config.grace_period_secs,
+        downsample: config.downsample.clone(),
+    }
 }
 
-async fn get_config(State(state): State<AppState>) -> Json<AppConfig> {
-    Json(state.config_rx.borrow().clone())
+/// 校验候选配置但不保存，返回结构化的错误与警告，供配置页面在提交前提示
+async fn validate_config(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Json(raw): Json<serde_json::Value>,
+) -> Json<crate::validate::ValidationReport> {
+    let (mut report, config) = crate::validate::validate(raw).await;
+    // 与保存时相同：租户不能提交其他命名空间的目标 id
+    if let (Access::Namespace(_), Some(config)) = (&access, &config) {
+        let current = state.config_rx.borrow();
+        for (i, target) in config.targets.iter().enumerate() {
+            if current
+                .targets
+                .iter()
+                .any(|old| old.id == target.id && !access.can_see(old))
+            {
+                report.error(
+                    format!("targets[{}].id", i),
+                    format!("Target id {} belongs to another namespace", target.id),
+                );
+            }
+        }
+        report.valid = report.errors.is_empty();
+    }
+    Json(report)
 }
 
 async fn update_config(
     State(state): State<AppState>,
+    Editor(access): Editor,
+    headers: header::HeaderMap,
     Json(mut new_config): Json<AppConfig>,
-) -> Json<serde_json::Value> {
+) -> impl IntoResponse {
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    if let Some(response) = check_if_match(&state, &access, &headers) {
+        return response;
+    }
+    // 租户提交的是 scoped_config 视图，合并回完整配置，其余部分保持不变
+    if let Access::Namespace(ns) = &access {
+        let mut merged = state.config_rx.borrow().clone();
+        let foreign = new_config.targets.iter().find(|t| {
+            merged
+                .targets
+                .iter()
+                .any(|old| old.id == t.id && !access.can_see(old))
+        });
+        if let Some(t) = foreign {
+            return Json(serde_json::json!({
+                "success": false,
+                "error": format!("Target id {} belongs to another namespace", t.id)
+            }))
+            .into_response();
+        }
+        // 管理员分配给该命名空间的 EXEC 目标可以原样提交，但不能新增或修改命令
+        let exec = new_config.targets.iter().find(|t| {
+            t.protocol == Protocol::Exec
+                && !merged.targets.iter().any(|old| {
+                    old.id == t.id && old.protocol == Protocol::Exec && old.command == t.command
+                })
+        });
+        if let Some(t) = exec {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "success": false,
+                    "error": format!("Only admins can manage EXEC targets ({})", t.id)
+                })),
+            )
+                .into_response();
+        }
+        // exec 告警通道同样在服务器上执行命令，租户只能原样保留管理员配置的通道
+        let current_alert = merged
+            .namespaces
+            .iter()
+            .find(|n| &n.name == ns)
+            .and_then(|n| n.alert.as_ref());
+        let is_exec = |w: &WebhookConfig| matches!(w.kind, ChannelKind::Exec);
+        let exec_channel = new_config.alert.webhooks.iter().find(|w| {
+            is_exec(w)
+                && !current_alert.is_some_and(|alert| {
+                    alert
+                        .webhooks
+                        .iter()
+                        .any(|old| old.id == w.id && is_exec(old) && old.command == w.command)
+                })
+        });
+        if let Some(w) = exec_channel {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "success": false,
+                    "error": format!("Only admins can manage exec channels ({})", w.name)
+                })),
+            )
+                .into_response();
+        }
+
+        merged.targets.retain(|t| !access.can_see(t));
+        for mut target in new_config.targets {
+            target.namespace = Some(ns.clone());
+            merged.targets.push(target);
+        }
+        if let Some(entry) = merged.namespaces.iter_mut().find(|n| &n.name == ns) {
+            // 未配置 webhook 时该命名空间不发送告警 (不会回落到全局告警通道)
+            entry.alert = if new_config.alert.webhooks.is_empty() {
+                None
+            } else {
+                Some(new_config.alert)
+            };
+        }
+        new_config = merged;
+    }
+    restore_secrets(&mut new_config, &state.config_rx.borrow());
+
+    // 维护模式只通过 /api/maintenance 切换，避免提交旧的配置页面时把它覆盖掉
+    new_config.maintenance = state.config_rx.borrow().maintenance.clone();
+
     // 0. Preserve last_known_state from memory
     // Because frontend might send null or outdated states (since it only fetches config once).
     // We should trust our in-memory status map (which has the latest probe results).
@@ -124,13 +1260,1895 @@ async fn update_config(
         }
     }
 
+    // 新增的目标记录添加时间，用于告警宽限期；提交的添加时间晚于当前时间时按当前时间处理，
+    // 否则宽限期与 PUSH 目标等待首个心跳的时间会被推迟到将来
+    {
+        let current_config = state.config_rx.borrow();
+        let now = chrono::Local::now();
+        for target in &mut new_config.targets {
+            if target.created_at.is_none()
+                && !current_config.targets.iter().any(|t| t.id == target.id)
+            {
+                target.created_at = Some(now);
+            }
+            if target.created_at.is_some_and(|c| c > now) {
+                target.created_at = Some(now);
+            }
+            if target.push_token.is_none() {
+                target.push_token = current_config
+                    .targets
+                    .iter()
+                    .find(|t| t.id == target.id)
+                    .and_then(|t| t.push_token.clone());
+            }
+            crate::push::ensure_token(target);
+        }
+    }
+
     // 1. 保存到文件
     if let Err(e) = crate::config::save_config(&new_config) {
-        return Json(serde_json::json!({ "success": false, "error": e.to_string() }));
+        return Json(serde_json::json!({ "success": false, "error": e.to_string() }))
+            .into_response();
     }
 
     // 2. 广播更新 (这会触发 Monitor Loop 重载)
     let _ = state.config_tx.send(new_config);
 
-    Json(serde_json::json!({ "success": true }))
+    // 降级模式下配置只在内存中生效，重启后丢失
+    let persisted = crate::config::is_persisted();
+    // 新的版本号，提交方可以继续带 If-Match 修改而无需重新读取
+    let etag = config_etag(&config_view(&state.config_rx.borrow(), &access));
+    (
+        [(header::ETAG, etag)],
+        Json(serde_json::json!({ "success": true, "persisted": persisted })),
+    )
+        .into_response()
+}
+
+/// 新增单个目标，未指定 `id` 时自动生成；租户创建的目标归属其命名空间
+async fn create_target(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Json(body): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    match insert_target(&state, &access, body).await {
+        Ok((target, persisted)) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "success": true, "persisted": persisted, "target": target })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReorderRequest {
+    ids: Vec<String>,
+}
+
+/// 调整目标顺序 (仪表盘与各接口按配置中的顺序排列)，只改动顺序，不会覆盖其他配置。
+/// 列出的目标按给定顺序排在前面，未列出的保持原有相对顺序排在其后；
+/// 租户只调整本命名空间目标之间的顺序，其他目标的位置不变。支持与 `/api/config` 相同的 If-Match
+async fn reorder_targets(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    headers: header::HeaderMap,
+    Json(req): Json<ReorderRequest>,
+) -> impl IntoResponse {
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    if let Some(response) = check_if_match(&state, &access, &headers) {
+        return response;
+    }
+    let mut new_config = state.config_rx.borrow().clone();
+
+    let mut seen = std::collections::HashSet::new();
+    for id in &req.ids {
+        if !seen.insert(id.as_str()) {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Duplicate target id {}", id),
+            )
+                .into_response();
+        }
+        if !new_config
+            .targets
+            .iter()
+            .any(|t| t.id == *id && access.can_see(t))
+        {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Unknown target id {}", id),
+            )
+                .into_response();
+        }
+    }
+
+    // 可见目标占据的位置不变，按新顺序依次填入
+    let rank = |t: &Target| {
+        req.ids
+            .iter()
+            .position(|id| *id == t.id)
+            .unwrap_or(usize::MAX)
+    };
+    let mut visible: Vec<Target> = new_config
+        .targets
+        .iter()
+        .filter(|t| access.can_see(t))
+        .cloned()
+        .collect();
+    visible.sort_by_key(|t| rank(t));
+    let mut visible = visible.into_iter();
+    for slot in new_config.targets.iter_mut() {
+        if access.can_see(slot) {
+            if let Some(target) = visible.next() {
+                *slot = target;
+            }
+        }
+    }
+
+    let order: Vec<String> = new_config
+        .targets
+        .iter()
+        .filter(|t| access.can_see(t))
+        .map(|t| t.id.clone())
+        .collect();
+    match commit_config(&state, new_config) {
+        Ok(persisted) => {
+            let etag = config_etag(&config_view(&state.config_rx.borrow(), &access));
+            (
+                [(header::ETAG, etag)],
+                Json(serde_json::json!({ "success": true, "persisted": persisted, "ids": order })),
+            )
+                .into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 整体替换单个目标的配置 (ID 以路径为准)，添加时间、当前状态与所属命名空间保持不变。
+/// 支持与 `/api/config` 相同的 If-Match
+async fn replace_target(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Path(id): Path<String>,
+    headers: header::HeaderMap,
+    Json(body): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    match update_target(&state, &access, &id, &headers, body).await {
+        Ok((target, persisted)) => {
+            let etag = config_etag(&config_view(&state.config_rx.borrow(), &access));
+            (
+                [(header::ETAG, etag)],
+                Json(
+                    serde_json::json!({ "success": true, "persisted": persisted, "target": target }),
+                ),
+            )
+                .into_response()
+        }
+        Err((StatusCode::PRECONDITION_FAILED, _)) => precondition_failed(&state, &access),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 删除单个目标，其历史记录随之从内存与缓存中移除。支持与 `/api/config` 相同的 If-Match
+async fn delete_target(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Path(id): Path<String>,
+    headers: header::HeaderMap,
+) -> impl IntoResponse {
+    match remove_target(&state, &access, &id, &headers).await {
+        Ok(persisted) => {
+            let etag = config_etag(&config_view(&state.config_rx.borrow(), &access));
+            (
+                [(header::ETAG, etag)],
+                Json(serde_json::json!({ "success": true, "persisted": persisted })),
+            )
+                .into_response()
+        }
+        Err((StatusCode::PRECONDITION_FAILED, _)) => precondition_failed(&state, &access),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 复制目标：使用新的 ID，名称加上 ` (copy)`，协议与各项参数保持不变。
+/// PUSH 目标会生成新的令牌，历史记录与当前状态不复制
+async fn clone_target(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let source = state
+        .config_rx
+        .borrow()
+        .targets
+        .iter()
+        .find(|t| t.id == id && access.can_see(t))
+        .cloned();
+    let Some(mut target) = source else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+    target.id = String::new();
+    target.name = format!("{} (copy)", target.name);
+    target.push_token = None;
+    let body = serde_json::to_value(&target).unwrap_or_default();
+
+    match insert_target(&state, &access, body).await {
+        Ok((target, persisted)) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "success": true, "persisted": persisted, "target": target })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 目标增删改失败时的状态码与说明，gRPC 接口映射为对应的状态
+pub(crate) type TargetError = (StatusCode, String);
+
+// EXEC 目标在服务器上执行命令，只有管理员可以创建或修改
+fn check_exec_allowed(access: &Access, target: &Target) -> Result<(), TargetError> {
+    if target.protocol == Protocol::Exec && !matches!(access, Access::Admin) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only admins can manage EXEC targets".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 新增目标，返回保存后的目标与是否已写入磁盘
+pub(crate) async fn insert_target(
+    state: &AppState,
+    access: &Access,
+    mut body: serde_json::Value,
+) -> Result<(Target, bool), TargetError> {
+    if let Some(obj) = body.as_object_mut() {
+        if obj
+            .get("id")
+            .and_then(|v| v.as_str())
+            .is_none_or(str::is_empty)
+        {
+            obj.insert(
+                "id".to_string(),
+                format!("t_{}", uuid::Uuid::new_v4().simple()).into(),
+            );
+        }
+    }
+    let mut target: Target = serde_json::from_value(body)
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    check_exec_allowed(access, &target)?;
+    if let Access::Namespace(ns) = access {
+        target.namespace = Some(ns.clone());
+    }
+    target.created_at = Some(chrono::Local::now());
+    target.last_known_state = None;
+    crate::push::ensure_token(&mut target);
+
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    let mut new_config = state.config_rx.borrow().clone();
+    if new_config.targets.iter().any(|t| t.id == target.id) {
+        return Err((StatusCode::CONFLICT, "Target id already exists".to_string()));
+    }
+    new_config.targets.push(target.clone());
+    let persisted = commit_config(state, new_config)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((target, persisted))
+}
+
+/// 整体替换目标 `id` 的配置，返回保存后的目标与是否已写入磁盘。
+/// `headers` 带 If-Match 且配置已被他人修改时返回 412
+pub(crate) async fn update_target(
+    state: &AppState,
+    access: &Access,
+    id: &str,
+    headers: &header::HeaderMap,
+    mut body: serde_json::Value,
+) -> Result<(Target, bool), TargetError> {
+    if let Some(obj) = body.as_object_mut() {
+        obj.insert("id".to_string(), id.into());
+    }
+    let mut target: Target = serde_json::from_value(body)
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    check_exec_allowed(access, &target)?;
+
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    if if_match_failed(state, access, headers) {
+        return Err((
+            StatusCode::PRECONDITION_FAILED,
+            PRECONDITION_FAILED_MESSAGE.to_string(),
+        ));
+    }
+    let mut new_config = state.config_rx.borrow().clone();
+    let Some(slot) = new_config
+        .targets
+        .iter_mut()
+        .find(|t| t.id == id && access.can_see(t))
+    else {
+        return Err((StatusCode::NOT_FOUND, "Target not found".to_string()));
+    };
+    if !matches!(access, Access::Admin) {
+        target.namespace = slot.namespace.clone();
+    }
+    target.created_at = slot.created_at;
+    // 未提交令牌时沿用原令牌，避免被监控方的心跳地址失效
+    if target.push_token.is_none() {
+        target.push_token = slot.push_token.clone();
+    }
+    crate::push::ensure_token(&mut target);
+    target.last_known_state = state
+        .status_map
+        .get(id)
+        .map(|e| e.value().current_state)
+        .or(slot.last_known_state);
+    *slot = target.clone();
+
+    let persisted = commit_config(state, new_config)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    DAILY_CACHE.retain(|key, _| key.0 != id);
+    SUMMARY_CACHE.remove(id);
+    Ok((target, persisted))
+}
+
+/// 删除目标 `id`，返回是否已写入磁盘。`headers` 带 If-Match 且配置已被他人修改时返回 412
+pub(crate) async fn remove_target(
+    state: &AppState,
+    access: &Access,
+    id: &str,
+    headers: &header::HeaderMap,
+) -> Result<bool, TargetError> {
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    if if_match_failed(state, access, headers) {
+        return Err((
+            StatusCode::PRECONDITION_FAILED,
+            PRECONDITION_FAILED_MESSAGE.to_string(),
+        ));
+    }
+    let mut new_config = state.config_rx.borrow().clone();
+    let before = new_config.targets.len();
+    new_config
+        .targets
+        .retain(|t| !(t.id == id && access.can_see(t)));
+    if new_config.targets.len() == before {
+        return Err((StatusCode::NOT_FOUND, "Target not found".to_string()));
+    }
+
+    let persisted = commit_config(state, new_config)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    DAILY_CACHE.retain(|key, _| key.0 != id);
+    SUMMARY_CACHE.remove(id);
+    Ok(persisted)
+}
+
+/// 保存并广播新配置 (监控引擎随之重载)，返回是否已写入磁盘。调用方需持有 `UPDATE_LOCK`
+fn commit_config(state: &AppState, new_config: AppConfig) -> anyhow::Result<bool> {
+    crate::config::save_config(&new_config)?;
+    let _ = state.config_tx.send(new_config);
+    Ok(crate::config::is_persisted())
+}
+
+#[derive(Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+    reason: Option<String>,
+    // 持续时间，为空表示直到手动关闭
+    duration_minutes: Option<i64>,
+}
+
+async fn get_maintenance(State(state): State<AppState>, _access: Access) -> Json<Maintenance> {
+    Json(state.config_rx.borrow().maintenance.clone())
+}
+
+// 维护模式影响全部目标，仅管理员可切换
+async fn update_maintenance(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Json(req): Json<MaintenanceRequest>,
+) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+
+    let now = chrono::Local::now();
+    // 过大的时长无法表示为结束时间，拒绝而不是溢出
+    let until = match req.duration_minutes.filter(|m| *m > 0) {
+        None => None,
+        Some(m) => match chrono::Duration::try_minutes(m).and_then(|d| now.checked_add_signed(d)) {
+            Some(until) => Some(until),
+            None => {
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "duration_minutes is out of range",
+                )
+                    .into_response()
+            }
+        },
+    };
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    let mut new_config = state.config_rx.borrow().clone();
+    new_config.maintenance = if req.enabled {
+        Maintenance {
+            enabled: true,
+            reason: req.reason.filter(|r| !r.is_empty()),
+            since: Some(now),
+            until,
+        }
+    } else {
+        Maintenance::default()
+    };
+    tracing::info!(
+        "Maintenance mode {}",
+        if req.enabled { "enabled" } else { "disabled" }
+    );
+
+    if let Err(e) = crate::config::save_config(&new_config) {
+        return Json(serde_json::json!({ "success": false, "error": e.to_string() }))
+            .into_response();
+    }
+    let maintenance = new_config.maintenance.clone();
+    let _ = state.config_tx.send(new_config);
+    Json(serde_json::json!({ "success": true, "maintenance": maintenance })).into_response()
+}
+
+#[derive(Deserialize)]
+struct AckRequest {
+    user: Option<String>,
+    note: Option<String>,
+}
+
+/// 确认进行中的故障 (`id` 见状态中的 `incident.id`)：该故障恢复前的后续告警不再发送，
+/// 确认信息随 `status` 事件推送。登录用户以会话为准，使用 API Key 时需在请求体中提供 `user`
+async fn ack_incident(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    headers: header::HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<AckRequest>,
+) -> impl IntoResponse {
+    let user = session_token(&headers)
+        .and_then(crate::auth::session_user)
+        .or_else(|| {
+            req.user
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty())
+        });
+    let Some(user) = user else {
+        return (StatusCode::UNPROCESSABLE_ENTITY, "user is required").into_response();
+    };
+    let target_id = state
+        .status_map
+        .iter()
+        .find(|e| {
+            e.value().incident.as_ref().is_some_and(|i| i.id == id)
+                && access.can_see(&e.value().target)
+        })
+        .map(|e| e.key().clone());
+    let Some(mut entry) = target_id.and_then(|t| state.status_map.get_mut(&t)) else {
+        return (StatusCode::NOT_FOUND, "Incident not found").into_response();
+    };
+    // 查找与取得写锁之间故障可能已恢复
+    let status = entry.value_mut();
+    let Some(incident) = status.incident.as_mut().filter(|i| i.id == id) else {
+        return (StatusCode::NOT_FOUND, "Incident not found").into_response();
+    };
+    if incident.acknowledged.is_some() {
+        return (StatusCode::CONFLICT, "Incident already acknowledged").into_response();
+    }
+    incident.acknowledged = Some(crate::model::Acknowledgement {
+        by: user,
+        note: req.note.filter(|n| !n.trim().is_empty()),
+        at: chrono::Local::now(),
+    });
+    let incident = incident.clone();
+    tracing::info!(
+        "Incident {} of {} acknowledged by {}",
+        incident.id,
+        status.target.name,
+        incident.acknowledged.as_ref().map_or("", |a| a.by.as_str())
+    );
+    state
+        .live
+        .send(LiveUpdate::Snapshot(Arc::new(entry.value().clone())));
+
+    Json(serde_json::json!({ "success": true, "incident": incident })).into_response()
+}
+
+/// 公开状态页数据 (无需鉴权)：只含 `public` 目标的名称、分组、当前状态与可用率，
+/// 不包含地址、探测详情与错误信息
+async fn get_public_status(State(state): State<AppState>) -> impl IntoResponse {
+    let config = state.config_rx.borrow().clone();
+    let Some(page) = &config.status_page else {
+        return (StatusCode::NOT_FOUND, "Status page is not enabled").into_response();
+    };
+    let days = page.days.clamp(1, MAX_DAILY_DAYS);
+    let now = chrono::Local::now();
+
+    let mut overall = TargetState::Up;
+    let targets: Vec<serde_json::Value> = config
+        .targets
+        .iter()
+        .filter(|t| t.public && t.enabled)
+        .filter_map(|t| {
+            let entry = state.status_map.get(&t.id)?;
+            let status = entry.value();
+            overall = overall.min(status.current_state);
+            Some(serde_json::json!({
+                "id": t.id,
+                "name": t.name,
+                "group": t.group,
+                "state": status.current_state,
+                "uptime_24h": status.uptime_24h,
+                "days": cached_daily(status, days, config.interval_for(t)).as_ref(),
+            }))
+        })
+        .collect();
+    let maintenance = config
+        .maintenance
+        .is_active(now)
+        .then(|| serde_json::json!({ "reason": config.maintenance.reason, "until": config.maintenance.until }));
+
+    (
+        [(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")],
+        Json(serde_json::json!({
+            "title": page.title,
+            "description": page.description,
+            "updated_at": now,
+            "state": overall,
+            "maintenance": maintenance,
+            "targets": targets,
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct PushQuery {
+    // `down` 表示被监控方自行报告失败
+    status: Option<String>,
+    msg: Option<String>,
+    ping: Option<f32>,
+}
+
+/// PUSH 目标的心跳地址 (无需鉴权，令牌即凭据)，可附带 `?status=down&msg=...&ping=...`
+async fn push_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<PushQuery>,
+) -> impl IntoResponse {
+    let target_id = {
+        let config = state.config_rx.borrow();
+        crate::push::find_target(&config.targets, &token).map(|t| t.id.clone())
+    };
+    let Some(target_id) = target_id else {
+        return (StatusCode::NOT_FOUND, "Unknown push token").into_response();
+    };
+    crate::push::record(
+        &target_id,
+        crate::push::Push {
+            received: chrono::Local::now(),
+            success: !query.status.is_some_and(|s| s.eq_ignore_ascii_case("down")),
+            message: query.msg.filter(|m| !m.is_empty()),
+            latency_ms: query.ping.filter(|p| p.is_finite() && *p >= 0.0),
+        },
+    );
+    Json(serde_json::json!({ "ok": true })).into_response()
+}
+
+async fn status_page_handler(State(state): State<AppState>) -> impl IntoResponse {
+    if state.config_rx.borrow().status_page.is_none() {
+        return (StatusCode::NOT_FOUND, "404 Not Found").into_response();
+    }
+    static_handler(State(state), Uri::from_static("/status.html"))
+        .await
+        .into_response()
+}
+
+/// 嵌入组件 `/embed/<目标 ID 或分组名>`：单行的自包含页面，供其他内网页面以 iframe 引用，
+/// 数据来自 SSE，鉴权与仪表盘相同
+async fn embed_handler(State(state): State<AppState>) -> impl IntoResponse {
+    static_handler(State(state), Uri::from_static("/embed.html"))
+        .await
+        .into_response()
+}
+
+// 不需要鉴权，供负载均衡 / 容器健康检查使用；降级时仍返回 200 (服务可用，只是不持久化)
+async fn healthz() -> Json<serde_json::Value> {
+    let persistence = crate::config::persistence_status();
+    let status = if persistence.degraded {
+        "degraded"
+    } else {
+        "ok"
+    };
+    Json(serde_json::json!({ "status": status, "persistence": persistence }))
+}
+
+async fn get_system_status(
+    State(state): State<AppState>,
+    access: Access,
+) -> Json<serde_json::Value> {
+    let visible = state
+        .status_map
+        .iter()
+        .filter(|e| access.can_see(&e.value().target))
+        .count();
+    Json(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "targets": visible,
+        "persistence": crate::config::persistence_status(),
+        "cache": crate::cache::integrity_report(),
+        "runtimes": crate::runtime::snapshot(),
+        "alert_queue": crate::alert::queue_status(),
+    }))
+}
+
+#[derive(Deserialize)]
+struct StatusQuery {
+    group: Option<String>,
+    // 以下两项可用逗号分隔多个值
+    protocol: Option<String>,
+    state: Option<String>,
+    q: Option<String>,
+    records: Option<usize>,
+}
+
+// 逗号分隔的枚举值列表，按 serde 名称解析
+fn parse_list<T: serde::de::DeserializeOwned>(
+    value: &str,
+    case: fn(&str) -> String,
+) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            serde_json::from_value(serde_json::Value::String(case(v)))
+                .map_err(|_| format!("Unknown value {}", v))
+        })
+        .collect()
+}
+
+/// 按条件过滤的目标状态列表，格式与实时推送的 `init` 相同，
+/// 目标较多时前端或脚本只取需要的部分：`?group=&protocol=&state=down&q=&records=`
+async fn get_status(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<StatusQuery>,
+) -> impl IntoResponse {
+    let states: Vec<TargetState> = match query
+        .state
+        .as_deref()
+        .map(|v| parse_list(v, str::to_lowercase))
+    {
+        Some(Ok(states)) => states,
+        Some(Err(e)) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        None => Vec::new(),
+    };
+    let protocols: Vec<Protocol> = match query
+        .protocol
+        .as_deref()
+        .map(|v| parse_list(v, str::to_uppercase))
+    {
+        Some(Ok(protocols)) => protocols,
+        Some(Err(e)) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        None => Vec::new(),
+    };
+    let group = query.group.filter(|g| !g.is_empty());
+    let q = query
+        .q
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+    // 请求中的条数 (含表示全部的 0) 不超过 MAX_RECORDS_LIMIT，避免一次请求复制全部历史
+    let limit = match query.records {
+        Some(0) => MAX_RECORDS_LIMIT,
+        Some(n) => n.min(MAX_RECORDS_LIMIT),
+        None => state.config_rx.borrow().live_init_records,
+    };
+
+    let list = status_snapshot(&state, &access, limit, |status| {
+        let target = &status.target;
+        (states.is_empty() || states.contains(&status.current_state))
+            && (protocols.is_empty() || protocols.contains(&target.protocol))
+            && group.as_deref().is_none_or(|g| {
+                target
+                    .group
+                    .as_deref()
+                    .is_some_and(|tg| tg.eq_ignore_ascii_case(g))
+            })
+            && q.as_deref().is_none_or(|q| {
+                [&target.name, &target.id, &target.host]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(q))
+            })
+    });
+    // 供脚本与 Home Assistant 等定时轮询，禁止中间代理缓存旧状态
+    ([(header::CACHE_CONTROL, "no-store")], Json(list)).into_response()
+}
+
+const SUMMARY_WINDOWS: [(&str, chrono::Duration); 3] = [
+    ("24h", chrono::Duration::hours(24)),
+    ("7d", chrono::Duration::days(7)),
+    ("30d", chrono::Duration::days(30)),
+];
+
+#[derive(Deserialize)]
+struct SummaryQuery {
+    worst: Option<usize>,
+    // 表现最差目标的排序窗口：24h (默认)、7d 或 30d
+    window: Option<String>,
+}
+
+/// 总览：各状态的目标数、整体可用率 (各目标可用率的平均值) 与表现最差的目标，
+/// 供大屏等只需一次轻量请求即可渲染总览的场景
+async fn get_summary(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<SummaryQuery>,
+) -> impl IntoResponse {
+    let window = query.window.as_deref().unwrap_or("24h");
+    let Some(rank_by) = SUMMARY_WINDOWS.iter().position(|(name, _)| *name == window) else {
+        return (StatusCode::BAD_REQUEST, "window must be 24h, 7d or 30d").into_response();
+    };
+    let worst_limit = query
+        .worst
+        .unwrap_or(DEFAULT_SUMMARY_WORST)
+        .min(MAX_SUMMARY_WORST);
+    let config = state.config_rx.borrow().clone();
+    let now = chrono::Local::now();
+
+    let (mut up, mut degraded, mut down, mut unreachable, mut disabled) = (0, 0, 0, 0, 0);
+    let mut sums = [(0.0, 0usize); 3];
+    let mut ranked = Vec::new();
+    for target in config.targets.iter().filter(|t| access.can_see(t)) {
+        if !target.enabled {
+            disabled += 1;
+            continue;
+        }
+        let Some(entry) = state.status_map.get(&target.id) else {
+            continue;
+        };
+        let status = entry.value();
+        match status.current_state {
+            TargetState::Up => up += 1,
+            TargetState::Degraded => degraded += 1,
+            TargetState::Down => down += 1,
+        }
+        if status.unreachable {
+            unreachable += 1;
+        }
+        let uptimes = cached_uptimes(status, config.interval_for(target));
+        for (sum, u) in sums.iter_mut().zip(uptimes.iter()) {
+            if let Some(pct) = u.uptime_pct {
+                sum.0 += pct;
+                sum.1 += 1;
+            }
+        }
+        if let Some(pct) = uptimes[rank_by].uptime_pct.filter(|p| *p < 100.0) {
+            ranked.push((pct, target, status.current_state, uptimes));
+        }
+    }
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let overall: serde_json::Map<_, _> = SUMMARY_WINDOWS
+        .iter()
+        .zip(sums)
+        .map(|((name, _), (sum, n))| {
+            let pct = (n > 0).then(|| sum / n as f64);
+            (name.to_string(), serde_json::json!(pct))
+        })
+        .collect();
+    let worst: Vec<_> = ranked
+        .into_iter()
+        .take(worst_limit)
+        .map(|(_, target, current_state, uptimes)| {
+            serde_json::json!({
+                "id": target.id,
+                "name": target.name,
+                "group": target.group,
+                "state": current_state,
+                "uptime": SUMMARY_WINDOWS
+                    .iter()
+                    .zip(uptimes.iter())
+                    .map(|((name, _), u)| (name.to_string(), serde_json::json!(u)))
+                    .collect::<serde_json::Map<_, _>>(),
+            })
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "updated_at": now,
+        "total": up + degraded + down,
+        "up": up,
+        "degraded": degraded,
+        "down": down,
+        "unreachable": unreachable,
+        "disabled": disabled,
+        "maintenance": config.maintenance.is_active(now),
+        "uptime_pct": overall,
+        "worst": worst,
+    }))
+    .into_response()
+}
+
+async fn get_groups(State(state): State<AppState>, access: Access) -> Json<Vec<GroupStatus>> {
+    let config = state.config_rx.borrow();
+    Json(crate::group::rollups(&config, &state.status_map, |t| {
+        access.can_see(t)
+    }))
+}
+
+async fn get_schedule(State(state): State<AppState>, access: Access) -> Json<Vec<ScheduleInfo>> {
+    // 按配置顺序输出，尚未被调度过的目标也列出来
+    let config = state.config_rx.borrow();
+    let list = config
+        .targets
+        .iter()
+        .filter(|t| access.can_see(t))
+        .map(|t| match state.schedule.get(&t.id) {
+            Some(info) => ScheduleInfo {
+                target_name: t.name.clone(),
+                ..info.value().clone()
+            },
+            None => ScheduleInfo {
+                target_id: t.id.clone(),
+                target_name: t.name.clone(),
+                ..Default::default()
+            },
+        })
+        .collect();
+    Json(list)
+}
+
+#[derive(Deserialize)]
+struct UptimeQuery {
+    window: Option<String>,
+}
+
+/// 解析 `30m` / `24h` / `7d` 形式的时间窗口
+/// 格式错误或超出范围时为 None (调用方返回 400)，不会因用户输入 panic
+fn parse_window(window: &str) -> Option<chrono::Duration> {
+    // 按字符切分，末尾为多字节字符时不会切在字符中间
+    let (split, _) = window.char_indices().last()?;
+    let (value, unit) = window.split_at(split);
+    let value: i64 = value.parse().ok().filter(|v| *v > 0)?;
+    let window = match unit {
+        "m" => chrono::Duration::try_minutes(value)?,
+        "h" => chrono::Duration::try_hours(value)?,
+        "d" => chrono::Duration::try_days(value)?,
+        _ => return None,
+    };
+    (window <= chrono::Duration::days(MAX_DAILY_DAYS as i64)).then_some(window)
+}
+
+async fn get_uptime(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<UptimeQuery>,
+) -> impl IntoResponse {
+    let window_str = query.window.unwrap_or_else(|| "24h".to_string());
+    // window=all 使用持久化的累计计数，不受记录保留策略限制
+    let window = match window_str.as_str() {
+        "all" => None,
+        s => match parse_window(s) {
+            Some(window) => Some(window),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "Invalid window, expected e.g. 24h, 7d, 30d or all",
+                )
+                    .into_response()
+            }
+        },
+    };
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+
+    let (result, since) = match window {
+        Some(window) => {
+            let interval = state.config_rx.borrow().interval_for(&entry.value().target);
+            let now = chrono::Local::now();
+            let result = uptime(&entry.value().records, window, now, interval);
+            (result, Some(now - window))
+        }
+        None => (
+            cumulative(&entry.value().totals),
+            entry.value().totals.since,
+        ),
+    };
+    (
+        [(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")],
+        Json(serde_json::json!({
+            "target_id": id,
+            "target_name": entry.value().target.name,
+            "window": window_str,
+            "since": since,
+            "uptime_pct": result.uptime_pct,
+            "avg_latency_ms": result.avg_latency_ms,
+            "downtime_minutes": result.downtime_minutes,
+            "probes": result.probes,
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct TargetDetailQuery {
+    // 附带的最近记录条数
+    records: Option<usize>,
+}
+
+/// 单个目标的概览：配置、当前状态与调度、最近记录、各时间窗口的可用率与最近一次故障
+async fn get_target(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<TargetDetailQuery>,
+) -> impl IntoResponse {
+    let limit = query
+        .records
+        .unwrap_or(DEFAULT_DETAIL_RECORDS)
+        .min(MAX_RECORDS_LIMIT);
+    let now = chrono::Local::now();
+    let mut detail = {
+        let Some(entry) = state
+            .status_map
+            .get(&id)
+            .filter(|e| access.can_see(&e.value().target))
+        else {
+            return (StatusCode::NOT_FOUND, "Target not found").into_response();
+        };
+        let status = entry.value();
+        let mut target = status.target.clone();
+        if access.read_only() {
+            redact_target(&mut target);
+        }
+        let interval = state.config_rx.borrow().interval_for(&status.target);
+        let mut windows = serde_json::Map::new();
+        for (name, window) in [
+            ("24h", chrono::Duration::hours(24)),
+            ("7d", chrono::Duration::days(7)),
+            ("30d", chrono::Duration::days(30)),
+        ] {
+            let result = uptime(&status.records, window, now, interval);
+            windows.insert(name.to_string(), serde_json::json!(result));
+        }
+        windows.insert(
+            "all".to_string(),
+            serde_json::json!(cumulative(&status.totals)),
+        );
+
+        serde_json::json!({
+            "target": target,
+            "state": status.current_state,
+            "unreachable": status.unreachable,
+            "schedule": state.schedule.get(&id).map(|s| s.value().clone()),
+            "uptime": windows,
+            "latency_ms": latency_stats(&status.records, chrono::Duration::hours(24), now),
+            "records": status.records.iter().take(limit).collect::<Vec<_>>(),
+        })
+    };
+
+    // 最近一次故障来自状态切换事件日志，读取文件时不持有 status_map 的锁
+    let target_id = id.clone();
+    let events =
+        tokio::task::spawn_blocking(move || crate::events::read(|e| e.target_id == target_id))
+            .await;
+    let events = match events {
+        Ok(Ok(events)) => events,
+        Ok(Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let events: Vec<_> = events.iter().collect();
+    let last_incident =
+        crate::sla::incidents(&events, chrono::DateTime::UNIX_EPOCH.into(), now).pop();
+    detail["last_incident"] = serde_json::json!(last_incident);
+    Json(detail).into_response()
+}
+
+async fn export_history(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+    let export = HistoryExport {
+        version: HISTORY_VERSION,
+        target_id: id.clone(),
+        exported_at: chrono::Local::now(),
+        records: entry.value().records.iter().cloned().collect(),
+    };
+    drop(entry);
+    (
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}-history.json\"", file_stem(&id)),
+        )],
+        Json(export),
+    )
+        .into_response()
+}
+
+/// 导入其他实例导出的历史，与现有记录按时间戳合并 (已有的记录优先)
+async fn import_history(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Path(id): Path<String>,
+    Json(import): Json<HistoryExport>,
+) -> impl IntoResponse {
+    if import.version != HISTORY_VERSION {
+        return (StatusCode::BAD_REQUEST, "Unsupported history version").into_response();
+    }
+    let config = state.config_rx.borrow().clone();
+    let Some(mut entry) = state
+        .status_map
+        .get_mut(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+
+    let now = chrono::Local::now();
+    let status = entry.value_mut();
+    let received = import.records.len();
+    let imported = merge_history(
+        &mut status.records,
+        import.records,
+        config.retention_cutoff(now),
+        config.record_limit(&status.target),
+    );
+    status.uptime_24h = uptime(
+        &status.records,
+        chrono::Duration::hours(24),
+        now,
+        config.interval_for(&status.target),
+    )
+    .uptime_pct;
+    tracing::info!(
+        "Imported {} of {} history records for {}",
+        imported,
+        received,
+        status.target.name
+    );
+    state
+        .live
+        .send(LiveUpdate::Snapshot(Arc::new(entry.value().clone())));
+    drop(entry);
+    DAILY_CACHE.retain(|key, _| key.0 != id);
+    SUMMARY_CACHE.remove(&id);
+
+    Json(serde_json::json!({
+        "success": true,
+        "received": received,
+        "imported": imported,
+    }))
+    .into_response()
+}
+
+/// 下载完整备份 (配置 + 所有目标的状态与历史 + 状态切换事件)，包含密钥，仅管理员可用
+async fn get_backup(State(state): State<AppState>, access: Access) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    let config = state.config_rx.borrow().clone();
+    let status_map = state.status_map.clone();
+    let snapshot =
+        match tokio::task::spawn_blocking(move || Snapshot::capture(&status_map, config)).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    (
+        [(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"netwatch-backup-{}.json\"",
+                snapshot.created_at.format("%Y%m%d-%H%M%S")
+            ),
+        )],
+        Json(snapshot),
+    )
+        .into_response()
+}
+
+/// 从磁盘 (环境变量模式下为环境变量) 重新读取配置并立即生效，用于在服务器上直接修改配置文件后无需重启。
+/// 监听地址、HTTPS、子路径与 gRPC 等启动参数仍需重启，`restart_required` 表示这些设置有变化
+async fn admin_reload(State(state): State<AppState>, Editor(access): Editor) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    let mut config = match crate::config::load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({ "success": false, "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+    if crate::push::ensure_tokens(&mut config) {
+        if let Err(e) = crate::config::save_config(&config) {
+            tracing::error!("Failed to save generated push tokens: {}", e);
+        }
+    }
+
+    let restart_required = {
+        let current = state.config_rx.borrow();
+        let startup = |c: &AppConfig| {
+            serde_json::json!([crate::config::base_path(c), c.grpc, c.dns_cache_size])
+        };
+        startup(&current) != startup(&config)
+    };
+    let targets = config.targets.len();
+    let _ = state.config_tx.send(config);
+    DAILY_CACHE.clear();
+    SUMMARY_CACHE.clear();
+    tracing::info!("Configuration reloaded from disk ({} targets)", targets);
+    Json(serde_json::json!({
+        "success": true,
+        "targets": targets,
+        "restart_required": restart_required,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    // 最低级别，如 `warn`；只能在 RUST_LOG 已输出的日志中进一步筛选
+    level: Option<String>,
+    // 连接时先回放的最近日志条数
+    backlog: Option<usize>,
+}
+
+/// 以 SSE 实时推送本进程的日志 (`log` 事件，JSON 为 `timestamp`、`level`、`target`、`message`)，
+/// 便于在无法登录服务器时排查探测失败
+async fn stream_logs(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<LogsQuery>,
+) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    let min_level = match query
+        .level
+        .as_deref()
+        .map(str::parse::<tracing::level_filters::LevelFilter>)
+    {
+        Some(Ok(level)) => level,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, "Invalid level").into_response(),
+        None => tracing::level_filters::LevelFilter::TRACE,
+    };
+    let wanted = move |line: &crate::logs::LogLine| min_level >= line.severity;
+    let to_event = |line: &crate::logs::LogLine| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .event("log")
+                .data(serde_json::to_string(line).unwrap_or_default()),
+        )
+    };
+
+    let (recent, rx) = crate::logs::subscribe();
+    let skip = recent.len().saturating_sub(query.backlog.unwrap_or(100));
+    let backlog: Vec<_> = recent
+        .iter()
+        .skip(skip)
+        .filter(|l| wanted(l))
+        .map(|l| to_event(l))
+        .collect();
+    let live = tokio_stream::wrappers::BroadcastStream::new(rx).filter_map(move |msg| {
+        let event = match msg {
+            Ok(line) => wanted(&line).then(|| to_event(&line)),
+            Err(_) => Some(Ok(Event::default().event("error").data("stream lagged"))),
+        };
+        futures::future::ready(event)
+    });
+
+    let mut shutdown_rx = state.shutdown_tx.subscribe();
+    let stream = futures::stream::iter(backlog)
+        .chain(live)
+        .take_until(async move {
+            let _ = shutdown_rx.recv().await;
+        });
+    Sse::new(stream)
+        .keep_alive(axum::response::sse::KeepAlive::default())
+        .into_response()
+}
+
+/// 远程触发与 SIGTERM 相同的优雅关闭 (保存缓存后退出)，由进程管理器负责重新拉起
+async fn admin_shutdown(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    tracing::info!("Shutdown requested via API");
+    state.shutdown_request.notify_one();
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "success": true })),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct RestoreQuery {
+    // 同时用备份中的管理员 Key 与登录设置替换本机的，默认保留本机的
+    #[serde(default)]
+    replace_admin_keys: bool,
+}
+
+/// 从 `/api/backup` 的文件恢复：替换配置、目标历史与事件日志。
+/// 本机的对象存储备份设置保持不变，避免把凭据带到另一台机器上；管理员 Key 与登录设置
+/// 默认也保留本机的，`?replace_admin_keys=true` 时才使用备份中的
+async fn restore_backup(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Query(query): Query<RestoreQuery>,
+    Json(snapshot): Json<Snapshot>,
+) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    let Snapshot {
+        created_at,
+        mut config,
+        statuses,
+        events,
+    } = snapshot;
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    {
+        let current = state.config_rx.borrow();
+        config.backup = current.backup.clone();
+        // 旧的或其他机器的备份可能没有当前管理员的 Key 或账号，默认保留，避免执行恢复的人被锁在外面
+        if !query.replace_admin_keys {
+            config.admin_api_keys = current.admin_api_keys.clone();
+            config.auth = current.auth.clone();
+        }
+    }
+    // 备份中未设置令牌的 PUSH 目标与启动、重新加载时一样生成令牌，否则无法接收心跳
+    crate::push::ensure_tokens(&mut config);
+
+    if let Err(e) = crate::config::save_config(&config) {
+        return Json(serde_json::json!({ "success": false, "error": e.to_string() }))
+            .into_response();
+    }
+
+    // 先按新配置准备好目标状态，监控引擎收到新配置后会沿用这些条目
+    let ids: std::collections::HashSet<&str> =
+        config.targets.iter().map(|t| t.id.as_str()).collect();
+    state.status_map.retain(|k, _| ids.contains(k.as_str()));
+    for target in &config.targets {
+        state
+            .status_map
+            .entry(target.id.clone())
+            .or_insert_with(|| MonitorStatus::new(target.clone()));
+    }
+    let restored_statuses = statuses
+        .iter()
+        .filter(|s| ids.contains(s.target.id.as_str()))
+        .count();
+    crate::cache::restore_statuses(&state.status_map, statuses);
+
+    let restored_events = events.len();
+    if !events.is_empty() {
+        match tokio::task::spawn_blocking(move || crate::events::replace(&events)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Failed to restore state events: {}", e),
+            Err(e) => tracing::error!("Failed to restore state events: {}", e),
+        }
+    }
+
+    tracing::info!(
+        "Restored backup created at {} ({} targets, {} statuses, {} events)",
+        created_at,
+        config.targets.len(),
+        restored_statuses,
+        restored_events
+    );
+    let targets = config.targets.len();
+    let _ = state.config_tx.send(config);
+    DAILY_CACHE.clear();
+    SUMMARY_CACHE.clear();
+
+    let persisted = crate::config::is_persisted();
+    Json(serde_json::json!({
+        "success": true,
+        "persisted": persisted,
+        "targets": targets,
+        "statuses": restored_statuses,
+        "events": restored_events,
+    }))
+    .into_response()
+}
+
+/// 导入 Uptime Kuma 备份中的监控项与通知通道，合并到当前配置
+async fn import_kuma(
+    State(state): State<AppState>,
+    Editor(access): Editor,
+    Json(backup): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    if !matches!(access, Access::Admin) {
+        return (StatusCode::FORBIDDEN, "Admin API key required").into_response();
+    }
+    let _guard = crate::config::UPDATE_LOCK.lock().await;
+    let mut new_config = state.config_rx.borrow().clone();
+    let report = match crate::kuma::import(&mut new_config, &backup) {
+        Ok(report) => report,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    tracing::info!(
+        "Imported {} targets and {} webhooks from Uptime Kuma ({} skipped)",
+        report.targets,
+        report.webhooks,
+        report.skipped.len()
+    );
+
+    if let Err(e) = crate::config::save_config(&new_config) {
+        return Json(serde_json::json!({ "success": false, "error": e.to_string() }))
+            .into_response();
+    }
+    let _ = state.config_tx.send(new_config);
+
+    let persisted = crate::config::is_persisted();
+    Json(serde_json::json!({
+        "success": true,
+        "persisted": persisted,
+        "targets": report.targets,
+        "webhooks": report.webhooks,
+        "skipped": report.skipped,
+    }))
+    .into_response()
+}
+
+async fn get_latency(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<UptimeQuery>,
+) -> impl IntoResponse {
+    let window_str = query.window.unwrap_or_else(|| "24h".to_string());
+    let Some(window) = parse_window(&window_str) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Invalid window, expected e.g. 24h, 7d, 30d",
+        )
+            .into_response();
+    };
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+
+    let stats = latency_stats(&entry.value().records, window, chrono::Local::now());
+    Json(serde_json::json!({
+        "target_id": id,
+        "target_name": entry.value().target.name,
+        "window": window_str,
+        "latency_ms": stats,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct ReportQuery {
+    // json (默认) 或 html
+    format: Option<String>,
+    // 只包含指定目标，逗号分隔
+    target: Option<String>,
+}
+
+/// 月度 SLA 报告：每个可见目标的可用率、延迟分布与故障列表
+async fn get_monthly_report(
+    State(state): State<AppState>,
+    access: Access,
+    Path((year, month)): Path<(i32, u32)>,
+    Query(query): Query<ReportQuery>,
+) -> impl IntoResponse {
+    let Some(range) = crate::sla::month_range(year, month) else {
+        return (StatusCode::BAD_REQUEST, "Invalid year or month").into_response();
+    };
+    let now = chrono::Local::now();
+    if range.0 > now {
+        return (StatusCode::BAD_REQUEST, "Month has not started yet").into_response();
+    }
+    let html = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("html") => true,
+        Some(_) => return (StatusCode::BAD_REQUEST, "Invalid format").into_response(),
+    };
+
+    let until = range.1;
+    let events =
+        match tokio::task::spawn_blocking(move || crate::events::read(|e| e.timestamp < until))
+            .await
+        {
+            Ok(Ok(events)) => events,
+            Ok(Err(e)) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            }
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+
+    let config = state.config_rx.borrow().clone();
+    let statuses: Vec<_> = state
+        .status_map
+        .iter()
+        .filter(|e| access.can_see(&e.value().target))
+        .filter(|e| {
+            query
+                .target
+                .as_deref()
+                .is_none_or(|ids| ids.split(',').any(|id| id.trim() == e.key()))
+        })
+        .collect();
+    let report = crate::sla::build(
+        &config,
+        statuses.iter().map(|e| e.value()),
+        &events,
+        range,
+        now,
+    );
+    drop(statuses);
+
+    if html {
+        (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            crate::sla::render_html(&report),
+        )
+            .into_response()
+    } else {
+        Json(report).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct HeatmapQuery {
+    window: Option<String>,
+    bucket: Option<String>,
+    // 逗号分隔的延迟区间上界 (毫秒)，递增
+    bands: Option<String>,
+}
+
+/// 按时间桶 × 延迟区间汇总的探测次数，用于绘制延迟热力图
+async fn get_heatmap(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<HeatmapQuery>,
+) -> impl IntoResponse {
+    let window_str = query.window.unwrap_or_else(|| "24h".to_string());
+    let bucket_str = query.bucket.unwrap_or_else(|| "1h".to_string());
+    let (Some(window), Some(bucket)) = (parse_window(&window_str), parse_window(&bucket_str))
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Invalid window or bucket, expected e.g. 30m, 1h, 7d",
+        )
+            .into_response();
+    };
+    if window.num_seconds() / bucket.num_seconds() > MAX_HEATMAP_BUCKETS {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Too many buckets, use a larger bucket",
+        )
+            .into_response();
+    }
+    let bands = match query.bands.as_deref() {
+        None => DEFAULT_LATENCY_BANDS.to_vec(),
+        Some(s) => {
+            let bands: Option<Vec<f64>> = s
+                .split(',')
+                .map(|b| {
+                    b.trim()
+                        .parse()
+                        .ok()
+                        .filter(|v: &f64| v.is_finite() && *v > 0.0)
+                })
+                .collect();
+            match bands {
+                Some(bands)
+                    if !bands.is_empty()
+                        && bands.len() <= MAX_HEATMAP_BANDS
+                        && bands.windows(2).all(|w| w[0] < w[1]) =>
+                {
+                    bands
+                }
+                _ => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        "Invalid bands, expected increasing millisecond values, e.g. 10,50,100",
+                    )
+                        .into_response()
+                }
+            }
+        }
+    };
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+
+    let buckets = latency_heatmap(
+        &entry.value().records,
+        window,
+        chrono::Local::now(),
+        bucket,
+        &bands,
+    );
+    Json(serde_json::json!({
+        "target_id": id,
+        "target_name": entry.value().target.name,
+        "window": window_str,
+        "bucket_secs": bucket.num_seconds(),
+        "bands": bands,
+        "buckets": buckets,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct ChartQuery {
+    window: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+/// 服务端渲染的延迟折线图 (PNG)，可直接嵌入告警、邮件与聊天消息
+async fn get_chart(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<ChartQuery>,
+) -> impl IntoResponse {
+    let window_str = query.window.unwrap_or_else(|| "24h".to_string());
+    let Some(window) = parse_window(&window_str) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Invalid window, expected e.g. 30m, 24h, 7d",
+        )
+            .into_response();
+    };
+    let size = (
+        query.width.unwrap_or(DEFAULT_CHART_SIZE.0),
+        query.height.unwrap_or(DEFAULT_CHART_SIZE.1),
+    );
+    if !(1..=MAX_CHART_SIZE.0).contains(&size.0) || !(1..=MAX_CHART_SIZE.1).contains(&size.1) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Chart size must be at most {}x{}",
+                MAX_CHART_SIZE.0, MAX_CHART_SIZE.1
+            ),
+        )
+            .into_response();
+    }
+
+    let now = chrono::Local::now();
+    // 只复制窗口内的记录，绘制时不占用状态表
+    let (records, interval) = {
+        let Some(entry) = state
+            .status_map
+            .get(&id)
+            .filter(|e| access.can_see(&e.value().target))
+        else {
+            return (StatusCode::NOT_FOUND, "Target not found").into_response();
+        };
+        let records: std::collections::VecDeque<_> = entry
+            .value()
+            .records
+            .iter()
+            .take_while(|r| r.timestamp >= now - window)
+            .cloned()
+            .collect();
+        let interval = state.config_rx.borrow().interval_for(&entry.value().target);
+        (records, interval)
+    };
+
+    let rendered = tokio::task::spawn_blocking(move || {
+        crate::chart::sparkline_png(&records, window, now, interval, size)
+    })
+    .await;
+    match rendered {
+        Ok(Ok(png)) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                // 图表需鉴权，只允许浏览器缓存，共享代理不得缓存后转给其他用户
+                (header::CACHE_CONTROL, "private, max-age=60"),
+            ],
+            png,
+        )
+            .into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct EventHistoryQuery {
+    target: Option<String>,
+    from: Option<chrono::DateTime<chrono::Local>>,
+    to: Option<chrono::DateTime<chrono::Local>>,
+    limit: Option<usize>,
+}
+
+/// 状态切换事件日志，最新的在前；命名空间只能看到本命名空间现有目标的事件
+async fn get_event_history(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<EventHistoryQuery>,
+) -> impl IntoResponse {
+    let visible = visible_target_ids(&state, &access);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECORDS_LIMIT)
+        .clamp(1, MAX_RECORDS_LIMIT);
+    let result = tokio::task::spawn_blocking(move || {
+        crate::events::read(|e| {
+            visible
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&e.target_id))
+                && query.target.as_ref().is_none_or(|id| *id == e.target_id)
+                && query.from.is_none_or(|from| e.timestamp >= from)
+                && query.to.is_none_or(|to| e.timestamp <= to)
+        })
+    })
+    .await;
+    match result {
+        Ok(Ok(events)) => {
+            let events: Vec<_> = events.into_iter().rev().take(limit).collect();
+            Json(serde_json::json!({ "events": events })).into_response()
+        }
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// 重试耗尽的告警 (死信)，最新的在前；参数与事件日志相同，
+/// 命名空间只能看到本命名空间现有目标的死信
+async fn get_dead_letters(
+    State(state): State<AppState>,
+    access: Access,
+    Query(query): Query<EventHistoryQuery>,
+) -> impl IntoResponse {
+    let visible = visible_target_ids(&state, &access);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECORDS_LIMIT)
+        .clamp(1, MAX_RECORDS_LIMIT);
+    let result = tokio::task::spawn_blocking(move || {
+        crate::deadletter::read(|l| {
+            visible
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&l.target_id))
+                && query.target.as_ref().is_none_or(|id| *id == l.target_id)
+                && query.from.is_none_or(|from| l.timestamp >= from)
+                && query.to.is_none_or(|to| l.timestamp <= to)
+        })
+    })
+    .await;
+    match result {
+        Ok(Ok(letters)) => {
+            let letters: Vec<_> = letters.into_iter().rev().take(limit).collect();
+            Json(serde_json::json!({ "dead_letters": letters })).into_response()
+        }
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// 命名空间可见的目标 id，不限定命名空间时不限制 (None)
+fn visible_target_ids(state: &AppState, access: &Access) -> Option<Vec<String>> {
+    access.namespace()?;
+    Some(
+        state
+            .config_rx
+            .borrow()
+            .targets
+            .iter()
+            .filter(|t| access.can_see(t))
+            .map(|t| t.id.clone())
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+struct RecordsQuery {
+    from: Option<chrono::DateTime<chrono::Local>>,
+    to: Option<chrono::DateTime<chrono::Local>>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+/// 按时间范围分页查询探测记录，最新的在前；`total` 为范围内的记录总数
+async fn get_records(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<RecordsQuery>,
+) -> impl IntoResponse {
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECORDS_LIMIT)
+        .clamp(1, MAX_RECORDS_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+    let in_range = |r: &&ProbeRecord| {
+        query.from.is_none_or(|from| r.timestamp >= from)
+            && query.to.is_none_or(|to| r.timestamp <= to)
+    };
+    let records = &entry.value().records;
+    let total = records.iter().filter(in_range).count();
+    let page: Vec<_> = records
+        .iter()
+        .filter(in_range)
+        .skip(offset)
+        .take(limit)
+        .collect();
+
+    Json(serde_json::json!({
+        "target_id": id,
+        "total": total,
+        "offset": offset,
+        "limit": limit,
+        "records": page,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct RangeQuery {
+    from: Option<chrono::DateTime<chrono::Local>>,
+    to: Option<chrono::DateTime<chrono::Local>>,
+}
+
+/// 按时间范围 (RFC 3339，含两端) 导出探测记录为 CSV，从旧到新排列
+async fn get_records_csv(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(range): Query<RangeQuery>,
+) -> impl IntoResponse {
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+    // 先复制出范围内的记录再逐行输出，避免在传输期间持有 DashMap 的锁
+    let records: Vec<_> = entry
+        .value()
+        .records
+        .iter()
+        .rev()
+        .filter(|r| range.from.is_none_or(|from| r.timestamp >= from))
+        .filter(|r| range.to.is_none_or(|to| r.timestamp <= to))
+        .cloned()
+        .collect();
+    drop(entry);
+
+    let lines = std::iter::once(crate::export::CSV_HEADER.to_string())
+        .chain(records.into_iter().map(|r| crate::export::csv_row(&r)));
+    let stream = futures::stream::iter(lines.map(Ok::<_, Infallible>));
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.csv\"", file_stem(&id)),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+/// 用作下载文件名的目标 ID，非 ASCII 字母数字的字符替换为 `_`
+fn file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct DailyQuery {
+    days: Option<u32>,
+}
+
+fn cached_uptimes(status: &MonitorStatus, interval: Duration) -> Arc<[Uptime; 3]> {
+    let cached = SUMMARY_CACHE
+        .get(&status.target.id)
+        .filter(|c| c.0.elapsed() < DAILY_CACHE_TTL)
+        .map(|c| c.1.clone());
+    if let Some(uptimes) = cached {
+        return uptimes;
+    }
+    let now = chrono::Local::now();
+    let uptimes =
+        Arc::new(SUMMARY_WINDOWS.map(|(_, window)| uptime(&status.records, window, now, interval)));
+    SUMMARY_CACHE.retain(|_, c| c.0.elapsed() < DAILY_CACHE_TTL);
+    SUMMARY_CACHE.insert(status.target.id.clone(), (Instant::now(), uptimes.clone()));
+    uptimes
+}
+
+// 状态页轮询频繁，日汇总在 DAILY_CACHE_TTL 内复用
+fn cached_daily(status: &MonitorStatus, days: u32, interval: Duration) -> Arc<Vec<DailyStatus>> {
+    let key = (status.target.id.clone(), days);
+    let cached = DAILY_CACHE
+        .get(&key)
+        .filter(|c| c.0.elapsed() < DAILY_CACHE_TTL)
+        .map(|c| c.1.clone());
+    if let Some(summary) = cached {
+        return summary;
+    }
+    let summary = Arc::new(daily_summary(
+        &status.records,
+        days,
+        chrono::Local::now(),
+        interval,
+    ));
+    DAILY_CACHE.retain(|_, c| c.0.elapsed() < DAILY_CACHE_TTL);
+    DAILY_CACHE.insert(key, (Instant::now(), summary.clone()));
+    summary
+}
+
+async fn get_daily(
+    State(state): State<AppState>,
+    access: Access,
+    Path(id): Path<String>,
+    Query(query): Query<DailyQuery>,
+) -> impl IntoResponse {
+    let days = query.days.unwrap_or(90).clamp(1, MAX_DAILY_DAYS);
+    let Some(entry) = state
+        .status_map
+        .get(&id)
+        .filter(|e| access.can_see(&e.value().target))
+    else {
+        return (StatusCode::NOT_FOUND, "Target not found").into_response();
+    };
+
+    let interval = state.config_rx.borrow().interval_for(&entry.value().target);
+    let summary = cached_daily(entry.value(), days, interval);
+
+    // 允许其他站点的状态页直接引用
+    (
+        [(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")],
+        Json(serde_json::json!({
+            "target_id": id,
+            "target_name": entry.value().target.name,
+            "days": summary.as_ref(),
+        })),
+    )
+        .into_response()
 }
@@ -1,18 +1,72 @@
 let currentConfig = null;
+// 读取配置时的版本号，保存时通过 If-Match 提交，避免覆盖他人的修改
+let configEtag = null;
 let isSettingsOpen = false;
 let retentionDays = 3; // Default
 let monitorData = [];
+let groupStatus = {}; // 服务端下发的分组汇总，按分组名索引
 let eventSource = null;
 
+// 多租户: 通过 ?api_key=xxx 打开页面时记住 Key，之后的请求都会携带
+const API_KEY = (() => {
+  const fromUrl = new URLSearchParams(location.search).get("api_key");
+  if (fromUrl) localStorage.setItem("netwatch_api_key", fromUrl);
+  return fromUrl || localStorage.getItem("netwatch_api_key") || "";
+})();
+
+function authHeaders(headers = {}) {
+  return API_KEY ? { ...headers, "X-API-Key": API_KEY } : headers;
+}
+
+// 登录认证: 未登录时写操作返回 401，跳转到登录页
+let session = { login_enabled: false, username: null, role: null };
+
+async function loadSession() {
+  try {
+    const res = await fetch("api/session");
+    session = await res.json();
+  } catch (e) {
+    session = { login_enabled: false, username: null, role: null };
+  }
+  const btn = document.getElementById("btn-logout");
+  btn.classList.toggle("hidden", !session.username);
+  btn.title = `Log out (${session.username || ""})`;
+  // 只读用户不显示修改入口 (服务端同样会拒绝)
+  const readOnly = session.role === "viewer";
+  document.getElementById("btn-settings").classList.toggle("hidden", readOnly);
+  document
+    .getElementById("btn-maintenance")
+    .classList.toggle("hidden", readOnly);
+}
+
+function needsLogin() {
+  return session.login_enabled && !session.username && !API_KEY;
+}
+
+function redirectToLogin(res) {
+  if (res.status !== 401 || !session.login_enabled) return false;
+  location.href = "login.html";
+  return true;
+}
+
+async function logout() {
+  await fetch("api/logout", { method: "POST" });
+  location.href = "./";
+}
+
 const DEFAULT_PORTS = {
   TCP: 22,
   DNS: 53,
   HTTP: 80,
   HTTPS: 443,
+  VNC: 5900,
+  SMB: 445,
+  NFS: 2049,
 };
 
 // Init config first to get retention days
 async function init() {
+  await loadSession();
   await loadConfig(false); // Load config silently
   retentionDays = currentConfig?.data_retention_days || 3;
   // Update dropdown "Retention Policy" label
@@ -20,10 +74,81 @@ async function init() {
   if (retentionOpt)
     retentionOpt.text = `Retention Policy (${retentionDays} Days)`;
 
+  loadMaintenance();
   startDashboardUpdates();
 }
 
+// --- Maintenance Mode ---
+let maintenance = null;
+
+async function loadMaintenance() {
+  try {
+    const res = await fetch("api/maintenance", { headers: authHeaders() });
+    maintenance = await res.json();
+  } catch (e) {
+    maintenance = null;
+  }
+  renderMaintenance();
+}
+
+function maintenanceActive() {
+  return (
+    maintenance?.enabled &&
+    (!maintenance.until || new Date(maintenance.until) > new Date())
+  );
+}
+
+function renderMaintenance() {
+  const banner = document.getElementById("maintenance-banner");
+  const btn = document.getElementById("btn-maintenance");
+  const active = maintenanceActive();
+  btn.classList.toggle("active", active);
+  btn.title = active ? "End maintenance" : "Start maintenance";
+  banner.classList.toggle("hidden", !active);
+  if (active) {
+    let text = "Maintenance mode: alerts are suppressed";
+    if (maintenance.reason) text += ` (${maintenance.reason})`;
+    if (maintenance.until)
+      text += ` until ${new Date(maintenance.until).toLocaleString()}`;
+    banner.innerText = text;
+  }
+}
+
+async function toggleMaintenance() {
+  let body = { enabled: false };
+  if (!maintenanceActive()) {
+    const reason = prompt("Maintenance reason (optional):", "");
+    if (reason === null) return;
+    const minutes = prompt("Duration in minutes (empty = until turned off):", "60");
+    if (minutes === null) return;
+    body = {
+      enabled: true,
+      reason: reason,
+      duration_minutes: parseInt(minutes) || null,
+    };
+  }
+  try {
+    const res = await fetch("api/maintenance", {
+      method: "POST",
+      headers: authHeaders({ "Content-Type": "application/json" }),
+      body: JSON.stringify(body),
+    });
+    if (redirectToLogin(res)) return;
+    if (!res.ok) throw new Error(await res.text());
+    const result = await res.json();
+    if (!result.success) throw new Error(result.error);
+    maintenance = result.maintenance;
+    renderMaintenance();
+  } catch (e) {
+    alert("Failed to update maintenance mode: " + e.message);
+  }
+}
+
 function toggleSettings() {
+  if (!isSettingsOpen && needsLogin()) {
+    location.href = "login.html";
+    return;
+  }
   isSettingsOpen = !isSettingsOpen;
   const dash = document.getElementById("view-dashboard");
   const settings = document.getElementById("view-settings");
@@ -48,6 +173,49 @@ function toggleSettings() {
 
 function updateTimeRange() {
   renderDashboard(monitorData); // Refresh view with current data
+  ensureHistory();
+}
+
+// init 只携带每个目标最近的记录，所选时间范围需要更早的数据时通过记录接口按需补齐
+async function ensureHistory() {
+  const rangeVal = document.getElementById("time-range").value;
+  const seconds =
+    rangeVal === "retention" ? retentionDays * 86400 : parseInt(rangeVal) * 10;
+  const since = Date.now() - seconds * 1000;
+  const pending = monitorData.filter((item) => !item.historyComplete);
+  await Promise.all(pending.map((item) => loadOlderRecords(item, since)));
+  if (pending.length) renderDashboard(monitorData);
+}
+
+async function loadOlderRecords(item, since) {
+  while (!item.historyComplete) {
+    const oldest = item.records[item.records.length - 1];
+    if (!oldest) {
+      item.historyComplete = true;
+      return;
+    }
+    const oldestTime = new Date(oldest.timestamp).getTime();
+    if (oldestTime <= since) return;
+    const params = new URLSearchParams({ to: oldest.timestamp, limit: 5000 });
+    try {
+      const res = await fetch(
+        `api/targets/${encodeURIComponent(item.target.id)}/records?${params}`,
+        { headers: authHeaders() },
+      );
+      if (!res.ok) return;
+      const page = await res.json();
+      // `to` 包含边界，去掉已有的最旧记录
+      const older = page.records.filter(
+        (r) => new Date(r.timestamp).getTime() < oldestTime,
+      );
+      item.records.push(...older);
+      if (page.records.length < page.limit || !older.length)
+        item.historyComplete = true;
+    } catch (err) {
+      console.error("Failed to load history", err);
+      return;
+    }
+  }
 }
 
 // --- Dashboard Logic ---
@@ -78,14 +246,35 @@ function renderDashboard(data) {
   // Each bar represents 'secondsPerBar'
   const secondsPerBar = Math.max(10, Math.ceil(totalSeconds / displayBars));
 
-  data.forEach((item) => {
+  let lastGroup = null;
+  orderByGroup(data).forEach((item) => {
+    const group = item.target.group || "";
+    if (group && group !== lastGroup) list.appendChild(renderGroupHeader(group));
+    lastGroup = group;
+
     const card = document.createElement("div");
     card.className = "monitor-card";
-
-    const isUp = item.current_state;
-    const statusClass = isUp ? "up" : "down";
-    const statusText = isUp ? "Online" : "Offline";
-    const statusColor = isUp ? "text-success" : "text-danger";
+    // 告警消息中的链接 (#target-<id>) 定位到该目标
+    card.id = `target-${item.target.id}`;
+    if (focusedCardId() === card.id) card.classList.add("focused");
+
+    const state = item.current_state; // "up" | "degraded" | "down"
+    const paused = item.target.enabled === false;
+    const statusClass = paused ? "" : state;
+    const statusText = paused
+      ? "Paused"
+      : state === "up"
+        ? "Online"
+        : state === "degraded"
+          ? "Degraded"
+          : item.unreachable
+            ? "Unreachable"
+            : "Offline";
+    const statusColor = paused
+      ? ""
+      : { up: "text-success", degraded: "text-warning", down: "text-danger" }[
+          state
+        ];
 
     // --- Aggregation Logic ---
     // item.records[0] is newest.
@@ -137,15 +326,18 @@ function renderDashboard(data) {
         }
       } else {
         // Aggregate
-        const successCount = bucketRecords.filter((r) => r.success).length;
-        const failCount = bucketRecords.length - successCount;
+        const sampleCount = sum(bucketRecords, sampleCountOf);
+        const successCount = sum(bucketRecords, okCountOf);
+        const failCount = sampleCount - successCount;
         const avgLatency =
           bucketRecords.reduce((acc, r) => acc + (r.latency_ms || 0), 0) /
           bucketRecords.length;
 
         let type = "ok";
-        if (failCount === bucketRecords.length) type = "fail";
-        else if (failCount > 0) type = "warning";
+        if (bucketRecords.every((r) => r.maintenance)) type = "maintenance";
+        else if (failCount === sampleCount) type = "fail";
+        else if (failCount > 0 || bucketRecords.some((r) => r.degraded))
+          type = "warning";
 
         barsData.push({
           type: type,
@@ -157,21 +349,26 @@ function renderDashboard(data) {
             avgLatency < 1 && avgLatency > 0
               ? avgLatency.toFixed(2)
               : Math.round(avgLatency),
-          count: bucketRecords.length,
+          count: sampleCount,
           fails: failCount,
+          statusCodes: [
+            ...new Set(
+              bucketRecords.map((r) => r.status_code).filter((c) => c != null),
+            ),
+          ],
         });
       }
     }
 
     // Uptime calc (based on full visible range)
-    const totalRecs = records.length;
-    const totalSuccess = records.filter((r) => r.success).length;
+    const totalRecs = sum(records, sampleCountOf);
+    const totalSuccess = sum(records, okCountOf);
     const uptime =
       totalRecs > 0 ? ((totalSuccess / totalRecs) * 100).toFixed(1) : "0.0";
 
     const protocol = item.target.protocol;
     let targetStr = "";
-    if (protocol === "ICMP") {
+    if (protocol === "ICMP" || protocol === "EXEC" || protocol === "DOMAIN" || protocol === "DNSBL" || protocol === "WATCHDOG" || protocol === "PUSH") {
       targetStr = item.target.host;
     } else if (protocol === "HTTP" || protocol === "HTTPS") {
       // For Web, showing the host is usually enough, or host:port if non-standard
@@ -187,7 +384,9 @@ function renderDashboard(data) {
       .map((b) => {
         if (b.type === "empty") return `<div class="bar-segment empty"></div>`;
 
-        const title = `${b.time}\nAvg Latency: ${b.latency}ms\nSuccess: ${b.count - b.fails}/${b.count}`;
+        let title = `${b.time}\nAvg Latency: ${b.latency}ms\nSuccess: ${b.count - b.fails}/${b.count}`;
+        if (b.statusCodes.length > 0)
+          title += `\nHTTP: ${b.statusCodes.join(", ")}`;
         return `<div class="bar-segment ${b.type}" data-title="${title}"></div>`;
       })
       .join("");
@@ -197,7 +396,7 @@ function renderDashboard(data) {
                 <div class="m-info">
                     <span class="m-name">${item.target.name}</span>
                     <span class="m-target">${targetStr}</span>
-                    <span class="m-meta">| ${protocol} | Uptime: ${uptime}% (${rangeVal === "retention" ? retentionDays + "d" : Math.round(totalSeconds / 3600) + "h"})</span>
+                    <span class="m-meta">| ${protocol} | Uptime: ${uptime}% (${rangeVal === "retention" ? retentionDays + "d" : Math.round(totalSeconds / 3600) + "h"})${item.uptime_24h != null ? ` | 24h: ${item.uptime_24h.toFixed(2)}%` : ""}</span>
                 </div>
                 <div class="m-status ${statusColor}">
                     <span class="status-dot ${statusClass}"></span> ${statusText}
@@ -213,6 +412,52 @@ function renderDashboard(data) {
   renderGlobalEventLog(data);
 }
 
+// 未分组的目标在前，分组按首次出现的顺序排列，组内保持配置顺序
+function orderByGroup(data) {
+  const sections = new Map([["", []]]);
+  data.forEach((item) => {
+    const group = item.target.group || "";
+    if (!sections.has(group)) sections.set(group, []);
+    sections.get(group).push(item);
+  });
+  return [...sections.values()].flat();
+}
+
+function renderGroupHeader(group) {
+  const header = document.createElement("div");
+  header.className = "group-header";
+  const g = groupStatus[group];
+  const state = g ? g.state : "unknown";
+  const label = { up: "Online", degraded: "Degraded", down: "Offline" }[state] || "";
+  header.innerHTML = `
+            <span class="group-name">${group}</span>
+            <span class="m-status">
+                <span class="status-dot ${state}"></span> ${label}
+                ${g ? `<span class="m-meta">${g.up}/${g.members} up</span>` : ""}
+            </span>
+        `;
+  return header;
+}
+
+// 降采样后的聚合记录 (rollup) 代表多次探测
+function sampleCountOf(r) {
+  return r.rollup ? r.rollup.samples : 1;
+}
+
+function okCountOf(r) {
+  if (r.rollup) return r.rollup.ok;
+  return r.success ? 1 : 0;
+}
+
+function sum(records, fn) {
+  return records.reduce((acc, r) => acc + fn(r), 0);
+}
+
+function recordState(r) {
+  if (!r.success) return "DOWN";
+  return r.degraded ? "DEGRADED" : "UP";
+}
+
 function renderGlobalEventLog(data) {
   const allEvents = [];
 
@@ -221,12 +466,16 @@ function renderGlobalEventLog(data) {
       for (let i = 0; i < item.records.length - 1; i++) {
         const curr = item.records[i];
         const prev = item.records[i + 1];
-        if (curr.success !== prev.success) {
+        const currState = recordState(curr);
+        if (currState !== recordState(prev)) {
           allEvents.push({
             target: item.target.name,
-            type: curr.success ? "UP" : "DOWN",
+            type: currState,
             time: new Date(curr.timestamp), // Keep as Date object for sorting
-            msg: curr.message || (curr.success ? "Recovered" : "Unknown Error"),
+            msg:
+              curr.degraded ||
+              curr.message ||
+              (curr.success ? "Recovered" : "Unknown Error"),
           });
         }
       }
@@ -260,40 +509,93 @@ function renderGlobalEventLog(data) {
   });
 }
 
+function focusedCardId() {
+  return decodeURIComponent(location.hash.slice(1));
+}
+
+// 卡片在数据到达后才生成，浏览器不会自动滚动到锚点
+function scrollToFocused() {
+  const card = location.hash && document.getElementById(focusedCardId());
+  if (card) card.scrollIntoView({ block: "center" });
+}
+
+window.addEventListener("hashchange", () => {
+  renderDashboard(monitorData);
+  scrollToFocused();
+});
+
 function startDashboardUpdates() {
   if (eventSource) {
     eventSource.close();
   }
 
-  eventSource = new EventSource("/api/events");
+  const query = API_KEY ? `?api_key=${encodeURIComponent(API_KEY)}` : "";
+  eventSource = new EventSource("api/events" + query);
 
   eventSource.addEventListener("init", (e) => {
     try {
       monitorData = JSON.parse(e.data);
       renderDashboard(monitorData);
+      scrollToFocused();
       document.getElementById("last-updated").innerText = "Connected via SSE";
+      ensureHistory();
     } catch (err) {
       console.error("Failed to parse init data", err);
     }
   });
 
+  eventSource.addEventListener("groups", (e) => {
+    try {
+      groupStatus = {};
+      JSON.parse(e.data).forEach((g) => (groupStatus[g.group] = g));
+      renderDashboard(monitorData);
+    } catch (err) {
+      console.error("Failed to parse groups data", err);
+    }
+  });
+
+  eventSource.addEventListener("group", (e) => {
+    try {
+      const g = JSON.parse(e.data);
+      groupStatus[g.group] = g;
+      renderDashboard(monitorData);
+    } catch (err) {
+      console.error("Failed to parse group data", err);
+    }
+  });
+
+  // 增量更新：新记录插入最前面，并同步当前状态
   eventSource.addEventListener("update", (e) => {
     try {
-      const updatedStatus = JSON.parse(e.data);
+      const update = JSON.parse(e.data);
+      const item = monitorData.find((m) => m.target.id === update.target_id);
+      if (!item) return; // 新目标会先收到 status 事件
+      item.records.unshift(update.record);
+      trimRecords(item);
+      item.current_state = update.state;
+      item.unreachable = update.unreachable;
+      item.uptime_24h = update.uptime_24h;
+      renderDashboard(monitorData);
+    } catch (err) {
+      console.error("Failed to parse update data", err);
+    }
+  });
+
+  // 完整状态：新增或修改的目标、导入历史后
+  eventSource.addEventListener("status", (e) => {
+    try {
+      const status = JSON.parse(e.data);
       const index = monitorData.findIndex(
-        (item) => item.target.id === updatedStatus.target.id,
+        (item) => item.target.id === status.target.id,
       );
       if (index !== -1) {
-        monitorData[index] = updatedStatus;
+        monitorData[index] = status;
       } else {
-        // New target? or reordered? Just push it for now or reload
-        monitorData.push(updatedStatus);
+        monitorData.push(status);
       }
-      // Re-render
       renderDashboard(monitorData);
-      // document.getElementById('last-updated').innerText = 'Last updated: ' + new Date().toLocaleTimeString();
     } catch (err) {
-      console.error("Failed to parse update data", err);
+      console.error("Failed to parse status data", err);
     }
   });
 
@@ -303,6 +605,27 @@ function startDashboardUpdates() {
       "Connection lost, reconnecting...";
     // EventSource automatically reconnects, but we might want to handle visual state
   };
+
+  // 重连时浏览器自动带上 Last-Event-ID，服务端补发缺失的事件而不再下发 init
+  eventSource.onopen = () => {
+    document.getElementById("last-updated").innerText = "Connected via SSE";
+  };
+}
+
+// 与服务端的保留策略一致，长时间打开页面时记录不会无限增长
+function trimRecords(item) {
+  const limit = currentConfig?.data_retention_records;
+  if (limit) {
+    item.records.length = Math.min(item.records.length, limit);
+    return;
+  }
+  const cutoff = Date.now() - retentionDays * 86400 * 1000;
+  while (
+    item.records.length &&
+    new Date(item.records[item.records.length - 1].timestamp) < cutoff
+  ) {
+    item.records.pop();
+  }
 }
 
 function stopDashboardUpdates() {
@@ -315,8 +638,10 @@ function stopDashboardUpdates() {
 // --- Config Logic ---
 async function loadConfig(renderForm = true) {
   try {
-    const res = await fetch("/api/config");
+    const res = await fetch("api/config", { headers: authHeaders() });
+    if (redirectToLogin(res)) return;
     currentConfig = await res.json();
+    configEtag = res.headers.get("ETag");
     if (renderForm) renderConfigForm();
   } catch (e) {
     alert("Failed to load config: " + e.message);
@@ -336,6 +661,8 @@ function renderConfigForm() {
 
   document.getElementById("config-retention").value =
     currentConfig.data_retention_days || 3;
+  document.getElementById("config-retention-records").value =
+    currentConfig.data_retention_records || "";
 }
 
 function addTargetRow() {
@@ -345,7 +672,6 @@ function addTargetRow() {
     host: "localhost",
     port: 22,
     protocol: "TCP",
-    threshold: 3,
   });
 }
 
@@ -354,7 +680,7 @@ function addConfigRow(target) {
   const row = document.createElement("tr");
   row.className = "target-row";
 
-  const protoOptions = ["TCP", "ICMP", "DNS", "HTTP", "HTTPS"]
+  const protoOptions = ["TCP", "ICMP", "DNS", "HTTP", "HTTPS", "VNC", "SMB", "NFS", "EXEC", "DOMAIN", "DNSBL", "WATCHDOG", "PUSH"]
     .map(
       (p) =>
         `<option value="${p}" ${target.protocol === p ? "selected" : ""}>${p}</option>`,
@@ -363,7 +689,11 @@ function addConfigRow(target) {
 
   row.innerHTML = `
         <input type="hidden" class="c-id" value="${target.id}">
+        <td class="drag-handle" title="Drag to reorder">&#x2630;</td>
+        <td><input type="checkbox" class="c-enabled" ${target.enabled !== false ? "checked" : ""}></td>
         <td><input type="text" class="c-name" value="${target.name}"></td>
+        <td><input type="text" class="c-group" value="${target.group || ""}" placeholder="-" style="width: 90px;"></td>
+        <td><input type="checkbox" class="c-public" ${target.public ? "checked" : ""}></td>
         <td>
             <select class="c-proto" onchange="updateRowState(this.closest('tr'), true)">
                 ${protoOptions}
@@ -371,18 +701,98 @@ function addConfigRow(target) {
         </td>
         <td><input type="text" class="c-host" value="${target.host}"></td>
         <td><input type="number" class="c-port" value="${target.port !== null ? target.port : ""}" placeholder="N/A"></td>
-        <td><input type="number" class="c-threshold" value="${target.threshold || 3}" min="1" max="20" style="width: 60px;"></td>
+        <td><input type="number" class="c-threshold" value="${target.threshold ?? ""}" min="1" max="20" placeholder="${currentConfig?.threshold || 3}" style="width: 60px;"></td>
+        <td><input type="number" class="c-interval" value="${target.interval_secs ?? ""}" min="1" placeholder="${currentConfig?.probe_interval_secs || 10}" style="width: 70px;"></td>
         <td><button class="btn btn-danger btn-sm" onclick="this.closest('tr').remove()">Delete</button></td>
     `;
   tbody.appendChild(row);
+  enableRowDrag(row);
   updateRowState(row);
 }
 
+// 拖动行首的把手调整目标顺序，松开后立即通过 /api/targets/reorder 保存，
+// 只提交顺序，不影响表单中其他尚未保存的修改
+let draggedRow = null;
+let orderBeforeDrag = "";
+
+function configRowIds() {
+  return [...document.querySelectorAll("#config-targets-body .c-id")].map(
+    (input) => input.value,
+  );
+}
+
+function enableRowDrag(row) {
+  // 仅在按住把手时可拖动，否则无法在输入框中选择文字
+  const handle = row.querySelector(".drag-handle");
+  handle.addEventListener("mousedown", () => (row.draggable = true));
+  handle.addEventListener("mouseup", () => (row.draggable = false));
+
+  row.addEventListener("dragstart", (e) => {
+    draggedRow = row;
+    orderBeforeDrag = configRowIds().join(",");
+    e.dataTransfer.effectAllowed = "move";
+    row.classList.add("dragging");
+  });
+  row.addEventListener("dragover", (e) => {
+    if (!draggedRow || draggedRow.parentNode !== row.parentNode) return;
+    e.preventDefault();
+    if (draggedRow === row) return;
+    const rect = row.getBoundingClientRect();
+    const after = e.clientY > rect.top + rect.height / 2;
+    row.parentNode.insertBefore(draggedRow, after ? row.nextSibling : row);
+  });
+  row.addEventListener("drop", (e) => e.preventDefault());
+  row.addEventListener("dragend", () => {
+    row.draggable = false;
+    row.classList.remove("dragging");
+    draggedRow = null;
+    if (configRowIds().join(",") !== orderBeforeDrag) saveTargetOrder();
+  });
+}
+
+async function saveTargetOrder() {
+  // 新增但尚未保存的目标随下次保存配置一起提交
+  const known = new Set((currentConfig?.targets || []).map((t) => t.id));
+  const ids = configRowIds().filter((id) => known.has(id));
+  try {
+    const headers = { "Content-Type": "application/json" };
+    if (configEtag) headers["If-Match"] = configEtag;
+    const res = await fetch("api/targets/reorder", {
+      method: "POST",
+      headers: authHeaders(headers),
+      body: JSON.stringify({ ids }),
+    });
+    if (redirectToLogin(res)) return;
+    if (res.status === 412) {
+      alert(
+        "The configuration was changed by someone else since you opened it. " +
+          "It will be reloaded; please re-apply your changes.",
+      );
+      await loadConfig();
+      return;
+    }
+    if (!res.ok) {
+      alert("Failed to save order: " + (await res.text()));
+      return;
+    }
+    configEtag = res.headers.get("ETag");
+
+    const rank = new Map(ids.map((id, i) => [id, i]));
+    const byRank = (a, b) =>
+      (rank.get(a) ?? ids.length) - (rank.get(b) ?? ids.length);
+    currentConfig.targets.sort((a, b) => byRank(a.id, b.id));
+    monitorData.sort((a, b) => byRank(a.target.id, b.target.id));
+    renderDashboard(monitorData);
+  } catch (e) {
+    alert("Network error: " + e.message);
+  }
+}
+
 function updateRowState(row, fromUserChange = false) {
   const proto = row.querySelector(".c-proto").value;
   const portInput = row.querySelector(".c-port");
 
-  if (proto === "ICMP") {
+  if (proto === "ICMP" || proto === "EXEC" || proto === "DOMAIN" || proto === "DNSBL" || proto === "WATCHDOG" || proto === "PUSH") {
     portInput.disabled = true;
     portInput.value = "";
     portInput.placeholder = "N/A";
@@ -439,19 +849,29 @@ async function saveConfig() {
     let portVal = row.querySelector(".c-port").value;
     let port = parseInt(portVal);
 
-    if (proto === "ICMP") {
+    if (proto === "ICMP" || proto === "EXEC" || proto === "DOMAIN" || proto === "DNSBL" || proto === "WATCHDOG" || proto === "PUSH") {
       port = null;
     } else if (isNaN(port)) {
       if (DEFAULT_PORTS[proto]) port = DEFAULT_PORTS[proto];
     }
 
+    // Keep fields the form doesn't edit (advanced options set in config.json)
+    const id = row.querySelector(".c-id").value;
+    const original =
+      (currentConfig?.targets || []).find((t) => t.id === id) || {};
+
     targets.push({
-      id: row.querySelector(".c-id").value,
+      ...original,
+      id: id,
       name: row.querySelector(".c-name").value,
       host: row.querySelector(".c-host").value,
       port: isNaN(port) ? null : port,
       protocol: proto,
-      threshold: parseInt(row.querySelector(".c-threshold").value) || 3,
+      enabled: row.querySelector(".c-enabled").checked,
+      threshold: parseInt(row.querySelector(".c-threshold").value) || null,
+      interval_secs: parseInt(row.querySelector(".c-interval").value) || null,
+      group: row.querySelector(".c-group").value.trim() || null,
+      public: row.querySelector(".c-public").checked,
     });
   });
 
@@ -459,8 +879,12 @@ async function saveConfig() {
   const webhooks = [];
   document.querySelectorAll("#config-webhooks-body tr").forEach((row) => {
     const tmplVal = row.querySelector(".w-template").value.trim();
+    const id = row.querySelector(".w-id").value;
+    const original =
+      (currentConfig?.alert?.webhooks || []).find((w) => w.id === id) || {};
     webhooks.push({
-      id: row.querySelector(".w-id").value,
+      ...original,
+      id: id,
       name: row.querySelector(".w-name").value,
       url: row.querySelector(".w-url").value,
       template: tmplVal ? tmplVal : null,
@@ -469,26 +893,70 @@ async function saveConfig() {
   });
 
   const newConfig = {
+    ...currentConfig,
     targets: targets,
     alert: {
+      ...currentConfig?.alert,
       enabled: true,
       webhooks: webhooks,
     },
     data_retention_days:
       parseInt(document.getElementById("config-retention").value) || 3,
+    // 填写后按条数保留，代替保留天数
+    data_retention_records:
+      parseInt(document.getElementById("config-retention-records").value) ||
+      null,
   };
 
   try {
-    const res = await fetch("/api/config", {
+    // 保存前先校验：有错误时不提交，只有警告时由用户确认
+    const check = await fetch("api/config/validate", {
       method: "POST",
-      headers: { "Content-Type": "application/json" },
+      headers: authHeaders({ "Content-Type": "application/json" }),
       body: JSON.stringify(newConfig),
     });
+    if (redirectToLogin(check)) return;
+    if (check.ok) {
+      const report = await check.json();
+      const describe = (issues) =>
+        issues.map((i) => `${i.path || "(root)"}: ${i.message}`).join("\n");
+      if (!report.valid) {
+        alert("Configuration has errors:\n" + describe(report.errors));
+        return;
+      }
+      if (
+        report.warnings.length > 0 &&
+        !confirm(
+          "Configuration warnings:\n" +
+            describe(report.warnings) +
+            "\n\nSave anyway?",
+        )
+      )
+        return;
+    }
+
+    const headers = { "Content-Type": "application/json" };
+    if (configEtag) headers["If-Match"] = configEtag;
+    const res = await fetch("api/config", {
+      method: "POST",
+      headers: authHeaders(headers),
+      body: JSON.stringify(newConfig),
+    });
+    if (redirectToLogin(res)) return;
+    if (res.status === 412) {
+      alert(
+        "The configuration was changed by someone else since you opened it. " +
+          "It will be reloaded; please re-apply your changes.",
+      );
+      await loadConfig();
+      return;
+    }
     const result = await res.json();
 
     if (result.success) {
       alert("Configuration saved successfully!");
       currentConfig = newConfig;
+      configEtag = res.headers.get("ETag");
       retentionDays = newConfig.data_retention_days;
       // Update dropdown
       const retentionOpt = document.querySelector('option[value="retention"]');